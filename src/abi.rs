@@ -186,6 +186,20 @@ sol! {
         }
     }
 
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct QuoteExactSingleParams {
+        PoolKey poolKey;
+        bool zeroForOne;
+        uint128 exactAmount;
+        bytes hookData;
+    }
+
+    interface IV4Quoter {
+        function quoteExactInputSingle(QuoteExactSingleParams memory params)
+            external
+            returns (uint256 amountOut, uint256 gasEstimate);
+    }
+
     interface IPositionManager {
         function initializePool(PoolKey calldata key, uint160 sqrtPriceX96) external payable returns (int24);
 
@@ -200,6 +214,51 @@ sol! {
             external
             payable;
     }
+
+    interface IV3NonfungiblePositionManager {
+        #[derive(Debug, Default, PartialEq, Eq)]
+        struct DecreaseLiquidityParams {
+            uint256 tokenId;
+            uint128 liquidity;
+            uint256 amount0Min;
+            uint256 amount1Min;
+            uint256 deadline;
+        }
+
+        #[derive(Debug, Default, PartialEq, Eq)]
+        struct CollectParams {
+            uint256 tokenId;
+            address recipient;
+            uint128 amount0Max;
+            uint128 amount1Max;
+        }
+
+        function decreaseLiquidity(DecreaseLiquidityParams calldata params)
+            external
+            payable
+            returns (uint256 amount0, uint256 amount1);
+
+        function collect(CollectParams calldata params) external payable returns (uint256 amount0, uint256 amount1);
+
+        function burn(uint256 tokenId) external payable;
+    }
+
+    interface IUniversalRouter {
+        function execute(bytes calldata commands, bytes[] calldata inputs, uint256 deadline) external payable;
+    }
+}
+
+/// Formats the key as the canonical JSON it is serialized to, with lowercase hex addresses and
+/// decimal fee and tick spacing
+impl core::fmt::Display for PoolKey {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            r#"{{"currency0":"{:#x}","currency1":"{:#x}","fee":{},"tickSpacing":{},"hooks":"{:#x}"}}"#,
+            self.currency0, self.currency1, self.fee, self.tickSpacing, self.hooks
+        )
+    }
 }
 
 #[cfg(feature = "extensions")]
@@ -210,6 +269,17 @@ alloy::sol! {
         function extsload(bytes32 startSlot, uint256 nSlots) external view returns (bytes32[] memory values);
         function extsload(bytes32[] calldata slots) external view returns (bytes32[] memory values);
     }
+
+    #[sol(rpc)]
+    interface IERC20Metadata {
+        function decimals() external view returns (uint8);
+    }
+
+    interface IPoolManager {
+        event ModifyLiquidity(
+            bytes32 indexed id, address indexed sender, int24 tickLower, int24 tickUpper, int256 liquidityDelta, bytes32 salt
+        );
+    }
 }
 
 #[cfg(all(test, feature = "extensions"))]
@@ -1,9 +1,13 @@
 pub mod pool;
 pub mod position;
 pub mod route;
+#[cfg(feature = "serde")]
+pub mod serialization;
 pub mod trade;
 
 pub use pool::*;
 pub use position::*;
 pub use route::*;
+#[cfg(feature = "serde")]
+pub use serialization::*;
 pub use trade::*;
@@ -1,10 +1,70 @@
-use crate::prelude::{Error, *};
-use alloy_primitives::{aliases::U24, keccak256, uint, Address, ChainId, B256, I256, U160};
+use crate::prelude::{tick_to_price, Error, *};
+use alloc::{sync::Arc, vec, vec::Vec};
+use alloy_primitives::{
+    aliases::{I24, U24},
+    keccak256, uint, Address, ChainId, B256, I256, U160, U256,
+};
 use alloy_sol_types::SolValue;
 use uniswap_sdk_core::prelude::*;
 use uniswap_v3_sdk::prelude::*;
 
 pub const DYANMIC_FEE_FLAG: U24 = uint!(0x800000_U24);
+/// The maximum protocol fee in pips for each swap direction, i.e. 0.1%
+pub const MAX_PROTOCOL_FEE: u32 = 1000;
+/// The minimum tick spacing of a pool
+pub const MIN_TICK_SPACING: i32 = 1;
+/// The maximum tick spacing of a pool, i.e. `type(int16).max`
+pub const MAX_TICK_SPACING: i32 = i16::MAX as i32;
+
+/// Computes the ID of a pool from its key, whose currencies are already sorted
+///
+/// ## Arguments
+///
+/// * `key`: The key of the pool
+#[inline]
+#[must_use]
+pub fn pool_id_from_pool_key(key: &PoolKey) -> B256 {
+    keccak256(key.abi_encode())
+}
+
+/// Simulates the effect of a pool's hook on swaps, e.g. for hooks with `BeforeSwapReturnsDelta`
+/// or `AfterSwapReturnsDelta` permissions that implement custom curves or take fees
+pub trait HookSwapSimulator<I: TickIndex>: core::fmt::Debug + Send + Sync {
+    /// Simulates a swap through the pool, including the hook's deltas, in place of swapping
+    /// through the pool's liquidity locally
+    ///
+    /// ## Arguments
+    ///
+    /// * `zero_for_one`: Whether the amount in is token0 or token1
+    /// * `amount_specified`: The amount of the swap, positive for exact input and negative for
+    ///   exact output
+    /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit of the swap, if any
+    /// * `fee`: The swap fee the pool manager would charge, i.e. the resolved LP fee with the
+    ///   protocol fee of the swap direction folded in
+    /// * `state`: The state of the pool at the start of the swap
+    ///
+    /// returns: The swap state after the swap
+    fn simulate_swap(
+        &self,
+        zero_for_one: bool,
+        amount_specified: I256,
+        sqrt_price_limit_x96: Option<U160>,
+        fee: U24,
+        state: SwapState<I>,
+    ) -> Result<SwapState<I>, Error>;
+}
+
+/// Resolves the LP fee of a dynamic fee pool for a swap step, given the state at the start of the
+/// step
+#[derive(Clone)]
+pub struct DynamicFeeResolver<I: TickIndex>(pub Arc<dyn Fn(&SwapState<I>) -> U24 + Send + Sync>);
+
+impl<I: TickIndex> core::fmt::Debug for DynamicFeeResolver<I> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("DynamicFeeResolver")
+    }
+}
 
 /// Represents a V4 pool
 #[derive(Clone, Debug)]
@@ -23,6 +83,13 @@ where
     pub tick_data_provider: TP,
     pub pool_key: PoolKey,
     pub pool_id: B256,
+    /// Simulates the hook's effect on swaps, if the hook impacts swaps
+    pub hook_swap_simulator: Option<Arc<dyn HookSwapSimulator<TP::Index>>>,
+    /// Resolves the LP fee of swaps if the pool has a dynamic fee
+    pub dynamic_fee_resolver: Option<DynamicFeeResolver<TP::Index>>,
+    /// The protocol fee as stored in slot0, with the fee of zero for one swaps in the lower 12
+    /// bits and the fee of one for zero swaps in the upper 12 bits
+    pub protocol_fee: U24,
 }
 
 impl<TP> PartialEq for Pool<TP>
@@ -39,9 +106,29 @@ where
             && self.hooks == other.hooks
             && self.liquidity == other.liquidity
             && self.tick_current == other.tick_current
+            && self.protocol_fee == other.protocol_fee
     }
 }
 
+/// The result of an exact input swap through a pool, with metadata for gas estimation and
+/// analytics, see [`Pool::get_output_amount_detailed`]
+#[derive(Clone, Debug)]
+pub struct SwapOutput<TP>
+where
+    TP: TickDataProvider,
+{
+    /// The output amount of the swap
+    pub output_amount: CurrencyAmount<Currency>,
+    /// The pool with state updated after the swap
+    pub pool_after: Pool<TP>,
+    /// The number of initialized ticks crossed by the swap
+    pub ticks_crossed: usize,
+    /// The tick of the pool after the swap
+    pub tick_after: TP::Index,
+    /// The sqrt price of the pool after the swap
+    pub sqrt_price_after: U160,
+}
+
 impl Pool {
     fn sort_currency(
         currency_a: &Currency,
@@ -84,17 +171,13 @@ impl Pool {
         tick_spacing: I,
         hooks: Address,
     ) -> Result<B256, Error> {
-        let (currency0_addr, currency1_addr) = Self::sort_currency(currency_a, currency_b)?;
-        Ok(keccak256(
-            (
-                currency0_addr,
-                currency1_addr,
-                U24::from(fee),
-                tick_spacing.to_i24(),
-                hooks,
-            )
-                .abi_encode(),
-        ))
+        Ok(pool_id_from_pool_key(&Self::get_pool_key(
+            currency_a,
+            currency_b,
+            fee,
+            tick_spacing,
+            hooks,
+        )?))
     }
 
     /// Constructs a pool
@@ -130,6 +213,46 @@ impl Pool {
             NoTickDataProvider,
         )
     }
+
+    /// Constructs a pool from the decimal `sqrtPrice` and `liquidity` strings returned by the
+    /// subgraph
+    ///
+    /// ## Arguments
+    ///
+    /// * `currency_a`: One of the currencies in the pool
+    /// * `currency_b`: The other currency in the pool
+    /// * `fee`: The fee in hundredths of a bips of the input amount of every swap that is collected
+    ///   by the pool
+    /// * `tick_spacing`: The tickSpacing of the pool
+    /// * `hooks`: The address of the hook contract
+    /// * `sqrt_price_str`: The sqrt of the current ratio of amounts of currency1 to currency0 as a
+    ///   decimal string
+    /// * `liquidity_str`: The current value of in range liquidity as a decimal string
+    #[inline]
+    pub fn new_from_strings(
+        currency_a: Currency,
+        currency_b: Currency,
+        fee: U24,
+        tick_spacing: <NoTickDataProvider as TickDataProvider>::Index,
+        hooks: Address,
+        sqrt_price_str: &str,
+        liquidity_str: &str,
+    ) -> Result<Self, Error> {
+        let sqrt_price_x96 =
+            U160::from_str_radix(sqrt_price_str, 10).map_err(|_| Error::ParseError("sqrtPrice"))?;
+        let liquidity = liquidity_str
+            .parse::<u128>()
+            .map_err(|_| Error::ParseError("liquidity"))?;
+        Self::new(
+            currency_a,
+            currency_b,
+            fee,
+            tick_spacing,
+            hooks,
+            sqrt_price_x96,
+            liquidity,
+        )
+    }
 }
 
 impl<TP: TickDataProvider> Pool<TP> {
@@ -162,6 +285,9 @@ impl<TP: TickDataProvider> Pool<TP> {
         if fee == DYANMIC_FEE_FLAG {
             assert_ne!(hooks, Address::ZERO, "Dynamic fee pool requires a hook");
         }
+        if !(MIN_TICK_SPACING..=MAX_TICK_SPACING).contains(&tick_spacing.to_i24().as_i32()) {
+            return Err(Error::InvalidTickSpacing);
+        }
         let pool_key =
             Pool::get_pool_key(&currency_a, &currency_b, fee, tick_spacing.to_i24(), hooks)?;
         let pool_id = Pool::get_pool_id(&currency_a, &currency_b, fee, tick_spacing, hooks)?;
@@ -183,9 +309,113 @@ impl<TP: TickDataProvider> Pool<TP> {
             tick_data_provider,
             pool_key,
             pool_id,
+            hook_swap_simulator: None,
+            dynamic_fee_resolver: None,
+            protocol_fee: U24::ZERO,
         })
     }
 
+    /// Registers a resolver for the LP fee of a pool with [`DYANMIC_FEE_FLAG`], which is otherwise
+    /// set by the hook on-chain and unknown to the pool
+    ///
+    /// ## Arguments
+    ///
+    /// * `resolver`: Returns the LP fee for a swap step given the state at the start of the step
+    #[inline]
+    #[must_use]
+    pub fn with_dynamic_fee_resolver(
+        mut self,
+        resolver: impl Fn(&SwapState<TP::Index>) -> U24 + Send + Sync + 'static,
+    ) -> Self {
+        self.dynamic_fee_resolver = Some(DynamicFeeResolver(Arc::new(resolver)));
+        self
+    }
+
+    /// Sets the protocol fee levied on swaps on top of the LP fee
+    ///
+    /// ## Arguments
+    ///
+    /// * `protocol_fee`: The protocol fee as stored in slot0, with the fee of zero for one swaps
+    ///   in the lower 12 bits and the fee of one for zero swaps in the upper 12 bits
+    ///
+    /// ## Returns
+    ///
+    /// The pool, or [`Error::InvalidProtocolFee`] if the fee of either direction exceeds
+    /// [`MAX_PROTOCOL_FEE`]
+    #[inline]
+    pub fn with_protocol_fee(mut self, protocol_fee: U24) -> Result<Self, Error> {
+        let protocol_fee_u32 = protocol_fee.to::<u32>();
+        if (protocol_fee_u32 & 0xfff) > MAX_PROTOCOL_FEE
+            || (protocol_fee_u32 >> 12) > MAX_PROTOCOL_FEE
+        {
+            return Err(Error::InvalidProtocolFee(protocol_fee));
+        }
+        self.protocol_fee = protocol_fee;
+        Ok(self)
+    }
+
+    /// Whether the pool manager would accept the pool's hook address, mirroring
+    /// `Hooks.isValidHookAddress`
+    ///
+    /// A hook address is valid if its return delta flags are only set together with the flags of
+    /// the hooks returning them, and, unless the pool has a dynamic fee, at least one of its 14
+    /// flag bits is set. An address whose only set bits are above the flags is therefore invalid
+    /// for a static fee pool.
+    #[inline]
+    #[must_use]
+    pub fn hook_flags_valid(&self) -> bool {
+        if self.hooks == Address::ZERO {
+            return self.fee != DYANMIC_FEE_FLAG;
+        }
+        has_valid_delta_flags(self.hooks)
+            && (self.fee == DYANMIC_FEE_FLAG
+                || permissions(self.hooks) != HookPermissions::default())
+    }
+
+    /// Returns the LP fee charged on the first step of a swap starting at the current pool state,
+    /// resolving the dynamic fee if the pool has one
+    #[inline]
+    pub fn effective_fee(&self) -> Result<U24, Error> {
+        self.swap_fee(&self.initial_swap_state(I256::ZERO))
+    }
+
+    /// Returns the amount left after deducting the LP fee of a single hop through the pool, i.e.
+    /// `amount * (1e6 - fee) / 1e6` rounded down
+    ///
+    /// The fee is the [`Self::effective_fee`]. The protocol fee is not deducted.
+    ///
+    /// ## Arguments
+    ///
+    /// * `amount`: The raw amount before fees
+    ///
+    /// ## Returns
+    ///
+    /// The amount after the fee, wrapped in a `Result` because the fee may not be resolvable.
+    /// Returns [`Error::InvalidFee`] if the pool has a dynamic fee without a
+    /// [`DynamicFeeResolver`], or if the resolved fee is not below 100%.
+    #[inline]
+    pub fn amount_after_fee(&self, amount: U256) -> Result<U256, Error> {
+        const PIPS: U256 = uint!(1_000_000_U256);
+        let fee = self.effective_fee()?;
+        Ok(mul_div(amount, PIPS - U256::from(fee), PIPS)?)
+    }
+
+    /// Registers a simulator for the pool's hook, so that swaps through a pool whose hook impacts
+    /// swaps can be quoted instead of failing with [`Error::UnsupportedHook`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `simulator`: The simulator to delegate swaps to
+    #[inline]
+    #[must_use]
+    pub fn with_hook_swap_simulator(
+        mut self,
+        simulator: Arc<dyn HookSwapSimulator<TP::Index>>,
+    ) -> Self {
+        self.hook_swap_simulator = Some(simulator);
+        self
+    }
+
     #[inline]
     pub const fn token0(&self) -> &Currency {
         &self.currency0
@@ -211,6 +441,20 @@ impl<TP: TickDataProvider> Pool<TP> {
         self.involves_currency(currency)
     }
 
+    /// Returns true if both pools are the same market, i.e. have the same pool id, regardless of
+    /// their swap state
+    ///
+    /// Unlike `==`, snapshots of a pool at different blocks are the same market.
+    ///
+    /// ## Arguments
+    ///
+    /// * `other`: The pool to compare with
+    #[inline]
+    #[must_use]
+    pub fn same_market<TP2: TickDataProvider>(&self, other: &Pool<TP2>) -> bool {
+        self.pool_id == other.pool_id
+    }
+
     /// v4-only involvesToken convenience method, used for mixed route ETH <-> WETH connection only
     #[inline]
     pub fn v4_involves_token(&self, currency: &impl BaseCurrency) -> bool {
@@ -260,6 +504,20 @@ impl<TP: TickDataProvider> Pool<TP> {
         self.currency1_price()
     }
 
+    /// Returns both [`Self::currency0_price`] and [`Self::currency1_price`], which are reciprocals
+    /// of each other
+    #[inline]
+    pub fn prices(&self) -> (Price<Currency, Currency>, Price<Currency, Currency>) {
+        let currency0_price = self.currency0_price();
+        let currency1_price = self.currency1_price();
+        debug_assert!(
+            currency0_price.numerator == currency1_price.denominator
+                && currency0_price.denominator == currency1_price.numerator,
+            "PRICES"
+        );
+        (currency0_price, currency1_price)
+    }
+
     /// Return the price of the given currency in terms of the other currency in the pool.
     ///
     /// ## Arguments
@@ -279,12 +537,59 @@ impl<TP: TickDataProvider> Pool<TP> {
         }
     }
 
+    /// Returns the prices in terms of currency0 at the lower and upper bounds of the tick spacing
+    /// interval containing the current tick, i.e. the prices at which the pool moves out of it in
+    /// each direction
+    #[inline]
+    pub fn tick_boundary_prices(
+        &self,
+    ) -> Result<(Price<Currency, Currency>, Price<Currency, Currency>), Error> {
+        let tick_spacing = self.tick_spacing.to_i24().as_i32();
+        let tick_lower =
+            self.tick_current.to_i24().as_i32().div_euclid(tick_spacing) * tick_spacing;
+        let tick_upper = tick_lower + tick_spacing;
+        Ok((
+            tick_to_price(
+                self.currency0.clone(),
+                self.currency1.clone(),
+                tick_lower.to_i24(),
+            )?,
+            tick_to_price(
+                self.currency0.clone(),
+                self.currency1.clone(),
+                tick_upper.to_i24(),
+            )?,
+        ))
+    }
+
     /// Returns the chain ID of the currencies in the pool.
     #[inline]
     pub fn chain_id(&self) -> ChainId {
         self.currency0.chain_id()
     }
 
+    /// Returns the sqrt price the pool reaches after swapping `amount_in` without crossing the
+    /// current tick
+    ///
+    /// ## Note
+    ///
+    /// The amount is assumed to be net of the LP fee and small enough for the swap to stay within
+    /// the current tick, so the pool's current liquidity applies throughout.
+    ///
+    /// ## Arguments
+    ///
+    /// * `zero_for_one`: Whether the amount in is currency0 or currency1
+    /// * `amount_in`: The amount of input currency swapped, after the fee
+    #[inline]
+    pub fn sqrt_price_after_input_single_tick(
+        &self,
+        zero_for_one: bool,
+        amount_in: U256,
+    ) -> Result<U160, Error> {
+        get_next_sqrt_price_from_input(self.sqrt_price_x96, self.liquidity, amount_in, zero_for_one)
+            .map_err(Error::V3)
+    }
+
     /// Executes a swap
     ///
     /// ## Arguments
@@ -295,28 +600,174 @@ impl<TP: TickDataProvider> Pool<TP> {
     /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit. If zero for one, the price cannot be
     ///   less than this value after the swap. If one for zero, the price cannot be greater than
     ///   this value after the swap
+    ///
+    /// returns: The swap state and the initialized ticks crossed, in the order they are crossed
     async fn swap(
         &self,
         zero_for_one: bool,
         amount_specified: I256,
         sqrt_price_limit_x96: Option<U160>,
-    ) -> Result<SwapState<TP::Index>, Error> {
-        if !self.hook_impacts_swap() {
-            Ok(v3_swap(
-                self.fee,
-                self.sqrt_price_x96,
-                self.tick_current,
-                self.liquidity,
-                self.tick_spacing,
-                &self.tick_data_provider,
+    ) -> Result<(SwapState<TP::Index>, Vec<TP::Index>), Error> {
+        if self.hook_impacts_swap() {
+            let simulator =
+                self.hook_swap_simulator
+                    .as_ref()
+                    .ok_or_else(|| Error::UnsupportedHook {
+                        address: self.hooks,
+                        permissions: permissions(self.hooks),
+                    })?;
+            let initial_state = self.initial_swap_state(amount_specified);
+            let lp_fee = self.swap_fee(&initial_state)?;
+            let fee = self.fee_with_protocol_fee(zero_for_one, lp_fee)?;
+            // the simulator replaces the local swap, so the crossed ticks are unknown
+            let state = simulator.simulate_swap(
                 zero_for_one,
                 amount_specified,
                 sqrt_price_limit_x96,
-            )
-            .await?)
+                fee,
+                initial_state,
+            )?;
+            return Ok((state, Vec::new()));
+        }
+        self.swap_steps(zero_for_one, amount_specified, sqrt_price_limit_x96)
+            .await
+    }
+
+    /// The swap loop of `v3_swap`, also recording the initialized ticks crossed and resolving the
+    /// swap fee for each step
+    async fn swap_steps(
+        &self,
+        zero_for_one: bool,
+        amount_specified: I256,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<(SwapState<TP::Index>, Vec<TP::Index>), Error> {
+        let sqrt_price_limit_x96 = sqrt_price_limit_x96.unwrap_or(if zero_for_one {
+            MIN_SQRT_RATIO + U160::from(1)
+        } else {
+            MAX_SQRT_RATIO - U160::from(1)
+        });
+        if zero_for_one {
+            assert!(sqrt_price_limit_x96 > MIN_SQRT_RATIO, "RATIO_MIN");
+            assert!(sqrt_price_limit_x96 < self.sqrt_price_x96, "RATIO_CURRENT");
+        } else {
+            assert!(sqrt_price_limit_x96 < MAX_SQRT_RATIO, "RATIO_MAX");
+            assert!(sqrt_price_limit_x96 > self.sqrt_price_x96, "RATIO_CURRENT");
+        }
+
+        let exact_input = amount_specified >= I256::ZERO;
+        let mut state = self.initial_swap_state(amount_specified);
+        let mut crossed_ticks = Vec::new();
+        while !state.amount_specified_remaining.is_zero()
+            && state.sqrt_price_x96 != sqrt_price_limit_x96
+        {
+            let sqrt_price_start_x96 = state.sqrt_price_x96;
+            let fee = self.fee_with_protocol_fee(zero_for_one, self.swap_fee(&state)?)?;
+            let (tick_next, initialized) = self
+                .tick_data_provider
+                .next_initialized_tick_within_one_word(
+                    state.tick_current,
+                    zero_for_one,
+                    self.tick_spacing,
+                )
+                .await?;
+            let tick_next = tick_next.to_i24().clamp(MIN_TICK, MAX_TICK);
+            let sqrt_price_next_x96 = get_sqrt_ratio_at_tick(tick_next)?;
+            let sqrt_price_target_x96 = if zero_for_one {
+                sqrt_price_next_x96.max(sqrt_price_limit_x96)
+            } else {
+                sqrt_price_next_x96.min(sqrt_price_limit_x96)
+            };
+            let (sqrt_price_x96, amount_in, amount_out, fee_amount) = compute_swap_step(
+                state.sqrt_price_x96,
+                sqrt_price_target_x96,
+                state.liquidity,
+                state.amount_specified_remaining,
+                fee,
+            )?;
+            state.sqrt_price_x96 = sqrt_price_x96;
+            if exact_input {
+                state.amount_specified_remaining -= I256::from_raw(amount_in + fee_amount);
+                state.amount_calculated -= I256::from_raw(amount_out);
+            } else {
+                state.amount_specified_remaining += I256::from_raw(amount_out);
+                state.amount_calculated += I256::from_raw(amount_in + fee_amount);
+            }
+
+            if state.sqrt_price_x96 == sqrt_price_next_x96 {
+                // if the tick is initialized, run the tick transition
+                if initialized {
+                    let mut liquidity_net = self
+                        .tick_data_provider
+                        .get_tick(TP::Index::from_i24(tick_next))
+                        .await?
+                        .liquidity_net;
+                    if zero_for_one {
+                        liquidity_net = -liquidity_net;
+                    }
+                    state.liquidity = add_delta(state.liquidity, liquidity_net)?;
+                    crossed_ticks.push(TP::Index::from_i24(tick_next));
+                }
+                state.tick_current = TP::Index::from_i24(if zero_for_one {
+                    tick_next - I24::ONE
+                } else {
+                    tick_next
+                });
+            } else if state.sqrt_price_x96 != sqrt_price_start_x96 {
+                // recompute unless we're on a lower tick boundary (i.e. already transitioned
+                // ticks), and haven't moved
+                state.tick_current =
+                    TP::Index::from_i24(state.sqrt_price_x96.get_tick_at_sqrt_ratio()?);
+            }
+        }
+        Ok((state, crossed_ticks))
+    }
+
+    const fn initial_swap_state(&self, amount_specified: I256) -> SwapState<TP::Index> {
+        SwapState {
+            amount_specified_remaining: amount_specified,
+            amount_calculated: I256::ZERO,
+            sqrt_price_x96: self.sqrt_price_x96,
+            tick_current: self.tick_current,
+            liquidity: self.liquidity,
+        }
+    }
+
+    /// The LP fee of a swap step starting at the given state, resolved with the
+    /// [`DynamicFeeResolver`] if the pool has a dynamic fee
+    fn swap_fee(&self, state: &SwapState<TP::Index>) -> Result<U24, Error> {
+        let fee = if self.fee == DYANMIC_FEE_FLAG {
+            match &self.dynamic_fee_resolver {
+                Some(DynamicFeeResolver(resolver)) => resolver(state),
+                None => return Err(Error::InvalidFee(self.fee)),
+            }
+        } else {
+            self.fee
+        };
+        if fee >= uint!(1_000_000_U24) {
+            return Err(Error::InvalidFee(fee));
+        }
+        Ok(fee)
+    }
+
+    /// Folds the protocol fee of the swap direction into the LP fee, as `ProtocolFeeLibrary`'s
+    /// `calculateSwapFee` does on-chain. The protocol fee is taken first and the LP fee applies to
+    /// the remainder, i.e. `protocolFee + lpFee * (1e6 - protocolFee) / 1e6`.
+    fn fee_with_protocol_fee(&self, zero_for_one: bool, lp_fee: U24) -> Result<U24, Error> {
+        let protocol_fee = self.protocol_fee.to::<u32>();
+        let protocol_fee = if zero_for_one {
+            protocol_fee & 0xfff
         } else {
-            Err(Error::UnsupportedHook)
+            protocol_fee >> 12
+        };
+        if protocol_fee == 0 {
+            return Ok(lp_fee);
         }
+        let lp_fee = lp_fee.to::<u32>();
+        let fee = protocol_fee + lp_fee - protocol_fee * lp_fee / 1_000_000;
+        if fee >= 1_000_000 {
+            return Err(Error::InvalidFee(U24::from(fee)));
+        }
+        Ok(U24::from(fee))
     }
 
     const fn hook_impacts_swap(&self) -> bool {
@@ -332,7 +783,8 @@ impl<TP: Clone + TickDataProvider> Pool<TP> {
     ///
     /// ## Note
     ///
-    /// Works only for vanilla hookless v3 pools, otherwise throws an error
+    /// Works only for pools whose hook doesn't impact swaps, unless a [`HookSwapSimulator`] is
+    /// registered, otherwise throws an error
     ///
     /// ## Arguments
     ///
@@ -346,19 +798,134 @@ impl<TP: Clone + TickDataProvider> Pool<TP> {
         input_amount: &CurrencyAmount<impl BaseCurrency>,
         sqrt_price_limit_x96: Option<U160>,
     ) -> Result<(CurrencyAmount<Currency>, Self), Error> {
+        let SwapOutput {
+            output_amount,
+            pool_after,
+            ..
+        } = self
+            .get_output_amount_detailed(input_amount, sqrt_price_limit_x96)
+            .await?;
+        Ok((output_amount, pool_after))
+    }
+
+    /// Given an input amount of a token, return the computed output amount and the pool state
+    /// after the trade, along with the number of initialized ticks crossed, counted as the swap
+    /// crosses them
+    ///
+    /// ## Note
+    ///
+    /// Works only for pools whose hook doesn't impact swaps, unless a [`HookSwapSimulator`] is
+    /// registered, otherwise throws an error
+    ///
+    /// ## Arguments
+    ///
+    /// * `input_amount`: The input amount for which to quote the output amount
+    /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit
+    #[inline]
+    pub async fn get_output_amount_detailed(
+        &self,
+        input_amount: &CurrencyAmount<impl BaseCurrency>,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<SwapOutput<TP>, Error> {
+        let (output_amount, _, pool_after, ticks_crossed) = self
+            .exact_input_swap(input_amount, sqrt_price_limit_x96)
+            .await?;
+        Ok(SwapOutput {
+            output_amount,
+            ticks_crossed,
+            tick_after: pool_after.tick_current,
+            sqrt_price_after: pool_after.sqrt_price_x96,
+            pool_after,
+        })
+    }
+
+    /// Returns the initialized ticks crossed by a swap through the pool, in the order they are
+    /// crossed
+    ///
+    /// ## Arguments
+    ///
+    /// * `zero_for_one`: Whether the amount in is token0 or token1
+    /// * `amount_specified`: The amount of the swap, which implicitly configures the swap as exact
+    ///   input (positive), or exact output (negative)
+    #[inline]
+    pub async fn ticks_crossed_by_swap(
+        &self,
+        zero_for_one: bool,
+        amount_specified: I256,
+    ) -> Result<Vec<TP::Index>, Error> {
+        let (
+            SwapState {
+                amount_specified_remaining,
+                ..
+            },
+            crossed_ticks,
+        ) = self.swap(zero_for_one, amount_specified, None).await?;
+        if !amount_specified_remaining.is_zero() {
+            return Err(Error::InsufficientLiquidity);
+        }
+        Ok(crossed_ticks)
+    }
+
+    /// Given an input amount of a token and a price limit, return the computed output amount, the
+    /// part of the input amount actually consumed before the price limit was reached, and a pool
+    /// with state updated after the trade
+    ///
+    /// ## Note
+    ///
+    /// Works only for pools whose hook doesn't impact swaps, unless a [`HookSwapSimulator`] is
+    /// registered, otherwise throws an error
+    ///
+    /// ## Arguments
+    ///
+    /// * `input_amount`: The input amount for which to quote the output amount
+    /// * `sqrt_price_limit_x96`: The Q64.96 sqrt price limit. If zero for one, the price cannot be
+    ///   less than this value after the swap. If one for zero, the price cannot be greater than
+    ///   this value after the swap
+    ///
+    /// returns: The output amount, the consumed input amount and the pool with updated state
+    #[inline]
+    pub async fn get_output_amount_with_price_limit(
+        &self,
+        input_amount: &CurrencyAmount<impl BaseCurrency>,
+        sqrt_price_limit_x96: U160,
+    ) -> Result<(CurrencyAmount<Currency>, CurrencyAmount<Currency>, Self), Error> {
+        let (output_amount, consumed_amount, pool_after, _) = self
+            .exact_input_swap(input_amount, Some(sqrt_price_limit_x96))
+            .await?;
+        Ok((output_amount, consumed_amount, pool_after))
+    }
+
+    /// Swaps an exact input amount, returning the output amount, the consumed input amount, the
+    /// pool after the swap and the number of initialized ticks crossed
+    async fn exact_input_swap(
+        &self,
+        input_amount: &CurrencyAmount<impl BaseCurrency>,
+        sqrt_price_limit_x96: Option<U160>,
+    ) -> Result<
+        (
+            CurrencyAmount<Currency>,
+            CurrencyAmount<Currency>,
+            Self,
+            usize,
+        ),
+        Error,
+    > {
         if !self.involves_currency(&input_amount.currency) {
             return Err(Error::InvalidCurrency);
         }
 
         let zero_for_one = input_amount.currency.equals(&self.currency0);
 
-        let SwapState {
-            amount_specified_remaining,
-            amount_calculated: output_amount,
-            sqrt_price_x96,
-            liquidity,
-            ..
-        } = self
+        let (
+            SwapState {
+                amount_specified_remaining,
+                amount_calculated: output_amount,
+                sqrt_price_x96,
+                liquidity,
+                ..
+            },
+            crossed_ticks,
+        ) = self
             .swap(
                 zero_for_one,
                 I256::from_big_int(input_amount.quotient()),
@@ -370,19 +937,24 @@ impl<TP: Clone + TickDataProvider> Pool<TP> {
             return Err(Error::InsufficientLiquidity);
         }
 
-        let output_currency = if zero_for_one {
-            self.currency1.clone()
+        let (input_currency, output_currency) = if zero_for_one {
+            (self.currency0.clone(), self.currency1.clone())
         } else {
-            self.currency0.clone()
+            (self.currency1.clone(), self.currency0.clone())
         };
         Ok((
             CurrencyAmount::from_raw_amount(output_currency, -output_amount.to_big_int())?,
+            CurrencyAmount::from_raw_amount(
+                input_currency,
+                input_amount.quotient() - amount_specified_remaining.to_big_int(),
+            )?,
             Self {
                 sqrt_price_x96,
                 tick_current: TP::Index::from_i24(sqrt_price_x96.get_tick_at_sqrt_ratio()?),
                 liquidity,
                 ..self.clone()
             },
+            crossed_ticks.len(),
         ))
     }
 
@@ -391,7 +963,8 @@ impl<TP: Clone + TickDataProvider> Pool<TP> {
     ///
     /// ## Note
     ///
-    /// Works only for vanilla hookless v3 pools, otherwise throws an error
+    /// Works only for pools whose hook doesn't impact swaps, unless a [`HookSwapSimulator`] is
+    /// registered, otherwise throws an error
     ///
     /// ## Arguments
     ///
@@ -413,13 +986,16 @@ impl<TP: Clone + TickDataProvider> Pool<TP> {
 
         let zero_for_one = output_amount.currency.equals(&self.currency1);
 
-        let SwapState {
-            amount_specified_remaining,
-            amount_calculated: input_amount,
-            sqrt_price_x96,
-            liquidity,
-            ..
-        } = self
+        let (
+            SwapState {
+                amount_specified_remaining,
+                amount_calculated: input_amount,
+                sqrt_price_x96,
+                liquidity,
+                ..
+            },
+            _,
+        ) = self
             .swap(
                 zero_for_one,
                 I256::from_big_int(-output_amount.quotient()),
@@ -446,25 +1022,135 @@ impl<TP: Clone + TickDataProvider> Pool<TP> {
             },
         ))
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{currency_amount, tests::*};
-    use alloy_primitives::b256;
 
-    mod constructor {
-        use super::*;
-        use alloy_primitives::address;
+    /// Approximates the liquidity the pool would need in its active range for a swap of
+    /// `swap_amount` to have a price impact of at most `max_impact`
+    ///
+    /// ## Note
+    ///
+    /// The result is an approximation, found by binary searching over swaps simulated against a
+    /// pool with the same price and fee whose liquidity spans the full tick range. The price
+    /// impact includes the LP fee, so a target impact that can't be reached with any liquidity
+    /// throws [`Error::InsufficientLiquidity`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `swap_amount`: The input amount of the swap
+    /// * `max_impact`: The maximum price impact of the swap
+    ///
+    /// returns: The smallest active liquidity for which the swap's price impact is within
+    /// `max_impact`
+    #[inline]
+    pub async fn liquidity_for_target_impact(
+        &self,
+        swap_amount: &CurrencyAmount<impl BaseCurrency>,
+        max_impact: Percent,
+    ) -> Result<u128, Error> {
+        const MAX_LIQUIDITY: u128 = i128::MAX as u128;
 
-        #[test]
-        #[should_panic(expected = "Core(ChainIdMismatch(1, 3))")]
-        fn cannot_be_used_for_currencies_on_different_chains() {
-            Pool::new(
-                Currency::Token(USDC.clone()),
-                Currency::Token(WETH9::on_chain(3).unwrap()),
-                FeeAmount::MEDIUM.into(),
+        let input_currency = if self.currency0.equals(&swap_amount.currency) {
+            self.currency0.clone()
+        } else if self.currency1.equals(&swap_amount.currency) {
+            self.currency1.clone()
+        } else {
+            return Err(Error::InvalidCurrency);
+        };
+        let input_amount = CurrencyAmount::from_raw_amount(input_currency, swap_amount.quotient())?;
+        let spot_output_amount = self
+            .price_of(&input_amount.currency)?
+            .quote(&input_amount)?;
+
+        // grow the upper bound until it reaches the target impact
+        let mut low = 0;
+        let mut high = self.liquidity.max(1);
+        while !self
+            .impact_within_target(high, &input_amount, &spot_output_amount, &max_impact)
+            .await?
+        {
+            if high == MAX_LIQUIDITY {
+                return Err(Error::InsufficientLiquidity);
+            }
+            low = high;
+            high = high.saturating_mul(2).min(MAX_LIQUIDITY);
+        }
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self
+                .impact_within_target(mid, &input_amount, &spot_output_amount, &max_impact)
+                .await?
+            {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Ok(high)
+    }
+
+    /// Whether a swap of `input_amount` through the pool with `liquidity` over the full tick range
+    /// has a price impact of at most `max_impact`
+    async fn impact_within_target(
+        &self,
+        liquidity: u128,
+        input_amount: &CurrencyAmount<Currency>,
+        spot_output_amount: &CurrencyAmount<Currency>,
+        max_impact: &Percent,
+    ) -> Result<bool, Error> {
+        let mut pool = Pool::new_with_tick_data_provider(
+            self.currency0.clone(),
+            self.currency1.clone(),
+            self.fee,
+            self.tick_spacing,
+            self.hooks,
+            self.sqrt_price_x96,
+            liquidity,
+            vec![
+                Tick {
+                    index: nearest_usable_tick(TP::Index::from_i24(MIN_TICK), self.tick_spacing),
+                    liquidity_net: liquidity as i128,
+                    liquidity_gross: liquidity,
+                },
+                Tick {
+                    index: nearest_usable_tick(TP::Index::from_i24(MAX_TICK), self.tick_spacing),
+                    liquidity_net: -(liquidity as i128),
+                    liquidity_gross: liquidity,
+                },
+            ],
+        )?;
+        pool.hook_swap_simulator
+            .clone_from(&self.hook_swap_simulator);
+        pool.dynamic_fee_resolver
+            .clone_from(&self.dynamic_fee_resolver);
+        pool.protocol_fee = self.protocol_fee;
+        let output_amount = match pool.get_output_amount(input_amount, None).await {
+            Ok((output_amount, _)) => output_amount,
+            Err(Error::InsufficientLiquidity) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let price_impact = spot_output_amount
+            .subtract(&output_amount)?
+            .divide(spot_output_amount)?;
+        Ok(Percent::new(price_impact.numerator, price_impact.denominator) <= *max_impact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{currency_amount, tests::*};
+    use alloy_primitives::b256;
+
+    mod constructor {
+        use super::*;
+        use alloy_primitives::address;
+
+        #[test]
+        #[should_panic(expected = "Core(ChainIdMismatch(1, 3))")]
+        fn cannot_be_used_for_currencies_on_different_chains() {
+            Pool::new(
+                Currency::Token(USDC.clone()),
+                Currency::Token(WETH9::on_chain(3).unwrap()),
+                FeeAmount::MEDIUM.into(),
                 10,
                 Address::ZERO,
                 *SQRT_PRICE_1_1,
@@ -518,6 +1204,65 @@ mod tests {
             .unwrap();
         }
 
+        #[test]
+        fn tick_spacing_cannot_be_zero() {
+            let result = Pool::new(
+                Currency::Token(USDC.clone()),
+                Currency::Token(WETH.clone()),
+                FeeAmount::MEDIUM.into(),
+                0,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            );
+            assert!(matches!(result, Err(Error::InvalidTickSpacing)));
+        }
+
+        #[test]
+        fn tick_spacing_cannot_be_negative() {
+            let result = Pool::new(
+                Currency::Token(USDC.clone()),
+                Currency::Token(WETH.clone()),
+                FeeAmount::MEDIUM.into(),
+                -10,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            );
+            assert!(matches!(result, Err(Error::InvalidTickSpacing)));
+        }
+
+        #[test]
+        fn tick_spacing_cannot_exceed_max() {
+            let result = Pool::new(
+                Currency::Token(USDC.clone()),
+                Currency::Token(WETH.clone()),
+                FeeAmount::MEDIUM.into(),
+                MAX_TICK_SPACING + 1,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            );
+            assert!(matches!(result, Err(Error::InvalidTickSpacing)));
+        }
+
+        #[test]
+        fn tick_spacing_can_be_min_or_max() {
+            for tick_spacing in [MIN_TICK_SPACING, MAX_TICK_SPACING] {
+                let pool = Pool::new(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(WETH.clone()),
+                    FeeAmount::MEDIUM.into(),
+                    tick_spacing,
+                    Address::ZERO,
+                    *SQRT_PRICE_1_1,
+                    0,
+                )
+                .unwrap();
+                assert_eq!(pool.tick_spacing, tick_spacing);
+            }
+        }
+
         #[test]
         #[should_panic(expected = "Core(EqualAddresses)")]
         fn cannot_be_given_two_of_the_same_currency() {
@@ -576,6 +1321,57 @@ mod tests {
         }
     }
 
+    mod new_from_strings {
+        use super::*;
+
+        #[test]
+        fn parses_decimal_strings() {
+            let pool = Pool::new_from_strings(
+                Currency::Token(USDC.clone()),
+                Currency::Token(DAI.clone()),
+                FeeAmount::LOWEST.into(),
+                10,
+                Address::ZERO,
+                "79228162514264337593543950336",
+                "1000000000000000000",
+            )
+            .unwrap();
+            assert_eq!(pool.sqrt_price_x96, *SQRT_PRICE_1_1);
+            assert_eq!(pool.liquidity, 1_000_000_000_000_000_000);
+            assert_eq!(pool.tick_current, 0);
+        }
+
+        #[test]
+        fn rejects_malformed_sqrt_price() {
+            let result = Pool::new_from_strings(
+                Currency::Token(USDC.clone()),
+                Currency::Token(DAI.clone()),
+                FeeAmount::LOWEST.into(),
+                10,
+                Address::ZERO,
+                "7922816251426433759354395033.6",
+                "0",
+            );
+            assert!(matches!(result, Err(Error::ParseError("sqrtPrice"))));
+        }
+
+        #[test]
+        fn rejects_malformed_liquidity() {
+            for liquidity in ["", "-1", "1e18", "340282366920938463463374607431768211456"] {
+                let result = Pool::new_from_strings(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    FeeAmount::LOWEST.into(),
+                    10,
+                    Address::ZERO,
+                    "79228162514264337593543950336",
+                    liquidity,
+                );
+                assert!(matches!(result, Err(Error::ParseError("liquidity"))));
+            }
+        }
+    }
+
     #[test]
     fn get_pool_id_returns_correct_pool_id() {
         let result1 = Pool::get_pool_id(
@@ -602,6 +1398,23 @@ mod tests {
         assert_eq!(result2, result1);
     }
 
+    #[test]
+    fn pool_id_from_pool_key_matches_get_pool_id() {
+        let key = Pool::get_pool_key(
+            &USDC.clone().into(),
+            &DAI.clone().into(),
+            FeeAmount::LOWEST.into(),
+            10,
+            Address::ZERO,
+        )
+        .unwrap();
+        assert_eq!(
+            pool_id_from_pool_key(&key),
+            b256!("503fb8d73fd2351c645ae9fea85381bac6b16ea0c2038e14dc1e96d447c8ffbb")
+        );
+        assert_eq!(pool_id_from_pool_key(&USDC_DAI.pool_key), USDC_DAI.pool_id);
+    }
+
     #[test]
     fn get_pool_key_returns_correct_pool_key() {
         let result1 = Pool::get_pool_key(
@@ -740,6 +1553,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prices_returns_reciprocal_currency_prices() {
+        let pool = Pool::new(
+            Currency::Token(USDC.clone()),
+            Currency::Token(DAI.clone()),
+            FeeAmount::LOWEST.into(),
+            10,
+            Address::ZERO,
+            encode_sqrt_ratio_x96(BigInt::from(101e6 as u128), BigInt::from(100e18 as u128)),
+            0,
+        )
+        .unwrap();
+        let (currency0_price, currency1_price) = pool.prices();
+        assert_eq!(currency0_price, pool.currency0_price());
+        assert_eq!(currency1_price, pool.currency1_price());
+        assert_eq!(
+            currency0_price
+                .multiply(&currency1_price)
+                .unwrap()
+                .to_significant(5, None)
+                .unwrap(),
+            "1"
+        );
+    }
+
     mod price_of {
         use super::*;
 
@@ -762,6 +1600,62 @@ mod tests {
         }
     }
 
+    mod tick_boundary_prices {
+        use super::*;
+
+        #[test]
+        fn returns_prices_at_current_tick_bounds() {
+            let pool = Pool::new_with_tick_data_provider(
+                USDC.clone().into(),
+                DAI.clone().into(),
+                FeeAmount::LOWEST.into(),
+                10,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                ONE_ETHER,
+                TICK_LIST.clone(),
+            )
+            .unwrap();
+            let (lower, upper) = pool.tick_boundary_prices().unwrap();
+            assert_eq!(lower, pool.currency0_price());
+            assert_eq!(
+                upper,
+                tick_to_price(pool.currency0.clone(), pool.currency1.clone(), 10.to_i24()).unwrap()
+            );
+            assert!(upper > lower);
+        }
+
+        #[test]
+        fn rounds_down_to_tick_spacing() {
+            let pool = Pool::new_with_tick_data_provider(
+                USDC.clone().into(),
+                DAI.clone().into(),
+                FeeAmount::LOWEST.into(),
+                10,
+                Address::ZERO,
+                get_sqrt_ratio_at_tick((-5).to_i24()).unwrap(),
+                ONE_ETHER,
+                TICK_LIST.clone(),
+            )
+            .unwrap();
+            assert_eq!(pool.tick_current, -5);
+            let (lower, upper) = pool.tick_boundary_prices().unwrap();
+            assert_eq!(
+                lower,
+                tick_to_price(
+                    pool.currency0.clone(),
+                    pool.currency1.clone(),
+                    (-10).to_i24()
+                )
+                .unwrap()
+            );
+            assert_eq!(
+                upper,
+                tick_to_price(pool.currency0.clone(), pool.currency1.clone(), 0.to_i24()).unwrap()
+            );
+        }
+    }
+
     #[test]
     fn chain_id_returns_chain_id_of_currencies() {
         assert_eq!(USDC_DAI.chain_id(), 1);
@@ -775,6 +1669,58 @@ mod tests {
         assert!(!USDC_DAI.involves_currency(&WETH9::on_chain(1).unwrap()));
     }
 
+    mod same_market {
+        use super::*;
+
+        #[test]
+        fn ignores_swap_state() {
+            let snapshot = Pool::new(
+                USDC.clone().into(),
+                DAI.clone().into(),
+                FeeAmount::LOWEST.into(),
+                10,
+                Address::ZERO,
+                encode_sqrt_ratio_x96(2, 1),
+                ONE_ETHER,
+            )
+            .unwrap();
+            assert_ne!(*USDC_DAI, snapshot);
+            assert!(USDC_DAI.same_market(&snapshot));
+            assert!(USDC_DAI.same_market(&*DAI_USDC));
+        }
+
+        #[test]
+        fn compares_tick_data_providers_of_different_types() {
+            let pool = Pool::new_with_tick_data_provider(
+                USDC.clone().into(),
+                DAI.clone().into(),
+                FeeAmount::LOWEST.into(),
+                10,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                ONE_ETHER,
+                TICK_LIST.clone(),
+            )
+            .unwrap();
+            assert!(pool.same_market(&*USDC_DAI));
+        }
+
+        #[test]
+        fn distinguishes_pool_keys() {
+            let other_fee = Pool::new(
+                USDC.clone().into(),
+                DAI.clone().into(),
+                FeeAmount::LOW.into(),
+                10,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap();
+            assert!(!USDC_DAI.same_market(&other_fee));
+        }
+    }
+
     mod v4_involves_token {
         use super::*;
 
@@ -815,6 +1761,134 @@ mod tests {
         }
     }
 
+    mod hook_flags_valid {
+        use super::*;
+        use alloy_primitives::address;
+
+        fn pool_with_hook(fee: U24, hooks: Address) -> Pool {
+            Pool::new(
+                Currency::Token(USDC.clone()),
+                Currency::Token(DAI.clone()),
+                fee,
+                10,
+                hooks,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn accepts_valid_hooks() {
+            let fee = FeeAmount::MEDIUM.into();
+            assert!(pool_with_hook(fee, Address::ZERO).hook_flags_valid());
+            assert!(
+                pool_with_hook(fee, address!("00000000000000000000000000000000000000c0"))
+                    .hook_flags_valid()
+            );
+            assert!(pool_with_hook(
+                DYANMIC_FEE_FLAG,
+                address!("0000000000000000000000000000000000004000")
+            )
+            .hook_flags_valid());
+        }
+
+        #[test]
+        fn rejects_spurious_high_flag_bit() {
+            assert!(!pool_with_hook(
+                FeeAmount::MEDIUM.into(),
+                address!("0000000000000000000000000000000000004000")
+            )
+            .hook_flags_valid());
+        }
+
+        #[test]
+        fn rejects_delta_flag_without_its_hook() {
+            assert!(!pool_with_hook(
+                FeeAmount::MEDIUM.into(),
+                address!("0000000000000000000000000000000000000048")
+            )
+            .hook_flags_valid());
+        }
+    }
+
+    mod amount_after_fee {
+        use super::*;
+        use alloy_primitives::address;
+
+        fn pool_with_fee(fee: FeeAmount) -> Pool {
+            Pool::new(
+                Currency::Token(USDC.clone()),
+                Currency::Token(DAI.clone()),
+                fee.into(),
+                10,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn deducts_standard_fee_tiers() {
+            let amount = U256::from(1_000_000);
+            for (fee, expected) in [
+                (FeeAmount::LOWEST, 999_900),
+                (FeeAmount::LOW, 999_500),
+                (FeeAmount::MEDIUM, 997_000),
+                (FeeAmount::HIGH, 990_000),
+            ] {
+                assert_eq!(
+                    pool_with_fee(fee).amount_after_fee(amount).unwrap(),
+                    U256::from(expected)
+                );
+            }
+        }
+
+        #[test]
+        fn rounds_down() {
+            let pool = pool_with_fee(FeeAmount::MEDIUM);
+            assert_eq!(
+                pool.amount_after_fee(U256::from(999)).unwrap(),
+                U256::from(996)
+            );
+            assert_eq!(pool.amount_after_fee(U256::ZERO).unwrap(), U256::ZERO);
+            assert_eq!(
+                pool.amount_after_fee(U256::MAX).unwrap(),
+                U256::MAX / U256::from(1000) * U256::from(997)
+                    + U256::MAX % U256::from(1000) * U256::from(997) / U256::from(1000)
+            );
+        }
+
+        #[test]
+        fn uses_the_resolved_dynamic_fee() {
+            let pool = Pool::new(
+                Currency::Token(USDC.clone()),
+                Currency::Token(DAI.clone()),
+                DYANMIC_FEE_FLAG,
+                10,
+                address!("0000000000000000000000000000000000001000"),
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap();
+            assert!(matches!(
+                pool.amount_after_fee(U256::from(100)),
+                Err(Error::InvalidFee(fee)) if fee == DYANMIC_FEE_FLAG
+            ));
+            let pool = pool.with_dynamic_fee_resolver(|_| FeeAmount::LOW.into());
+            assert_eq!(
+                pool.amount_after_fee(U256::from(1_000_000)).unwrap(),
+                U256::from(999_500)
+            );
+            let pool = pool.with_dynamic_fee_resolver(|_| uint!(1_000_000_U24));
+            assert!(matches!(
+                pool.amount_after_fee(U256::from(100)),
+                Err(Error::InvalidFee(fee)) if fee == uint!(1_000_000_U24)
+            ));
+        }
+    }
+
     mod swaps {
         use super::*;
         use once_cell::sync::Lazy;
@@ -853,6 +1927,250 @@ mod tests {
             }
         }
 
+        mod protocol_fee {
+            use super::*;
+
+            fn pool_with_fee(fee: U24) -> Pool<Vec<Tick>> {
+                Pool::new_with_tick_data_provider(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    fee,
+                    10,
+                    Address::ZERO,
+                    *SQRT_PRICE_1_1,
+                    ONE_ETHER,
+                    TICK_LIST.clone(),
+                )
+                .unwrap()
+            }
+
+            #[tokio::test]
+            async fn is_folded_into_the_swap_fee() {
+                // 0.1% protocol fee in both directions on top of a 0.01% LP fee
+                let pool = POOL.clone().with_protocol_fee(uint!(0x3e83e8_U24)).unwrap();
+                // 1000 + 100 - 1000 * 100 / 1e6
+                let equivalent = pool_with_fee(uint!(1100_U24));
+                for input_amount in [
+                    currency_amount!(DAI, ONE_ETHER / 100),
+                    currency_amount!(USDC, ONE_ETHER / 100),
+                ] {
+                    let (output_amount, _) =
+                        pool.get_output_amount(&input_amount, None).await.unwrap();
+                    let (without_protocol_fee, _) =
+                        POOL.get_output_amount(&input_amount, None).await.unwrap();
+                    let (expected, _) = equivalent
+                        .get_output_amount(&input_amount, None)
+                        .await
+                        .unwrap();
+                    assert_eq!(output_amount.quotient(), expected.quotient());
+                    assert!(output_amount.quotient() < without_protocol_fee.quotient());
+                }
+            }
+
+            #[tokio::test]
+            async fn only_applies_to_its_swap_direction() {
+                // 0.1% protocol fee on zero for one swaps only
+                let pool = POOL.clone().with_protocol_fee(uint!(1000_U24)).unwrap();
+                let input_amount = currency_amount!(USDC, ONE_ETHER / 100);
+                let (output_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+                let (expected, _) = POOL.get_output_amount(&input_amount, None).await.unwrap();
+                assert_eq!(output_amount.quotient(), expected.quotient());
+            }
+
+            #[test]
+            fn throws_if_protocol_fee_exceeds_max() {
+                for protocol_fee in [uint!(1001_U24), uint!(0x3e9000_U24)] {
+                    assert!(matches!(
+                        POOL.clone().with_protocol_fee(protocol_fee),
+                        Err(Error::InvalidProtocolFee(fee)) if fee == protocol_fee
+                    ));
+                }
+            }
+        }
+
+        mod get_output_amount_with_price_limit {
+            use super::*;
+
+            #[tokio::test]
+            async fn partially_fills_when_price_limit_is_reached() {
+                let input_amount = currency_amount!(DAI, ONE_ETHER / 10);
+                let sqrt_price_limit_x96 =
+                    encode_sqrt_ratio_x96(BigInt::from(99_u64), BigInt::from(100_u64));
+                let (output_amount, consumed_amount, pool) = POOL
+                    .get_output_amount_with_price_limit(&input_amount, sqrt_price_limit_x96)
+                    .await
+                    .unwrap();
+                assert!(consumed_amount.currency.equals(&DAI.clone()));
+                assert!(output_amount.currency.equals(&USDC.clone()));
+                assert!(consumed_amount.quotient() > BigInt::ZERO);
+                assert!(consumed_amount.quotient() < input_amount.quotient());
+                assert!(output_amount.quotient() > BigInt::ZERO);
+                assert_eq!(pool.sqrt_price_x96, sqrt_price_limit_x96);
+            }
+
+            #[tokio::test]
+            async fn consumes_full_input_when_price_limit_is_not_reached() {
+                let input_amount = currency_amount!(USDC, 100);
+                let (output_amount, consumed_amount, _) = POOL
+                    .get_output_amount_with_price_limit(
+                        &input_amount,
+                        MAX_SQRT_RATIO - U160::from(1),
+                    )
+                    .await
+                    .unwrap();
+                assert!(consumed_amount.currency.equals(&USDC.clone()));
+                assert_eq!(consumed_amount.quotient(), input_amount.quotient());
+                assert_eq!(output_amount.quotient(), 98.into());
+            }
+        }
+
+        mod crossed_ticks {
+            use super::*;
+
+            static POOL: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+                Pool::new_with_tick_data_provider(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    FeeAmount::LOWEST.into(),
+                    10,
+                    Address::ZERO,
+                    *SQRT_PRICE_1_1,
+                    2 * ONE_ETHER,
+                    vec![
+                        TICK_LIST[0].clone(),
+                        Tick {
+                            index: -100,
+                            liquidity_net: ONE_ETHER as i128,
+                            liquidity_gross: ONE_ETHER,
+                        },
+                        Tick {
+                            index: 100,
+                            liquidity_net: -(ONE_ETHER as i128),
+                            liquidity_gross: ONE_ETHER,
+                        },
+                        TICK_LIST[1].clone(),
+                    ],
+                )
+                .unwrap()
+            });
+
+            #[tokio::test]
+            async fn matches_get_output_amount() {
+                let input_amount = currency_amount!(DAI, ONE_ETHER / 10);
+                let detailed = POOL
+                    .get_output_amount_detailed(&input_amount, None)
+                    .await
+                    .unwrap();
+                let (output_amount, pool) =
+                    POOL.get_output_amount(&input_amount, None).await.unwrap();
+                assert_eq!(detailed.output_amount, output_amount);
+                assert_eq!(detailed.pool_after, pool);
+                assert_eq!(detailed.tick_after, pool.tick_current);
+                assert_eq!(detailed.sqrt_price_after, pool.sqrt_price_x96);
+            }
+
+            #[tokio::test]
+            async fn counts_no_ticks_within_range() {
+                let input_amount = currency_amount!(DAI, 100);
+                let detailed = POOL
+                    .get_output_amount_detailed(&input_amount, None)
+                    .await
+                    .unwrap();
+                assert_eq!(detailed.ticks_crossed, 0);
+                assert_eq!(detailed.pool_after.liquidity, 2 * ONE_ETHER);
+            }
+
+            #[tokio::test]
+            async fn counts_crossed_ticks_zero_for_one() {
+                let input_amount = currency_amount!(DAI, ONE_ETHER / 10);
+                let detailed = POOL
+                    .get_output_amount_detailed(&input_amount, None)
+                    .await
+                    .unwrap();
+                assert_eq!(detailed.ticks_crossed, 1);
+                assert!(detailed.tick_after < -100);
+                assert_eq!(detailed.pool_after.liquidity, ONE_ETHER);
+            }
+
+            #[tokio::test]
+            async fn counts_crossed_ticks_one_for_zero() {
+                let input_amount = currency_amount!(USDC, ONE_ETHER / 10);
+                let detailed = POOL
+                    .get_output_amount_detailed(&input_amount, None)
+                    .await
+                    .unwrap();
+                assert_eq!(detailed.ticks_crossed, 1);
+                assert!(detailed.tick_after >= 100);
+                assert_eq!(detailed.pool_after.liquidity, ONE_ETHER);
+            }
+
+            #[tokio::test]
+            async fn ticks_crossed_by_swap_lists_crossed_ticks() {
+                let amount = I256::from_big_int(BigInt::from(ONE_ETHER / 10));
+                assert_eq!(
+                    POOL.ticks_crossed_by_swap(true, amount).await.unwrap(),
+                    vec![-100]
+                );
+                assert_eq!(
+                    POOL.ticks_crossed_by_swap(false, amount).await.unwrap(),
+                    vec![100]
+                );
+                assert!(POOL
+                    .ticks_crossed_by_swap(true, I256::from_big_int(BigInt::from(100)))
+                    .await
+                    .unwrap()
+                    .is_empty());
+            }
+
+            #[tokio::test]
+            async fn swap_steps_matches_v3_swap() {
+                // `swap_steps` copies the swap loop of `v3_swap`, which it must match for a
+                // static fee
+                for pool in [&*POOL, &*super::POOL] {
+                    for zero_for_one in [true, false] {
+                        for amount in [100, -100, ONE_ETHER as i128 / 10, -(ONE_ETHER as i128) / 10]
+                        {
+                            let amount_specified = I256::from_big_int(BigInt::from(amount));
+                            let (state, _) = pool
+                                .swap_steps(zero_for_one, amount_specified, None)
+                                .await
+                                .unwrap();
+                            let expected = v3_swap(
+                                pool.fee,
+                                pool.sqrt_price_x96,
+                                pool.tick_current,
+                                pool.liquidity,
+                                pool.tick_spacing,
+                                &pool.tick_data_provider,
+                                zero_for_one,
+                                amount_specified,
+                                None,
+                            )
+                            .await
+                            .unwrap();
+                            assert_eq!(
+                                state.amount_specified_remaining,
+                                expected.amount_specified_remaining
+                            );
+                            assert_eq!(state.amount_calculated, expected.amount_calculated);
+                            assert_eq!(state.sqrt_price_x96, expected.sqrt_price_x96);
+                            assert_eq!(state.tick_current, expected.tick_current);
+                            assert_eq!(state.liquidity, expected.liquidity);
+                        }
+                    }
+                }
+            }
+
+            #[tokio::test]
+            async fn ticks_crossed_by_swap_supports_exact_output() {
+                let amount = -I256::from_big_int(BigInt::from(ONE_ETHER / 10));
+                assert_eq!(
+                    POOL.ticks_crossed_by_swap(true, amount).await.unwrap(),
+                    vec![-100]
+                );
+            }
+        }
+
         mod get_input_amount {
             use super::*;
 
@@ -872,5 +2190,555 @@ mod tests {
                 assert_eq!(input_amount.quotient(), 100.into());
             }
         }
+
+        mod native {
+            use super::*;
+
+            static POOL_ETH_USDC: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+                Pool::new_with_tick_data_provider(
+                    Currency::NativeCurrency(ETHER.clone()),
+                    Currency::Token(USDC.clone()),
+                    FeeAmount::LOWEST.into(),
+                    10,
+                    Address::ZERO,
+                    *SQRT_PRICE_1_1,
+                    ONE_ETHER,
+                    TICK_LIST.clone(),
+                )
+                .unwrap()
+            });
+
+            #[test]
+            fn native_currency_is_currency0() {
+                assert!(POOL_ETH_USDC.currency0.is_native());
+                assert!(POOL_ETH_USDC.involves_currency(&ETHER.clone()));
+                assert!(!POOL_ETH_USDC.involves_currency(&WETH.clone()));
+            }
+
+            #[tokio::test]
+            async fn get_output_amount_eth_to_usdc() {
+                let input_amount = currency_amount!(ETHER, 100);
+                let (output_amount, pool) = POOL_ETH_USDC
+                    .get_output_amount(&input_amount, None)
+                    .await
+                    .unwrap();
+                assert!(output_amount.currency.equals(&USDC.clone()));
+                assert_eq!(output_amount.quotient(), 98.into());
+                assert!(pool.sqrt_price_x96 < POOL_ETH_USDC.sqrt_price_x96);
+                assert!(pool.currency0.is_native());
+            }
+
+            #[tokio::test]
+            async fn get_output_amount_usdc_to_eth() {
+                let input_amount = currency_amount!(USDC, 100);
+                let (output_amount, pool) = POOL_ETH_USDC
+                    .get_output_amount(&input_amount, None)
+                    .await
+                    .unwrap();
+                assert!(output_amount.currency.is_native());
+                assert!(output_amount.currency.equals(&ETHER.clone()));
+                assert_eq!(output_amount.quotient(), 98.into());
+                assert!(pool.sqrt_price_x96 > POOL_ETH_USDC.sqrt_price_x96);
+            }
+
+            #[tokio::test]
+            async fn get_input_amount_eth_to_usdc() {
+                let output_amount = currency_amount!(USDC, 98);
+                let (input_amount, _) = POOL_ETH_USDC
+                    .get_input_amount(&output_amount, None)
+                    .await
+                    .unwrap();
+                assert!(input_amount.currency.is_native());
+                assert_eq!(input_amount.quotient(), 100.into());
+            }
+
+            #[tokio::test]
+            async fn get_input_amount_usdc_to_eth() {
+                let output_amount = currency_amount!(ETHER, 98);
+                let (input_amount, _) = POOL_ETH_USDC
+                    .get_input_amount(&output_amount, None)
+                    .await
+                    .unwrap();
+                assert!(input_amount.currency.equals(&USDC.clone()));
+                assert_eq!(input_amount.quotient(), 100.into());
+            }
+
+            #[tokio::test]
+            async fn wrapped_native_is_not_a_pool_currency() {
+                let amount = currency_amount!(WETH, 100);
+                assert!(matches!(
+                    POOL_ETH_USDC.get_output_amount(&amount, None).await,
+                    Err(Error::InvalidCurrency)
+                ));
+                assert!(matches!(
+                    POOL_ETH_USDC.get_input_amount(&amount, None).await,
+                    Err(Error::InvalidCurrency)
+                ));
+            }
+        }
+
+        mod hook_swap_simulator {
+            use super::*;
+            use alloy_primitives::address;
+
+            /// A constant sum curve that swaps 1:1 without touching the pool's liquidity
+            #[derive(Debug)]
+            struct ConstantSum;
+
+            impl HookSwapSimulator<i32> for ConstantSum {
+                fn simulate_swap(
+                    &self,
+                    _zero_for_one: bool,
+                    amount_specified: I256,
+                    _sqrt_price_limit_x96: Option<U160>,
+                    _fee: U24,
+                    mut state: SwapState<i32>,
+                ) -> Result<SwapState<i32>, Error> {
+                    state.amount_specified_remaining = I256::ZERO;
+                    state.amount_calculated = -amount_specified;
+                    Ok(state)
+                }
+            }
+
+            /// Checks the price limit and fee it is given, then swaps like [`ConstantSum`]
+            #[derive(Debug)]
+            struct ExpectLimitAndFee {
+                sqrt_price_limit_x96: Option<U160>,
+                fee: U24,
+            }
+
+            impl HookSwapSimulator<i32> for ExpectLimitAndFee {
+                fn simulate_swap(
+                    &self,
+                    zero_for_one: bool,
+                    amount_specified: I256,
+                    sqrt_price_limit_x96: Option<U160>,
+                    fee: U24,
+                    state: SwapState<i32>,
+                ) -> Result<SwapState<i32>, Error> {
+                    assert_eq!(sqrt_price_limit_x96, self.sqrt_price_limit_x96);
+                    assert_eq!(fee, self.fee);
+                    ConstantSum.simulate_swap(
+                        zero_for_one,
+                        amount_specified,
+                        sqrt_price_limit_x96,
+                        fee,
+                        state,
+                    )
+                }
+            }
+
+            // BeforeSwap | BeforeSwapReturnsDelta
+            const HOOK: Address = address!("0000000000000000000000000000000000000088");
+
+            static HOOKED_POOL: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+                Pool::new_with_tick_data_provider(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    FeeAmount::LOWEST.into(),
+                    10,
+                    HOOK,
+                    *SQRT_PRICE_1_1,
+                    ONE_ETHER,
+                    TICK_LIST.clone(),
+                )
+                .unwrap()
+            });
+
+            #[tokio::test]
+            async fn throws_without_simulator() {
+                let input_amount = currency_amount!(USDC, 100);
+                let result = HOOKED_POOL.get_output_amount(&input_amount, None).await;
+                assert!(matches!(result, Err(Error::UnsupportedHook { .. })));
+                let output_amount = currency_amount!(DAI, 98);
+                let result = HOOKED_POOL.get_input_amount(&output_amount, None).await;
+                assert!(matches!(result, Err(Error::UnsupportedHook { .. })));
+            }
+
+            #[tokio::test]
+            async fn error_carries_hook_address_and_permissions() {
+                let input_amount = currency_amount!(USDC, 100);
+                let Err(Error::UnsupportedHook {
+                    address,
+                    permissions,
+                }) = HOOKED_POOL.get_output_amount(&input_amount, None).await
+                else {
+                    panic!("expected UnsupportedHook");
+                };
+                assert_eq!(address, HOOK);
+                assert!(permissions.before_swap);
+                assert!(permissions.before_swap_returns_delta);
+                assert!(!permissions.after_swap);
+            }
+
+            #[tokio::test]
+            async fn delegates_to_simulator() {
+                let pool = HOOKED_POOL
+                    .clone()
+                    .with_hook_swap_simulator(Arc::new(ConstantSum));
+
+                let input_amount = currency_amount!(USDC, 100);
+                let (output_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+                assert!(output_amount.currency.equals(&DAI.clone()));
+                assert_eq!(output_amount.quotient(), 100.into());
+
+                let output_amount = currency_amount!(DAI, 98);
+                let (input_amount, _) = pool.get_input_amount(&output_amount, None).await.unwrap();
+                assert!(input_amount.currency.equals(&USDC.clone()));
+                assert_eq!(input_amount.quotient(), 98.into());
+            }
+
+            #[tokio::test]
+            async fn simulator_receives_price_limit_and_fee() {
+                let sqrt_price_limit_x96 = *SQRT_PRICE_1_1 - U160::from(1000);
+                // 0.1% protocol fee on zero for one swaps on top of the 0.01% LP fee
+                let pool = HOOKED_POOL
+                    .clone()
+                    .with_protocol_fee(uint!(1000_U24))
+                    .unwrap()
+                    .with_hook_swap_simulator(Arc::new(ExpectLimitAndFee {
+                        sqrt_price_limit_x96: Some(sqrt_price_limit_x96),
+                        fee: uint!(1100_U24),
+                    }));
+                let input_amount = currency_amount!(DAI, 100);
+                let (output_amount, ..) = pool
+                    .get_output_amount_with_price_limit(&input_amount, sqrt_price_limit_x96)
+                    .await
+                    .unwrap();
+                assert_eq!(output_amount.quotient(), 100.into());
+            }
+
+            #[tokio::test]
+            async fn simulator_replaces_local_swap() {
+                // without tick data, swapping through the pool's liquidity locally would fail
+                let pool = Pool::new(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    FeeAmount::LOWEST.into(),
+                    10,
+                    HOOK,
+                    *SQRT_PRICE_1_1,
+                    0,
+                )
+                .unwrap()
+                .with_hook_swap_simulator(Arc::new(ConstantSum));
+                let input_amount = currency_amount!(USDC, 100);
+                let (output_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+                assert_eq!(output_amount.quotient(), 100.into());
+            }
+
+            #[tokio::test]
+            async fn ignores_simulator_for_hookless_pools() {
+                let pool = POOL.clone().with_hook_swap_simulator(Arc::new(ConstantSum));
+                let input_amount = currency_amount!(USDC, 100);
+                let (output_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+                assert_eq!(output_amount.quotient(), 98.into());
+            }
+        }
+
+        mod dynamic_fee {
+            use super::*;
+            use alloy_primitives::address;
+
+            // AfterInitialize only, so the hook doesn't impact swaps
+            const HOOK: Address = address!("0000000000000000000000000000000000001000");
+
+            static DYNAMIC_FEE_POOL: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+                Pool::new_with_tick_data_provider(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    DYANMIC_FEE_FLAG,
+                    10,
+                    HOOK,
+                    *SQRT_PRICE_1_1,
+                    ONE_ETHER,
+                    TICK_LIST.clone(),
+                )
+                .unwrap()
+            });
+
+            #[test]
+            fn effective_fee_of_static_fee_pool() {
+                assert_eq!(POOL.effective_fee().unwrap(), POOL.fee);
+            }
+
+            #[tokio::test]
+            async fn throws_if_unresolved() {
+                assert!(matches!(
+                    DYNAMIC_FEE_POOL.effective_fee(),
+                    Err(Error::InvalidFee(fee)) if fee == DYANMIC_FEE_FLAG
+                ));
+                let input_amount = currency_amount!(USDC, 100);
+                let result = DYNAMIC_FEE_POOL
+                    .get_output_amount(&input_amount, None)
+                    .await;
+                assert!(matches!(
+                    result,
+                    Err(Error::InvalidFee(fee)) if fee == DYANMIC_FEE_FLAG
+                ));
+            }
+
+            #[tokio::test]
+            async fn swaps_with_resolved_fee() {
+                let pool = DYNAMIC_FEE_POOL
+                    .clone()
+                    .with_dynamic_fee_resolver(|_| FeeAmount::LOWEST.into());
+                assert_eq!(pool.effective_fee().unwrap(), FeeAmount::LOWEST.into());
+
+                let input_amount = currency_amount!(USDC, 100);
+                let (output_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+                let (expected, _) = POOL.get_output_amount(&input_amount, None).await.unwrap();
+                assert_eq!(output_amount.quotient(), expected.quotient());
+
+                let (input_amount, _) = pool.get_input_amount(&output_amount, None).await.unwrap();
+                assert_eq!(input_amount.quotient(), 100.into());
+            }
+
+            #[tokio::test]
+            async fn resolves_fee_for_each_step() {
+                // a swap large enough to span several tick bitmap words takes several steps
+                let input_amount = currency_amount!(DAI, ONE_ETHER / 2);
+                let pool = DYNAMIC_FEE_POOL
+                    .clone()
+                    .with_dynamic_fee_resolver(|_| FeeAmount::LOWEST.into());
+                let (fixed_fee_output, _) =
+                    pool.get_output_amount(&input_amount, None).await.unwrap();
+                let pool = DYNAMIC_FEE_POOL.clone().with_dynamic_fee_resolver(|state| {
+                    if state.amount_calculated.is_zero() {
+                        FeeAmount::LOWEST.into()
+                    } else {
+                        FeeAmount::HIGH.into()
+                    }
+                });
+                let (output_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+                assert!(output_amount.quotient() < fixed_fee_output.quotient());
+            }
+
+            #[tokio::test]
+            async fn throws_if_resolved_fee_is_too_high() {
+                let pool = DYNAMIC_FEE_POOL
+                    .clone()
+                    .with_dynamic_fee_resolver(|_| uint!(1_000_000_U24));
+                assert!(matches!(
+                    pool.effective_fee(),
+                    Err(Error::InvalidFee(fee)) if fee == uint!(1_000_000_U24)
+                ));
+                let input_amount = currency_amount!(USDC, 100);
+                let result = pool.get_output_amount(&input_amount, None).await;
+                assert!(matches!(result, Err(Error::InvalidFee(_))));
+            }
+        }
+
+        mod sqrt_price_after_input_single_tick {
+            use super::*;
+
+            #[test]
+            fn zero_for_one() {
+                let sqrt_price_x96 = POOL
+                    .sqrt_price_after_input_single_tick(true, U256::from(100))
+                    .unwrap();
+                assert_eq!(sqrt_price_x96, uint!(79228162514264329670727698910_U160));
+                assert!(sqrt_price_x96 < POOL.sqrt_price_x96);
+            }
+
+            #[test]
+            fn one_for_zero() {
+                let sqrt_price_x96 = POOL
+                    .sqrt_price_after_input_single_tick(false, U256::from(100))
+                    .unwrap();
+                assert_eq!(sqrt_price_x96, uint!(79228162514264345516360201762_U160));
+                assert!(sqrt_price_x96 > POOL.sqrt_price_x96);
+            }
+
+            #[tokio::test]
+            async fn matches_swap_within_tick() {
+                let input_amount = currency_amount!(USDC, 1_000_000);
+                let (_, pool) = POOL.get_output_amount(&input_amount, None).await.unwrap();
+                // the LP fee of 0.01% is taken from the input before it moves the price
+                let sqrt_price_x96 = POOL
+                    .sqrt_price_after_input_single_tick(false, U256::from(1_000_000 - 100))
+                    .unwrap();
+                assert_eq!(pool.sqrt_price_x96, sqrt_price_x96);
+            }
+        }
+
+        mod liquidity_for_target_impact {
+            use super::*;
+
+            fn v2_pool(liquidity: u128) -> Pool<Vec<Tick>> {
+                Pool::new_with_tick_data_provider(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    FeeAmount::LOWEST.into(),
+                    10,
+                    Address::ZERO,
+                    *SQRT_PRICE_1_1,
+                    liquidity,
+                    vec![
+                        Tick {
+                            index: nearest_usable_tick(MIN_TICK_I32, 10),
+                            liquidity_net: liquidity as i128,
+                            liquidity_gross: liquidity,
+                        },
+                        Tick {
+                            index: nearest_usable_tick(MAX_TICK_I32, 10),
+                            liquidity_net: -(liquidity as i128),
+                            liquidity_gross: liquidity,
+                        },
+                    ],
+                )
+                .unwrap()
+            }
+
+            async fn price_impact(
+                liquidity: u128,
+                input_amount: &CurrencyAmount<Token>,
+            ) -> Percent {
+                let pool = v2_pool(liquidity);
+                let (output_amount, _) = pool.get_output_amount(input_amount, None).await.unwrap();
+                let spot_output_amount = pool
+                    .price_of(&input_amount.currency)
+                    .unwrap()
+                    .quote(
+                        &CurrencyAmount::from_raw_amount(
+                            Currency::Token(input_amount.currency.clone()),
+                            input_amount.quotient(),
+                        )
+                        .unwrap(),
+                    )
+                    .unwrap();
+                let price_impact = spot_output_amount
+                    .subtract(&output_amount)
+                    .unwrap()
+                    .divide(&spot_output_amount)
+                    .unwrap();
+                Percent::new(price_impact.numerator, price_impact.denominator)
+            }
+
+            #[tokio::test]
+            async fn finds_smallest_liquidity_within_target_impact() {
+                let input_amount = currency_amount!(USDC, ONE_ETHER * 10);
+                let max_impact = Percent::new(1, 200);
+                let liquidity = POOL
+                    .liquidity_for_target_impact(&input_amount, max_impact.clone())
+                    .await
+                    .unwrap();
+                // roughly amount / (impact - fee) for a full range pool
+                assert!(liquidity > ONE_ETHER * 2000 && liquidity < ONE_ETHER * 2100);
+                assert!(price_impact(liquidity, &input_amount).await <= max_impact);
+                assert!(price_impact(liquidity - 1, &input_amount).await > max_impact);
+            }
+
+            #[tokio::test]
+            async fn returns_current_liquidity_bound_for_tiny_swaps() {
+                let input_amount = currency_amount!(USDC, 1_000_000);
+                let max_impact = Percent::new(1, 100);
+                let liquidity = POOL
+                    .liquidity_for_target_impact(&input_amount, max_impact.clone())
+                    .await
+                    .unwrap();
+                assert!(liquidity <= POOL.liquidity);
+                assert!(price_impact(liquidity, &input_amount).await <= max_impact);
+            }
+
+            #[tokio::test]
+            async fn throws_if_target_is_below_fee() {
+                let input_amount = currency_amount!(USDC, ONE_ETHER);
+                assert!(matches!(
+                    POOL.liquidity_for_target_impact(&input_amount, Percent::new(1, 1_000_000))
+                        .await,
+                    Err(Error::InsufficientLiquidity)
+                ));
+            }
+
+            #[tokio::test]
+            async fn throws_if_currency_not_in_pool() {
+                let input_amount = currency_amount!(WETH, ONE_ETHER);
+                assert!(matches!(
+                    POOL.liquidity_for_target_impact(&input_amount, Percent::new(1, 100))
+                        .await,
+                    Err(Error::InvalidCurrency)
+                ));
+            }
+        }
+
+        mod round_trip {
+            use super::*;
+            use alloy_primitives::address;
+
+            static POOL_HIGH_FEE: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+                Pool::new_with_tick_data_provider(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    FeeAmount::HIGH.into(),
+                    10,
+                    Address::ZERO,
+                    *SQRT_PRICE_1_1,
+                    ONE_ETHER,
+                    TICK_LIST.clone(),
+                )
+                .unwrap()
+            });
+
+            // AfterInitialize only, so the hook doesn't impact swaps
+            static POOL_DYNAMIC_HIGH_FEE: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+                Pool::new_with_tick_data_provider(
+                    Currency::Token(USDC.clone()),
+                    Currency::Token(DAI.clone()),
+                    DYANMIC_FEE_FLAG,
+                    10,
+                    address!("0000000000000000000000000000000000001000"),
+                    *SQRT_PRICE_1_1,
+                    ONE_ETHER,
+                    TICK_LIST.clone(),
+                )
+                .unwrap()
+                .with_dynamic_fee_resolver(|_| FeeAmount::HIGH.into())
+            });
+
+            #[tokio::test]
+            async fn exact_in_output_fed_to_exact_out_returns_original_input() {
+                let input_amount = currency_amount!(USDC, 100);
+                let (output_amount, _) = POOL.get_output_amount(&input_amount, None).await.unwrap();
+                let (round_trip_input, _) =
+                    POOL.get_input_amount(&output_amount, None).await.unwrap();
+                assert_eq!(round_trip_input.quotient(), input_amount.quotient());
+            }
+
+            #[tokio::test]
+            async fn exact_in_and_exact_out_use_the_same_fee() {
+                for pool in [&*POOL_HIGH_FEE, &*POOL_DYNAMIC_HIGH_FEE] {
+                    for amount in [100, 10_000, 1_000_000] {
+                        let input_amount = currency_amount!(USDC, amount);
+                        let (output_amount, _) =
+                            pool.get_output_amount(&input_amount, None).await.unwrap();
+                        let (round_trip_input, _) =
+                            pool.get_input_amount(&output_amount, None).await.unwrap();
+                        // exact out rounds the input up, so it never needs more than the original
+                        // input
+                        assert!(round_trip_input.quotient() <= input_amount.quotient());
+                        let (round_trip_output, _) = pool
+                            .get_output_amount(&round_trip_input, None)
+                            .await
+                            .unwrap();
+                        assert_eq!(round_trip_output.quotient(), output_amount.quotient());
+                    }
+                }
+            }
+
+            #[tokio::test]
+            async fn dynamic_fee_override_matches_static_fee() {
+                let input_amount = currency_amount!(USDC, 10_000);
+                let (output_amount, _) = POOL_DYNAMIC_HIGH_FEE
+                    .get_output_amount(&input_amount, None)
+                    .await
+                    .unwrap();
+                let (expected, _) = POOL_HIGH_FEE
+                    .get_output_amount(&input_amount, None)
+                    .await
+                    .unwrap();
+                assert_eq!(output_amount.quotient(), expected.quotient());
+            }
+        }
     }
 }
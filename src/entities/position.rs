@@ -2,7 +2,7 @@ use crate::prelude::{tick_to_price, Error, Pool, *};
 use alloc::vec;
 use alloy_primitives::{
     aliases::{I24, U48},
-    keccak256, uint, U160, U256,
+    keccak256, uint, B256, U160, U256,
 };
 use alloy_sol_types::SolValue;
 use num_traits::ToPrimitive;
@@ -33,6 +33,10 @@ impl<TP: TickDataProvider> Position<TP> {
     /// * `liquidity`: The amount of liquidity that is in the position
     /// * `tick_lower`: The lower tick of the position
     /// * `tick_upper`: The upper tick of the position
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the ticks are invalid, see [`Position::try_new`]
     #[inline]
     pub fn new(
         pool: Pool<TP>,
@@ -40,18 +44,43 @@ impl<TP: TickDataProvider> Position<TP> {
         tick_lower: TP::Index,
         tick_upper: TP::Index,
     ) -> Self {
-        assert!(tick_lower < tick_upper, "TICK_ORDER");
-        assert!(
-            tick_lower >= TP::Index::from_i24(MIN_TICK)
-                && (tick_lower % pool.tick_spacing).is_zero(),
-            "TICK_LOWER"
-        );
-        assert!(
-            tick_upper <= TP::Index::from_i24(MAX_TICK)
-                && (tick_upper % pool.tick_spacing).is_zero(),
-            "TICK_UPPER"
-        );
-        Self {
+        Self::try_new(pool, liquidity, tick_lower, tick_upper).unwrap()
+    }
+
+    /// Constructs a position for a given pool with the given liquidity, returning an error instead
+    /// of panicking if the ticks are invalid
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool`: For which pool the liquidity is assigned
+    /// * `liquidity`: The amount of liquidity that is in the position
+    /// * `tick_lower`: The lower tick of the position
+    /// * `tick_upper`: The upper tick of the position
+    ///
+    /// ## Returns
+    ///
+    /// [`Error::InvalidTickOrder`] if `tick_lower` is not below `tick_upper`, or
+    /// [`Error::TickNotOnSpacing`] if a tick is out of bounds or not a multiple of the pool's tick
+    /// spacing
+    #[inline]
+    pub fn try_new(
+        pool: Pool<TP>,
+        liquidity: u128,
+        tick_lower: TP::Index,
+        tick_upper: TP::Index,
+    ) -> Result<Self, Error> {
+        if tick_lower >= tick_upper {
+            return Err(Error::InvalidTickOrder);
+        }
+        if tick_lower < TP::Index::from_i24(MIN_TICK) || !(tick_lower % pool.tick_spacing).is_zero()
+        {
+            return Err(Error::TickNotOnSpacing(tick_lower.to_i24()));
+        }
+        if tick_upper > TP::Index::from_i24(MAX_TICK) || !(tick_upper % pool.tick_spacing).is_zero()
+        {
+            return Err(Error::TickNotOnSpacing(tick_upper.to_i24()));
+        }
+        Ok(Self {
             pool,
             liquidity,
             tick_lower,
@@ -59,7 +88,7 @@ impl<TP: TickDataProvider> Position<TP> {
             _token0_amount: None,
             _token1_amount: None,
             _mint_amounts: None,
-        }
+        })
     }
 
     /// Returns the price of token0 at the lower tick
@@ -82,36 +111,29 @@ impl<TP: TickDataProvider> Position<TP> {
         )
     }
 
+    /// Returns how far below and above the current pool price the position's range reaches, as
+    /// percentages of the current price of token0
+    ///
+    /// Either percentage is negative when the position is out of range on that side.
+    #[inline]
+    pub fn range_band_percent(&self) -> Result<(Percent, Percent), Error> {
+        let token0_price = self.pool.token0_price().as_fraction();
+        let inverted = token0_price.invert();
+        let low = (token0_price.clone() - self.token0_price_lower()?.as_fraction()) * &inverted;
+        let high = (self.token0_price_upper()?.as_fraction() - token0_price) * &inverted;
+        Ok((
+            Percent::new(low.numerator, low.denominator),
+            Percent::new(high.numerator, high.denominator),
+        ))
+    }
+
     /// Returns the amount of token0 that this position's liquidity could be burned for at the
     /// current pool price
     #[inline]
     pub fn amount0(&self) -> Result<CurrencyAmount<Currency>, Error> {
-        if self.pool.tick_current < self.tick_lower {
-            CurrencyAmount::from_raw_amount(
-                self.pool.currency0.clone(),
-                get_amount_0_delta(
-                    get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
-                    get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
-                    self.liquidity,
-                    false,
-                )?
-                .to_big_int(),
-            )
-        } else if self.pool.tick_current < self.tick_upper {
-            CurrencyAmount::from_raw_amount(
-                self.pool.currency0.clone(),
-                get_amount_0_delta(
-                    self.pool.sqrt_price_x96,
-                    get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
-                    self.liquidity,
-                    false,
-                )?
-                .to_big_int(),
-            )
-        } else {
-            CurrencyAmount::from_raw_amount(self.pool.currency0.clone(), BigInt::ZERO)
-        }
-        .map_err(Error::Core)
+        let (amount0, _) = self.amounts(false)?;
+        CurrencyAmount::from_raw_amount(self.pool.currency0.clone(), amount0.to_big_int())
+            .map_err(Error::Core)
     }
 
     /// Returns the amount of token0 that this position's liquidity could be burned for at the
@@ -130,32 +152,21 @@ impl<TP: TickDataProvider> Position<TP> {
     /// current pool price
     #[inline]
     pub fn amount1(&self) -> Result<CurrencyAmount<Currency>, Error> {
-        if self.pool.tick_current < self.tick_lower {
-            CurrencyAmount::from_raw_amount(self.pool.currency1.clone(), BigInt::ZERO)
-        } else if self.pool.tick_current < self.tick_upper {
-            CurrencyAmount::from_raw_amount(
-                self.pool.currency1.clone(),
-                get_amount_1_delta(
-                    get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
-                    self.pool.sqrt_price_x96,
-                    self.liquidity,
-                    false,
-                )?
-                .to_big_int(),
-            )
-        } else {
-            CurrencyAmount::from_raw_amount(
-                self.pool.currency1.clone(),
-                get_amount_1_delta(
-                    get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
-                    get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
-                    self.liquidity,
-                    false,
-                )?
-                .to_big_int(),
-            )
-        }
-        .map_err(Error::Core)
+        let (_, amount1) = self.amounts(false)?;
+        CurrencyAmount::from_raw_amount(self.pool.currency1.clone(), amount1.to_big_int())
+            .map_err(Error::Core)
+    }
+
+    /// Returns the amounts of currency0 and currency1 for the position's liquidity at the current
+    /// pool price, see [`amounts_for_liquidity`]
+    fn amounts(&self, round_up: bool) -> Result<(U256, U256), Error> {
+        amounts_for_liquidity(
+            self.pool.sqrt_price_x96,
+            get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
+            get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
+            self.liquidity,
+            round_up,
+        )
     }
 
     /// Returns the amount of token1 that this position's liquidity could be burned for at the
@@ -170,6 +181,79 @@ impl<TP: TickDataProvider> Position<TP> {
         Ok(amount)
     }
 
+    /// Computes the raw fees owed to the position since the fee growth inside its range was last
+    /// checkpointed, i.e. `liquidity * (fee_growth_inside_x128 - fee_growth_inside_last_x128) >>
+    /// 128` for each currency
+    ///
+    /// ## Note
+    ///
+    /// Fee growth values are allowed to overflow in `uint256`, so the differences are computed with
+    /// wrapping subtraction like the unchecked math of the pool manager. The product is computed
+    /// in full precision, so the result can't overflow.
+    ///
+    /// ## Arguments
+    ///
+    /// * `fee_growth_inside0_x128`: The current fee growth of currency0 inside the position's range
+    /// * `fee_growth_inside1_x128`: The current fee growth of currency1 inside the position's range
+    /// * `fee_growth_inside0_last_x128`: The fee growth of currency0 inside the range as of the
+    ///   position's last update
+    /// * `fee_growth_inside1_last_x128`: The fee growth of currency1 inside the range as of the
+    ///   position's last update
+    ///
+    /// returns: The raw fees owed in currency0 and currency1
+    #[inline]
+    #[must_use]
+    pub fn compute_fees(
+        &self,
+        fee_growth_inside0_x128: U256,
+        fee_growth_inside1_x128: U256,
+        fee_growth_inside0_last_x128: U256,
+        fee_growth_inside1_last_x128: U256,
+    ) -> (U256, U256) {
+        let liquidity = U256::from(self.liquidity);
+        // split the delta into its high and low 128 bits so that neither product overflows
+        let fees = |delta: U256| {
+            (delta >> 128) * liquidity + (((delta & (Q128 - U256::from(1))) * liquidity) >> 128)
+        };
+        (
+            fees(fee_growth_inside0_x128.wrapping_sub(fee_growth_inside0_last_x128)),
+            fees(fee_growth_inside1_x128.wrapping_sub(fee_growth_inside1_last_x128)),
+        )
+    }
+
+    /// Computes the fees owed to the position since the fee growth inside its range was last
+    /// checkpointed, as [`Position::compute_fees`] attached to the pool's currencies
+    ///
+    /// ## Arguments
+    ///
+    /// * `fee_growth_inside0_x128`: The current fee growth of currency0 inside the position's range
+    /// * `fee_growth_inside1_x128`: The current fee growth of currency1 inside the position's range
+    /// * `fee_growth_inside0_last_x128`: The fee growth of currency0 inside the range as of the
+    ///   position's last update
+    /// * `fee_growth_inside1_last_x128`: The fee growth of currency1 inside the range as of the
+    ///   position's last update
+    ///
+    /// returns: The fees owed in currency0 and currency1
+    #[inline]
+    pub fn fees_owed(
+        &self,
+        fee_growth_inside0_x128: U256,
+        fee_growth_inside1_x128: U256,
+        fee_growth_inside0_last_x128: U256,
+        fee_growth_inside1_last_x128: U256,
+    ) -> Result<(CurrencyAmount<Currency>, CurrencyAmount<Currency>), Error> {
+        let (fees0, fees1) = self.compute_fees(
+            fee_growth_inside0_x128,
+            fee_growth_inside1_x128,
+            fee_growth_inside0_last_x128,
+            fee_growth_inside1_last_x128,
+        );
+        Ok((
+            CurrencyAmount::from_raw_amount(self.pool.currency0.clone(), fees0.to_big_int())?,
+            CurrencyAmount::from_raw_amount(self.pool.currency1.clone(), fees1.to_big_int())?,
+        ))
+    }
+
     /// Returns the lower and upper sqrt ratios if the price 'slips' up to slippage tolerance
     /// percentage
     ///
@@ -204,31 +288,49 @@ impl<TP: TickDataProvider> Position<TP> {
         (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper)
     }
 
-    /// Returns the maximum amounts that must be sent in order to safely mint the amount of
-    /// liquidity held by the position
+    /// Returns the value of the position's amounts in terms of one of the pool's currencies, the
+    /// amount of the other currency being converted at the pool's current price
     ///
-    /// ## Note
+    /// ## Arguments
     ///
-    /// In v4, minting and increasing is protected by maximum amounts of token0 and token1.
+    /// * `quote`: The currency to denominate the value in
+    ///
+    /// ## Returns
+    ///
+    /// [`Error::InvalidCurrency`] if `quote` is not one of the pool's currencies
+    #[inline]
+    pub fn value_in(&mut self, quote: &Currency) -> Result<CurrencyAmount<Currency>, Error> {
+        let amount0 = self.amount0_cached()?;
+        let amount1 = self.amount1_cached()?;
+        let (quote_amount, other_amount) = if self.pool.currency0.equals(quote) {
+            (amount0, amount1)
+        } else if self.pool.currency1.equals(quote) {
+            (amount1, amount0)
+        } else {
+            return Err(Error::InvalidCurrency);
+        };
+        let other_price = self.pool.price_of(&other_amount.currency)?;
+        Ok(quote_amount.add(&other_price.quote(&other_amount)?)?)
+    }
+
+    /// Returns the counterfactual pools at the lowest and highest prices that the pool is allowed
+    /// to "slip" to, used to bound the amounts when minting or burning the position
     ///
     /// ## Arguments
     ///
-    /// * `slippage_tolerance`: Tolerance of unfavorable slippage from the current price
+    /// * `slippage_tolerance`: The amount by which the price can 'slip' before the transaction will
+    ///   revert
     ///
     /// ## Returns
     ///
-    /// The amounts, with slippage
+    /// (pool_lower, pool_upper), with zero liquidity since it doesn't matter for the amounts
     #[inline]
-    pub fn mint_amounts_with_slippage(
-        &mut self,
+    pub fn counterfactual_pools(
+        &self,
         slippage_tolerance: &Percent,
-    ) -> Result<MintAmounts, Error> {
-        // get lower/upper prices
-        // these represent the lowest and highest prices that the pool is allowed to "slip" to
+    ) -> Result<(Pool, Pool), Error> {
         let (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper) =
             self.ratios_after_slippage(slippage_tolerance);
-
-        // construct counterfactual pools from the lower bounded price and the upper bounded price
         let pool_lower = Pool::new(
             self.pool.currency0.clone(),
             self.pool.currency1.clone(),
@@ -236,7 +338,7 @@ impl<TP: TickDataProvider> Position<TP> {
             self.pool.tick_spacing.to_i24().as_i32(),
             self.pool.hooks,
             sqrt_ratio_x96_lower,
-            0, // liquidity doesn't matter
+            0,
         )?;
         let pool_upper = Pool::new(
             self.pool.currency0.clone(),
@@ -245,8 +347,32 @@ impl<TP: TickDataProvider> Position<TP> {
             self.pool.tick_spacing.to_i24().as_i32(),
             self.pool.hooks,
             sqrt_ratio_x96_upper,
-            0, // liquidity doesn't matter
+            0,
         )?;
+        Ok((pool_lower, pool_upper))
+    }
+
+    /// Returns the maximum amounts that must be sent in order to safely mint the amount of
+    /// liquidity held by the position
+    ///
+    /// ## Note
+    ///
+    /// In v4, minting and increasing is protected by maximum amounts of token0 and token1.
+    ///
+    /// ## Arguments
+    ///
+    /// * `slippage_tolerance`: Tolerance of unfavorable slippage from the current price
+    ///
+    /// ## Returns
+    ///
+    /// The amounts, with slippage
+    #[inline]
+    pub fn mint_amounts_with_slippage(
+        &mut self,
+        slippage_tolerance: &Percent,
+    ) -> Result<MintAmounts, Error> {
+        // construct counterfactual pools from the lower bounded price and the upper bounded price
+        let (pool_lower, pool_upper) = self.counterfactual_pools(slippage_tolerance)?;
 
         // Note: Slippage derivation in v4 is different from v3.
         // When creating a position (minting) or adding to a position (increasing) slippage is
@@ -277,6 +403,58 @@ impl<TP: TickDataProvider> Position<TP> {
         Ok(MintAmounts { amount0, amount1 })
     }
 
+    /// Returns [`Self::mint_amounts_with_slippage`] as currency amounts of the pool's currencies.
+    /// The native currency of a native pool is kept as is rather than wrapped.
+    ///
+    /// ## Arguments
+    ///
+    /// * `slippage_tolerance`: Tolerance of unfavorable slippage from the current price
+    ///
+    /// ## Returns
+    ///
+    /// The amounts of currency0 and currency1, with slippage
+    #[inline]
+    pub fn mint_currency_amounts_with_slippage(
+        &mut self,
+        slippage_tolerance: &Percent,
+    ) -> Result<(CurrencyAmount<Currency>, CurrencyAmount<Currency>), Error> {
+        let MintAmounts { amount0, amount1 } =
+            self.mint_amounts_with_slippage(slippage_tolerance)?;
+        Ok((
+            CurrencyAmount::from_raw_amount(self.pool.currency0.clone(), amount0.to_big_int())?,
+            CurrencyAmount::from_raw_amount(self.pool.currency1.clone(), amount1.to_big_int())?,
+        ))
+    }
+
+    /// Returns the most that adding the position's liquidity can spend with the given slippage
+    /// tolerance, matching the `value` and maximum amounts of [`add_call_parameters`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `slippage_tolerance`: Tolerance of unfavorable slippage from the current price
+    /// * `use_native`: Whether currency0 is paid in the native currency, which in v4 is always
+    ///   currency0 of a native pool
+    ///
+    /// ## Returns
+    ///
+    /// (value, amount0, amount1), the native value to send with the transaction and the maximum
+    /// amounts of currency0 and currency1
+    #[inline]
+    pub fn max_spend_with_slippage(
+        &mut self,
+        slippage_tolerance: &Percent,
+        use_native: bool,
+    ) -> Result<(U256, CurrencyAmount<Currency>, CurrencyAmount<Currency>), Error> {
+        let MintAmounts { amount0, amount1 } =
+            self.mint_amounts_with_slippage(slippage_tolerance)?;
+        let value = if use_native { amount0 } else { U256::ZERO };
+        Ok((
+            value,
+            CurrencyAmount::from_raw_amount(self.pool.currency0.clone(), amount0.to_big_int())?,
+            CurrencyAmount::from_raw_amount(self.pool.currency1.clone(), amount1.to_big_int())?,
+        ))
+    }
+
     /// Returns the minimum amounts that should be requested in order to safely burn the amount of
     /// liquidity held by the position with the given slippage tolerance
     ///
@@ -292,29 +470,8 @@ impl<TP: TickDataProvider> Position<TP> {
         &self,
         slippage_tolerance: &Percent,
     ) -> Result<(U256, U256), Error> {
-        // get lower/upper prices
-        let (sqrt_ratio_x96_lower, sqrt_ratio_x96_upper) =
-            self.ratios_after_slippage(slippage_tolerance);
-
         // construct counterfactual pools
-        let pool_lower = Pool::new(
-            self.pool.currency0.clone(),
-            self.pool.currency1.clone(),
-            self.pool.fee,
-            self.pool.tick_spacing.to_i24().as_i32(),
-            self.pool.hooks,
-            sqrt_ratio_x96_lower,
-            0, // liquidity doesn't matter
-        )?;
-        let pool_upper = Pool::new(
-            self.pool.currency0.clone(),
-            self.pool.currency1.clone(),
-            self.pool.fee,
-            self.pool.tick_spacing.to_i24().as_i32(),
-            self.pool.hooks,
-            sqrt_ratio_x96_upper,
-            0, // liquidity doesn't matter
-        )?;
+        let (pool_lower, pool_upper) = self.counterfactual_pools(slippage_tolerance)?;
 
         // we want the smaller amounts...
         // ...which occurs at the upper price for amount0...
@@ -343,42 +500,8 @@ impl<TP: TickDataProvider> Position<TP> {
     /// by the position at the current price for the pool
     #[inline]
     pub fn mint_amounts(&self) -> Result<MintAmounts, Error> {
-        Ok(if self.pool.tick_current < self.tick_lower {
-            MintAmounts {
-                amount0: get_amount_0_delta(
-                    get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
-                    get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
-                    self.liquidity,
-                    true,
-                )?,
-                amount1: U256::ZERO,
-            }
-        } else if self.pool.tick_current < self.tick_upper {
-            MintAmounts {
-                amount0: get_amount_0_delta(
-                    self.pool.sqrt_price_x96,
-                    get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
-                    self.liquidity,
-                    true,
-                )?,
-                amount1: get_amount_1_delta(
-                    get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
-                    self.pool.sqrt_price_x96,
-                    self.liquidity,
-                    true,
-                )?,
-            }
-        } else {
-            MintAmounts {
-                amount0: U256::ZERO,
-                amount1: get_amount_1_delta(
-                    get_sqrt_ratio_at_tick(self.tick_lower.to_i24())?,
-                    get_sqrt_ratio_at_tick(self.tick_upper.to_i24())?,
-                    self.liquidity,
-                    true,
-                )?,
-            }
-        })
+        let (amount0, amount1) = self.amounts(true)?;
+        Ok(MintAmounts { amount0, amount1 })
     }
 
     /// Returns the minimum amounts that must be sent in order to mint the amount of liquidity held
@@ -432,6 +555,73 @@ impl<TP: TickDataProvider> Position<TP> {
         })
     }
 
+    /// Returns the [`AllowanceTransferPermitSingle`] for adding liquidity to a position when only
+    /// one of its currencies needs a permit2 approval
+    ///
+    /// ## Arguments
+    ///
+    /// * `slippage_tolerance`: The amount by which the price can 'slip' before the transaction will
+    ///   revert
+    /// * `spender`: The spender of the permit (should usually be the [`PositionManager`])
+    /// * `nonce`: A valid permit2 nonce
+    /// * `deadline`: The deadline for the permit
+    /// * `for_currency0`: Whether the permit is for currency0, otherwise for currency1
+    ///
+    /// ## Returns
+    ///
+    /// The permit, or [`Error::InvalidCurrency`] if the selected currency is native, which can't be
+    /// approved with permit2
+    #[inline]
+    pub fn permit_single_data(
+        &mut self,
+        slippage_tolerance: &Percent,
+        spender: Address,
+        nonce: U256,
+        deadline: U256,
+        for_currency0: bool,
+    ) -> Result<AllowanceTransferPermitSingle, Error> {
+        let currency = if for_currency0 {
+            &self.pool.currency0
+        } else {
+            &self.pool.currency1
+        };
+        if currency.is_native() {
+            return Err(Error::InvalidCurrency);
+        }
+        let token = currency.address();
+        let MintAmounts { amount0, amount1 } =
+            self.mint_amounts_with_slippage(slippage_tolerance)?;
+        Ok(AllowanceTransferPermitSingle {
+            details: IAllowanceTransfer::PermitDetails {
+                token,
+                amount: U160::from(if for_currency0 { amount0 } else { amount1 }),
+                expiration: U48::from(deadline),
+                nonce: U48::from(nonce),
+            },
+            spender,
+            sigDeadline: deadline,
+        })
+    }
+
+    /// Returns the key of the position in the pool manager, as computed by
+    /// [`calculate_position_key`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `owner`: The owner of the position, e.g. the [`PositionManager`]
+    /// * `salt`: The salt of the position, e.g. the token ID for positions of the
+    ///   [`PositionManager`]
+    #[inline]
+    #[must_use]
+    pub fn position_key(&self, owner: Address, salt: B256) -> B256 {
+        calculate_position_key(
+            owner,
+            self.tick_lower.to_i24(),
+            self.tick_upper.to_i24(),
+            salt,
+        )
+    }
+
     /// Computes the maximum amount of liquidity received for a given amount of token0, token1,
     /// and the prices at the tick boundaries.
     ///
@@ -467,12 +657,7 @@ impl<TP: TickDataProvider> Position<TP> {
             amount1,
             use_full_precision,
         );
-        Ok(Self::new(
-            pool,
-            liquidity.to_u128().unwrap(),
-            tick_lower,
-            tick_upper,
-        ))
+        Self::try_new(pool, liquidity.to_u128().unwrap(), tick_lower, tick_upper)
     }
 
     /// Computes a position with the maximum amount of liquidity received for a given amount of
@@ -525,6 +710,38 @@ impl<TP: TickDataProvider> Position<TP> {
     }
 }
 
+impl<TP: Clone + TickDataProvider> Position<TP> {
+    /// Computes the result of moving the position's liquidity into a new tick range
+    ///
+    /// ## Arguments
+    ///
+    /// * `new_lower`: The lower tick of the new range
+    /// * `new_upper`: The upper tick of the new range
+    ///
+    /// ## Returns
+    ///
+    /// (amount0, amount1, position), the token amounts freed by burning the current position and
+    /// the position with the maximum liquidity mintable from them in the new range
+    #[inline]
+    pub fn rebalance_to(
+        &self,
+        new_lower: TP::Index,
+        new_upper: TP::Index,
+    ) -> Result<(CurrencyAmount<Currency>, CurrencyAmount<Currency>, Self), Error> {
+        let amount0 = self.amount0()?;
+        let amount1 = self.amount1()?;
+        let position = Self::from_amounts(
+            self.pool.clone(),
+            new_lower,
+            new_upper,
+            U256::from_big_int(amount0.quotient()),
+            U256::from_big_int(amount1.quotient()),
+            true,
+        )?;
+        Ok((amount0, amount1, position))
+    }
+}
+
 /// Computes the position key for a given position
 #[inline]
 #[must_use]
@@ -537,10 +754,55 @@ pub fn calculate_position_key(
     keccak256((owner, tick_lower, tick_upper, salt).abi_encode_packed())
 }
 
+/// Computes the amounts of currency0 and currency1 that correspond to the given liquidity between
+/// two sqrt price boundaries at the given price
+///
+/// Below the range the liquidity is held entirely in currency0, above it entirely in currency1.
+///
+/// ## Arguments
+///
+/// * `sqrt_price_x96`: The current sqrt price
+/// * `sqrt_ratio_a_x96`: The sqrt price of one boundary of the range
+/// * `sqrt_ratio_b_x96`: The sqrt price of the other boundary of the range
+/// * `liquidity`: The liquidity of the range
+/// * `round_up`: Whether to round the amounts up, e.g. for the amounts required to mint the
+///   liquidity, or down, e.g. for the amounts the liquidity could be burned for
+///
+/// returns: The amounts of currency0 and currency1
+#[inline]
+pub fn amounts_for_liquidity(
+    sqrt_price_x96: U160,
+    mut sqrt_ratio_a_x96: U160,
+    mut sqrt_ratio_b_x96: U160,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(U256, U256), Error> {
+    if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
+        (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = (sqrt_ratio_b_x96, sqrt_ratio_a_x96);
+    }
+    Ok(if sqrt_price_x96 <= sqrt_ratio_a_x96 {
+        (
+            get_amount_0_delta(sqrt_ratio_a_x96, sqrt_ratio_b_x96, liquidity, round_up)?,
+            U256::ZERO,
+        )
+    } else if sqrt_price_x96 < sqrt_ratio_b_x96 {
+        (
+            get_amount_0_delta(sqrt_price_x96, sqrt_ratio_b_x96, liquidity, round_up)?,
+            get_amount_1_delta(sqrt_ratio_a_x96, sqrt_price_x96, liquidity, round_up)?,
+        )
+    } else {
+        (
+            U256::ZERO,
+            get_amount_1_delta(sqrt_ratio_a_x96, sqrt_ratio_b_x96, liquidity, round_up)?,
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::tests::*;
+    use alloy_primitives::address;
     use once_cell::sync::Lazy;
 
     static POOL_SQRT_RATIO_START: Lazy<U160> =
@@ -563,6 +825,14 @@ mod tests {
         )
         .unwrap()
     });
+    static POSITION: Lazy<Position> = Lazy::new(|| {
+        Position::new(
+            DAI_USDC.clone(),
+            100e18 as u128,
+            nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) - TICK_SPACING * 2,
+            nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) + TICK_SPACING * 2,
+        )
+    });
 
     mod mint_amounts_with_slippage {
         use super::*;
@@ -672,4 +942,610 @@ mod tests {
             }
         }
     }
+
+    mod mint_currency_amounts_with_slippage {
+        use super::*;
+
+        #[test]
+        fn attaches_pool_currencies() {
+            let mut position = POSITION.clone();
+            let slippage_tolerance = Percent::new(5, 100);
+            let (amount0, amount1) = position
+                .mint_currency_amounts_with_slippage(&slippage_tolerance)
+                .unwrap();
+            let MintAmounts {
+                amount0: expected0,
+                amount1: expected1,
+            } = position
+                .mint_amounts_with_slippage(&slippage_tolerance)
+                .unwrap();
+            assert!(amount0.currency.equals(&DAI.clone()));
+            assert!(amount1.currency.equals(&USDC.clone()));
+            assert_eq!(amount0.quotient(), expected0.to_big_int());
+            assert_eq!(amount1.quotient(), expected1.to_big_int());
+        }
+
+        #[test]
+        fn keeps_native_currency() {
+            let pool = Pool::new(
+                ETHER.clone().into(),
+                USDC.clone().into(),
+                FeeAmount::LOW.into(),
+                TICK_SPACING,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap();
+            let mut position = Position::new(pool, 100e18 as u128, -TICK_SPACING, TICK_SPACING);
+            let (amount0, amount1) = position
+                .mint_currency_amounts_with_slippage(&Percent::new(5, 100))
+                .unwrap();
+            assert!(amount0.currency.is_native());
+            assert!(amount0.currency.equals(&ETHER.clone()));
+            assert!(amount1.currency.equals(&USDC.clone()));
+        }
+    }
+
+    mod value_in {
+        use super::*;
+
+        #[test]
+        fn sums_both_amounts_when_in_range() {
+            let mut position = POSITION.clone();
+            let amount0 = position.amount0().unwrap();
+            let amount1 = position.amount1().unwrap();
+            assert!(amount0.quotient() > BigInt::ZERO);
+            assert!(amount1.quotient() > BigInt::ZERO);
+
+            let value = position.value_in(&DAI.clone().into()).unwrap();
+            let expected = amount0
+                .add(&DAI_USDC.currency1_price().quote(&amount1).unwrap())
+                .unwrap();
+            assert!(value.currency.equals(&DAI.clone()));
+            assert_eq!(value.quotient(), expected.quotient());
+
+            let value = position.value_in(&USDC.clone().into()).unwrap();
+            let expected = amount1
+                .add(&DAI_USDC.currency0_price().quote(&amount0).unwrap())
+                .unwrap();
+            assert!(value.currency.equals(&USDC.clone()));
+            assert_eq!(value.quotient(), expected.quotient());
+        }
+
+        #[test]
+        fn converts_single_amount_when_out_of_range() {
+            let mut position = Position::new(
+                DAI_USDC.clone(),
+                100e18 as u128,
+                nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) + TICK_SPACING,
+                nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) + TICK_SPACING * 2,
+            );
+            let amount0 = position.amount0().unwrap();
+            assert_eq!(position.amount1().unwrap().quotient(), BigInt::ZERO);
+
+            let value = position.value_in(&DAI.clone().into()).unwrap();
+            assert_eq!(value.quotient(), amount0.quotient());
+            let value = position.value_in(&USDC.clone().into()).unwrap();
+            assert_eq!(
+                value.quotient(),
+                DAI_USDC
+                    .currency0_price()
+                    .quote(&amount0)
+                    .unwrap()
+                    .quotient()
+            );
+        }
+
+        #[test]
+        fn throws_if_quote_is_not_in_pool() {
+            let mut position = Position::new(DAI_USDC.clone(), 1, -TICK_SPACING, TICK_SPACING);
+            assert!(matches!(
+                position.value_in(&WETH.clone().into()),
+                Err(Error::InvalidCurrency)
+            ));
+        }
+    }
+
+    mod max_spend_with_slippage {
+        use super::*;
+
+        #[test]
+        fn is_zero_value_without_native() {
+            let mut position = POSITION.clone();
+            let slippage_tolerance = Percent::new(5, 100);
+            let (value, amount0, amount1) = position
+                .max_spend_with_slippage(&slippage_tolerance, false)
+                .unwrap();
+            let MintAmounts {
+                amount0: expected0,
+                amount1: expected1,
+            } = position
+                .mint_amounts_with_slippage(&slippage_tolerance)
+                .unwrap();
+            assert_eq!(value, U256::ZERO);
+            assert!(amount0.currency.equals(&DAI.clone()));
+            assert!(amount1.currency.equals(&USDC.clone()));
+            assert_eq!(amount0.quotient(), expected0.to_big_int());
+            assert_eq!(amount1.quotient(), expected1.to_big_int());
+        }
+
+        #[test]
+        fn sends_amount0_as_value_with_native() {
+            let pool = Pool::new(
+                ETHER.clone().into(),
+                USDC.clone().into(),
+                FeeAmount::LOW.into(),
+                TICK_SPACING,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap();
+            let mut position = Position::new(pool, 100e18 as u128, -TICK_SPACING, TICK_SPACING);
+            let slippage_tolerance = Percent::new(5, 100);
+            let (value, amount0, amount1) = position
+                .max_spend_with_slippage(&slippage_tolerance, true)
+                .unwrap();
+            let MintAmounts {
+                amount0: expected0,
+                amount1: expected1,
+            } = position
+                .mint_amounts_with_slippage(&slippage_tolerance)
+                .unwrap();
+            assert_eq!(value, expected0);
+            assert!(amount0.currency.is_native());
+            assert_eq!(amount0.quotient(), expected0.to_big_int());
+            assert_eq!(amount1.quotient(), expected1.to_big_int());
+        }
+    }
+
+    mod rebalance_to {
+        use super::*;
+
+        #[test]
+        fn frees_position_amounts_into_new_range() {
+            let new_lower =
+                nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) - TICK_SPACING * 10;
+            let new_upper =
+                nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) + TICK_SPACING * 10;
+            let (amount0, amount1, position) = POSITION.rebalance_to(new_lower, new_upper).unwrap();
+            assert_eq!(amount0.quotient(), POSITION.amount0().unwrap().quotient());
+            assert_eq!(amount1.quotient(), POSITION.amount1().unwrap().quotient());
+            assert_eq!(position.tick_lower, new_lower);
+            assert_eq!(position.tick_upper, new_upper);
+            assert!(position.liquidity > 0);
+            assert!(position.liquidity < POSITION.liquidity);
+            let MintAmounts {
+                amount0: mint0,
+                amount1: mint1,
+            } = position.mint_amounts().unwrap();
+            assert!(mint0 <= U256::from_big_int(amount0.quotient()));
+            assert!(mint1 <= U256::from_big_int(amount1.quotient()));
+        }
+
+        #[test]
+        fn throws_for_invalid_ticks() {
+            assert!(matches!(
+                POSITION.rebalance_to(TICK_SPACING, -TICK_SPACING),
+                Err(Error::InvalidTickOrder)
+            ));
+            assert!(matches!(
+                POSITION.rebalance_to(-TICK_SPACING + 1, TICK_SPACING),
+                Err(Error::TickNotOnSpacing(_))
+            ));
+        }
+    }
+
+    mod try_new {
+        use super::*;
+
+        #[test]
+        fn succeeds_for_valid_ticks() {
+            let position =
+                Position::try_new(DAI_USDC.clone(), 1, -TICK_SPACING, TICK_SPACING).unwrap();
+            assert_eq!(position.tick_lower, -TICK_SPACING);
+            assert_eq!(position.tick_upper, TICK_SPACING);
+        }
+
+        #[test]
+        fn throws_if_ticks_are_not_ordered() {
+            assert!(matches!(
+                Position::try_new(DAI_USDC.clone(), 1, TICK_SPACING, -TICK_SPACING),
+                Err(Error::InvalidTickOrder)
+            ));
+            assert!(matches!(
+                Position::try_new(DAI_USDC.clone(), 1, TICK_SPACING, TICK_SPACING),
+                Err(Error::InvalidTickOrder)
+            ));
+        }
+
+        #[test]
+        fn throws_if_tick_lower_is_not_on_spacing() {
+            let result = Position::try_new(DAI_USDC.clone(), 1, -TICK_SPACING + 1, TICK_SPACING);
+            assert!(matches!(result, Err(Error::TickNotOnSpacing(tick)) if tick.as_i32() == -9));
+        }
+
+        #[test]
+        fn throws_if_tick_upper_is_not_on_spacing() {
+            let result = Position::try_new(DAI_USDC.clone(), 1, -TICK_SPACING, TICK_SPACING - 1);
+            assert!(matches!(result, Err(Error::TickNotOnSpacing(tick)) if tick.as_i32() == 9));
+        }
+
+        #[test]
+        fn throws_if_ticks_are_out_of_bounds() {
+            let tick_lower = nearest_usable_tick(MIN_TICK_I32, TICK_SPACING) - TICK_SPACING;
+            assert!(matches!(
+                Position::try_new(DAI_USDC.clone(), 1, tick_lower, 0),
+                Err(Error::TickNotOnSpacing(_))
+            ));
+            let tick_upper = nearest_usable_tick(MAX_TICK_I32, TICK_SPACING) + TICK_SPACING;
+            assert!(matches!(
+                Position::try_new(DAI_USDC.clone(), 1, 0, tick_upper),
+                Err(Error::TickNotOnSpacing(_))
+            ));
+        }
+
+        #[test]
+        #[should_panic(expected = "InvalidTickOrder")]
+        fn new_panics_if_ticks_are_not_ordered() {
+            Position::new(DAI_USDC.clone(), 1, TICK_SPACING, -TICK_SPACING);
+        }
+    }
+
+    #[test]
+    fn position_key_matches_calculate_position_key() {
+        let position = Position::new(
+            DAI_USDC.clone(),
+            1,
+            nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) - TICK_SPACING,
+            nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) + TICK_SPACING,
+        );
+        let owner = address!("bd216513d74c8cf14cf4747e6aaa6420ff64ee9e");
+        let salt = B256::from(U256::from(42));
+        assert_eq!(
+            position.position_key(owner, salt),
+            calculate_position_key(
+                owner,
+                position.tick_lower.to_i24(),
+                position.tick_upper.to_i24(),
+                salt
+            )
+        );
+    }
+
+    mod permit_single_data {
+        use super::*;
+
+        const SPENDER: Address = address!("000000000000000000000000000000000000000b");
+
+        #[test]
+        fn permits_the_selected_currency() {
+            let mut position = POSITION.clone();
+            let slippage_tolerance = Percent::new(5, 100);
+            let MintAmounts { amount0, amount1 } = position
+                .mint_amounts_with_slippage(&slippage_tolerance)
+                .unwrap();
+            let deadline = U256::from(123);
+
+            let permit0 = position
+                .permit_single_data(&slippage_tolerance, SPENDER, U256::from(1), deadline, true)
+                .unwrap();
+            assert_eq!(permit0.details.token, DAI.address());
+            assert_eq!(permit0.details.amount, U160::from(amount0));
+            assert_eq!(permit0.details.nonce, U48::from(1));
+            assert_eq!(permit0.details.expiration, U48::from(deadline));
+            assert_eq!(permit0.spender, SPENDER);
+            assert_eq!(permit0.sigDeadline, deadline);
+
+            let permit1 = position
+                .permit_single_data(&slippage_tolerance, SPENDER, U256::from(1), deadline, false)
+                .unwrap();
+            assert_eq!(permit1.details.token, USDC.address());
+            assert_eq!(permit1.details.amount, U160::from(amount1));
+        }
+
+        #[test]
+        fn throws_for_native_currency() {
+            let pool = Pool::new(
+                ETHER.clone().into(),
+                USDC.clone().into(),
+                FeeAmount::LOW.into(),
+                TICK_SPACING,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap();
+            let mut position = Position::new(pool, 100e18 as u128, -TICK_SPACING, TICK_SPACING);
+            let slippage_tolerance = Percent::new(5, 100);
+            assert!(matches!(
+                position.permit_single_data(
+                    &slippage_tolerance,
+                    SPENDER,
+                    U256::ZERO,
+                    U256::ZERO,
+                    true
+                ),
+                Err(Error::InvalidCurrency)
+            ));
+            assert!(position
+                .permit_single_data(&slippage_tolerance, SPENDER, U256::ZERO, U256::ZERO, false)
+                .is_ok());
+        }
+    }
+
+    mod range_band_percent {
+        use super::*;
+
+        #[test]
+        fn in_range_position() {
+            let position = POSITION.clone();
+            let (low, high) = position.range_band_percent().unwrap();
+            // each side spans between 10 and 30 ticks, i.e. roughly 0.1% to 0.3%
+            assert!(low > Percent::new(1, 1000) && low < Percent::new(3, 1000));
+            assert!(high > Percent::new(1, 1000) && high < Percent::new(3, 1000));
+        }
+
+        #[test]
+        fn out_of_range_position() {
+            let position = Position::new(
+                DAI_USDC.clone(),
+                100e18 as u128,
+                nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) + TICK_SPACING * 2,
+                nearest_usable_tick(*POOL_TICK_CURRENT, TICK_SPACING) + TICK_SPACING * 4,
+            );
+            let (low, high) = position.range_band_percent().unwrap();
+            assert!(low < Percent::new(0, 1));
+            assert!(high > Percent::new(0, 1));
+        }
+    }
+
+    mod fees_owed {
+        use super::*;
+
+        #[test]
+        fn attaches_pool_currencies() {
+            let (fees0, fees1) = POSITION
+                .fees_owed(Q128 + (Q128 >> 1), Q128 >> 2, Q128, U256::ZERO)
+                .unwrap();
+            let (expected0, expected1) =
+                POSITION.compute_fees(Q128 + (Q128 >> 1), Q128 >> 2, Q128, U256::ZERO);
+            assert!(fees0.currency.equals(&DAI_USDC.currency0));
+            assert!(fees1.currency.equals(&DAI_USDC.currency1));
+            assert_eq!(fees0.quotient(), expected0.to_big_int());
+            assert_eq!(fees1.quotient(), expected1.to_big_int());
+        }
+    }
+
+    mod compute_fees {
+        use super::*;
+
+        #[test]
+        fn zero_if_fee_growth_unchanged() {
+            let growth = U256::from(123456789);
+            assert_eq!(
+                POSITION.compute_fees(growth, growth, growth, growth),
+                (U256::ZERO, U256::ZERO)
+            );
+        }
+
+        #[test]
+        fn liquidity_times_fee_growth_delta() {
+            let (fees0, fees1) =
+                POSITION.compute_fees(Q128 * U256::from(3), Q128 >> 2, Q128, U256::ZERO);
+            assert_eq!(fees0, U256::from(200e18 as u128));
+            assert_eq!(fees1, U256::from(25e18 as u128));
+        }
+
+        #[test]
+        fn rounds_down() {
+            let (fees0, fees1) =
+                POSITION.compute_fees(U256::from(1), Q128 - U256::from(1), U256::ZERO, U256::ZERO);
+            assert_eq!(fees0, U256::ZERO);
+            assert_eq!(fees1, U256::from(100e18 as u128 - 1));
+        }
+
+        #[test]
+        fn handles_fee_growth_overflow() {
+            // the last fee growth is 0.5 below 2^256, so the deltas are 0.75 and 2^128 - 1.5
+            let last = U256::MAX - (Q128 >> 1) + U256::from(1);
+            let (fees0, fees1) = POSITION.compute_fees(Q128 >> 2, last - Q128, last, U256::ZERO);
+            assert_eq!(fees0, U256::from(75e18 as u128));
+            assert_eq!(
+                fees1,
+                U256::from(100e18 as u128) * (Q128 - U256::from(1)) - U256::from(50e18 as u128)
+            );
+        }
+
+        #[test]
+        fn matches_mul_div() {
+            let liquidity = U256::from(POSITION.liquidity);
+            let (fees0, fees1) =
+                POSITION.compute_fees(U256::MAX, U256::from(987654321), U256::ZERO, U256::ZERO);
+            assert_eq!(fees0, mul_div(U256::MAX, liquidity, Q128).unwrap());
+            assert_eq!(
+                fees1,
+                mul_div(U256::from(987654321), liquidity, Q128).unwrap()
+            );
+        }
+    }
+
+    mod amounts_for_liquidity {
+        use super::*;
+
+        const LIQUIDITY: u128 = ONE_ETHER;
+        static SQRT_RATIO_LOWER: Lazy<U160> =
+            Lazy::new(|| get_sqrt_ratio_at_tick((-60_i32).to_i24()).unwrap());
+        static SQRT_RATIO_UPPER: Lazy<U160> =
+            Lazy::new(|| get_sqrt_ratio_at_tick(60_i32.to_i24()).unwrap());
+
+        #[test]
+        fn all_currency0_below_range() {
+            let sqrt_price = get_sqrt_ratio_at_tick((-120_i32).to_i24()).unwrap();
+            let (amount0, amount1) = amounts_for_liquidity(
+                sqrt_price,
+                *SQRT_RATIO_LOWER,
+                *SQRT_RATIO_UPPER,
+                LIQUIDITY,
+                true,
+            )
+            .unwrap();
+            assert_eq!(
+                amount0,
+                get_amount_0_delta(*SQRT_RATIO_LOWER, *SQRT_RATIO_UPPER, LIQUIDITY, true).unwrap()
+            );
+            assert_eq!(amount1, U256::ZERO);
+        }
+
+        #[test]
+        fn both_currencies_in_range() {
+            let (amount0, amount1) = amounts_for_liquidity(
+                *SQRT_PRICE_1_1,
+                *SQRT_RATIO_LOWER,
+                *SQRT_RATIO_UPPER,
+                LIQUIDITY,
+                false,
+            )
+            .unwrap();
+            assert_eq!(
+                amount0,
+                get_amount_0_delta(*SQRT_PRICE_1_1, *SQRT_RATIO_UPPER, LIQUIDITY, false).unwrap()
+            );
+            assert_eq!(
+                amount1,
+                get_amount_1_delta(*SQRT_RATIO_LOWER, *SQRT_PRICE_1_1, LIQUIDITY, false).unwrap()
+            );
+            assert!(amount0 > U256::ZERO && amount1 > U256::ZERO);
+        }
+
+        #[test]
+        fn all_currency1_above_range() {
+            let (amount0, amount1) = amounts_for_liquidity(
+                *SQRT_RATIO_UPPER,
+                *SQRT_RATIO_LOWER,
+                *SQRT_RATIO_UPPER,
+                LIQUIDITY,
+                true,
+            )
+            .unwrap();
+            assert_eq!(amount0, U256::ZERO);
+            assert_eq!(
+                amount1,
+                get_amount_1_delta(*SQRT_RATIO_LOWER, *SQRT_RATIO_UPPER, LIQUIDITY, true).unwrap()
+            );
+        }
+
+        #[test]
+        fn sorts_boundaries() {
+            assert_eq!(
+                amounts_for_liquidity(
+                    *SQRT_PRICE_1_1,
+                    *SQRT_RATIO_UPPER,
+                    *SQRT_RATIO_LOWER,
+                    LIQUIDITY,
+                    true
+                )
+                .unwrap(),
+                amounts_for_liquidity(
+                    *SQRT_PRICE_1_1,
+                    *SQRT_RATIO_LOWER,
+                    *SQRT_RATIO_UPPER,
+                    LIQUIDITY,
+                    true
+                )
+                .unwrap()
+            );
+        }
+
+        #[test]
+        fn matches_position_amounts() {
+            let position = POSITION.clone();
+            let sqrt_ratio_lower = get_sqrt_ratio_at_tick(position.tick_lower.to_i24()).unwrap();
+            let sqrt_ratio_upper = get_sqrt_ratio_at_tick(position.tick_upper.to_i24()).unwrap();
+
+            let (amount0, amount1) = amounts_for_liquidity(
+                DAI_USDC.sqrt_price_x96,
+                sqrt_ratio_lower,
+                sqrt_ratio_upper,
+                position.liquidity,
+                false,
+            )
+            .unwrap();
+            assert_eq!(amount0.to_big_int(), position.amount0().unwrap().quotient());
+            assert_eq!(amount1.to_big_int(), position.amount1().unwrap().quotient());
+
+            let MintAmounts { amount0, amount1 } = position.mint_amounts().unwrap();
+            assert_eq!(
+                (amount0, amount1),
+                amounts_for_liquidity(
+                    DAI_USDC.sqrt_price_x96,
+                    sqrt_ratio_lower,
+                    sqrt_ratio_upper,
+                    position.liquidity,
+                    true,
+                )
+                .unwrap()
+            );
+        }
+    }
+
+    mod counterfactual_pools {
+        use super::*;
+
+        static SLIPPAGE_TOLERANCE: Lazy<Percent> = Lazy::new(|| Percent::new(5, 100));
+
+        #[test]
+        fn brackets_the_current_price() {
+            let (pool_lower, pool_upper) =
+                POSITION.counterfactual_pools(&SLIPPAGE_TOLERANCE).unwrap();
+            assert!(pool_lower.sqrt_price_x96 < DAI_USDC.sqrt_price_x96);
+            assert!(pool_upper.sqrt_price_x96 > DAI_USDC.sqrt_price_x96);
+            assert_eq!(pool_lower.pool_id, DAI_USDC.pool_id);
+            assert_eq!(pool_upper.pool_id, DAI_USDC.pool_id);
+        }
+
+        #[test]
+        fn matches_mint_and_burn_amounts_with_slippage() {
+            let (pool_lower, pool_upper) =
+                POSITION.counterfactual_pools(&SLIPPAGE_TOLERANCE).unwrap();
+            let position_lower = Position::new(
+                pool_lower,
+                POSITION.liquidity,
+                POSITION.tick_lower,
+                POSITION.tick_upper,
+            );
+            let position_upper = Position::new(
+                pool_upper,
+                POSITION.liquidity,
+                POSITION.tick_lower,
+                POSITION.tick_upper,
+            );
+
+            let mint_amounts = POSITION
+                .clone()
+                .mint_amounts_with_slippage(&SLIPPAGE_TOLERANCE)
+                .unwrap();
+            assert_eq!(
+                mint_amounts.amount0,
+                position_lower.mint_amounts().unwrap().amount0
+            );
+            assert_eq!(
+                mint_amounts.amount1,
+                position_upper.mint_amounts().unwrap().amount1
+            );
+
+            let (amount0, amount1) = POSITION
+                .burn_amounts_with_slippage(&SLIPPAGE_TOLERANCE)
+                .unwrap();
+            assert_eq!(
+                amount0,
+                U256::from_big_int(position_upper.amount0().unwrap().quotient())
+            );
+            assert_eq!(
+                amount1,
+                U256::from_big_int(position_lower.amount1().unwrap().quotient())
+            );
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use crate::prelude::*;
-use alloc::vec::Vec;
-use alloy_primitives::ChainId;
+use alloc::{vec, vec::Vec};
+use alloy_primitives::{aliases::U24, Bytes, ChainId};
 use uniswap_sdk_core::prelude::{BaseCurrency, Currency, Price};
 use uniswap_v3_sdk::entities::TickDataProvider;
 
@@ -21,6 +21,9 @@ where
     pub path_input: Currency,
     /// equivalent or wrapped/unwrapped output to match pool
     pub path_output: Currency,
+    /// The hook data passed to the hook of each pool when swapping through it, in the order of
+    /// [`Self::pools`]
+    pub hook_data: Vec<Bytes>,
     _mid_price: Option<Price<TInput, TOutput>>,
 }
 
@@ -37,13 +40,53 @@ where
     /// * `pools`: An array of [`Pool`] objects, ordered by the route the swap will take
     /// * `input`: The input currency
     /// * `output`: The output currency
+    ///
+    /// ## Returns
+    ///
+    /// [`Error::EmptyPools`] if `pools` is empty, [`Error::MismatchedChains`] if the pools are not
+    /// all on the same chain, or [`Error::DisconnectedPath`] if the pools don't form a path from
+    /// the input to the output currency
     #[inline]
     pub fn new(pools: Vec<Pool<TP>>, input: TInput, output: TOutput) -> Result<Self, Error> {
-        assert!(!pools.is_empty(), "POOLS");
+        let hook_data = vec![Bytes::new(); pools.len()];
+        Self::new_with_hook_data(pools, input, output, hook_data)
+    }
+
+    /// Creates an instance of route whose pools are passed the given hook data when swapping
+    ///
+    /// ## Arguments
+    ///
+    /// * `pools`: An array of [`Pool`] objects, ordered by the route the swap will take
+    /// * `input`: The input currency
+    /// * `output`: The output currency
+    /// * `hook_data`: The hook data of each pool, in the order of `pools`
+    ///
+    /// ## Returns
+    ///
+    /// [`Error::HookDataLengthMismatch`] if `hook_data` doesn't have one entry per pool, otherwise
+    /// the same as [`Route::new`]
+    #[inline]
+    pub fn new_with_hook_data(
+        pools: Vec<Pool<TP>>,
+        input: TInput,
+        output: TOutput,
+        hook_data: Vec<Bytes>,
+    ) -> Result<Self, Error> {
+        if pools.is_empty() {
+            return Err(Error::EmptyPools);
+        }
+        if hook_data.len() != pools.len() {
+            return Err(Error::HookDataLengthMismatch {
+                pools: pools.len(),
+                hook_data: hook_data.len(),
+            });
+        }
 
         let chain_id = pools[0].chain_id();
         let all_on_same_chain = pools.iter().all(|pool| pool.chain_id() == chain_id);
-        assert!(all_on_same_chain, "CHAIN_IDS");
+        if !all_on_same_chain {
+            return Err(Error::MismatchedChains);
+        }
 
         // throws if pools do not involve the input and output currency or the native/wrapped
         // equivalent
@@ -57,10 +100,12 @@ where
             } else if current_input_currency.equals(&pool.currency1) {
                 &pool.currency0
             } else {
-                panic!("PATH")
+                return Err(Error::DisconnectedPath);
             };
         }
-        assert!(current_input_currency.equals(&path_output), "PATH");
+        if !current_input_currency.equals(&path_output) {
+            return Err(Error::DisconnectedPath);
+        }
 
         Ok(Self {
             pools,
@@ -68,10 +113,23 @@ where
             output,
             path_input,
             path_output,
+            hook_data,
             _mid_price: None,
         })
     }
 
+    /// Returns the route in the opposite direction, from the output currency to the input currency
+    /// through the same pools in reverse order. The path currencies are recomputed, the hook data
+    /// follows its pool and the cached mid price is dropped.
+    #[inline]
+    pub fn reversed(self) -> Result<Route<TOutput, TInput, TP>, Error> {
+        let mut pools = self.pools;
+        pools.reverse();
+        let mut hook_data = self.hook_data;
+        hook_data.reverse();
+        Route::new_with_hook_data(pools, self.output, self.input, hook_data)
+    }
+
     /// Normalizes currency0-currency1 order and selects the next currency/fee step to add to the
     /// path
     #[inline]
@@ -89,11 +147,52 @@ where
         currency_path
     }
 
+    /// Returns the currencies the route swaps through between its input and output, in path order
+    #[inline]
+    pub fn intermediate_currencies(&self) -> Vec<Currency> {
+        let mut currency_path = self.currency_path();
+        currency_path.pop();
+        currency_path.remove(0);
+        currency_path
+    }
+
+    /// Returns the path keys encoding the route for the V4 router, see [`encode_route_to_path`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `exact_output`: Whether the path is for an exact output swap, in which case each key
+    ///   names the currency before its pool rather than after it
+    #[inline]
+    pub fn to_path_keys(&self, exact_output: bool) -> Vec<PathKey> {
+        encode_route_to_path(self, exact_output)
+    }
+
+    /// Returns true if the route swaps through a pool pairing the native currency with its wrapped
+    /// token, e.g. `TOKEN -> [TOKEN/WETH] -> WETH -> [ETH/WETH] -> ETH`
+    #[inline]
+    pub fn requires_mid_path_wrap(&self) -> bool {
+        self.currency_path().windows(2).any(|pair| {
+            pair[0].is_native() != pair[1].is_native()
+                && pair[0].wrapped().equals(pair[1].wrapped())
+        })
+    }
+
     #[inline]
     pub fn chain_id(&self) -> ChainId {
         self.pools[0].chain_id()
     }
 
+    /// Returns the minimum liquidity across the pools of the route, since a route is only as deep
+    /// as its thinnest pool
+    #[inline]
+    pub fn min_pool_liquidity(&self) -> u128 {
+        self.pools
+            .iter()
+            .map(|pool| pool.liquidity)
+            .min()
+            .unwrap_or_default()
+    }
+
     /// Returns the mid price of the route
     #[inline]
     pub fn mid_price(&self) -> Result<Price<TInput, TOutput>, Error> {
@@ -109,6 +208,67 @@ where
         ))
     }
 
+    /// Returns the mid price of the route as input per output, i.e. the inverse of
+    /// [`Route::mid_price`]
+    #[inline]
+    pub fn mid_price_inverted(&self) -> Result<Price<TOutput, TInput>, Error> {
+        Ok(self.mid_price()?.invert())
+    }
+
+    /// Returns the mid price of the route quoted in `quote`, which must be the route's output
+    /// currency
+    ///
+    /// The base and quote of the price are the route's declared input and `quote`, even if the
+    /// pools trade the wrapped or native equivalent.
+    ///
+    /// ## Arguments
+    ///
+    /// * `quote`: The currency to quote the price in
+    #[inline]
+    pub fn price_in_terms_of(&self, quote: &Currency) -> Result<Price<TInput, Currency>, Error> {
+        if !quote.equals(&self.output) {
+            return Err(Error::InvalidCurrency);
+        }
+        let price = self.mid_price()?;
+        Ok(Price::new(
+            self.input.clone(),
+            quote.clone(),
+            price.denominator,
+            price.numerator,
+        ))
+    }
+
+    /// Returns the mid price of the route as if its pools charged the given fees
+    ///
+    /// Fees don't move the mid price, so this equals [`Route::mid_price`] once `fees` is validated
+    /// against the pools; quote with the overrides via [`Route::with_fee_overrides`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `fees`: The LP fee of each pool, in the order of [`Route::pools`]
+    ///
+    /// ## Returns
+    ///
+    /// The mid price, or [`Error::FeeOverridesLengthMismatch`] if there isn't one fee per pool
+    #[inline]
+    pub fn mid_price_with_fee_overrides(
+        &self,
+        fees: &[U24],
+    ) -> Result<Price<TInput, TOutput>, Error> {
+        self.check_fee_overrides(fees)?;
+        self.mid_price()
+    }
+
+    fn check_fee_overrides(&self, fees: &[U24]) -> Result<(), Error> {
+        if fees.len() != self.pools.len() {
+            return Err(Error::FeeOverridesLengthMismatch {
+                pools: self.pools.len(),
+                fees: fees.len(),
+            });
+        }
+        Ok(())
+    }
+
     /// Returns the cached mid price of the route
     #[inline]
     pub fn mid_price_cached(&mut self) -> Result<Price<TInput, TOutput>, Error> {
@@ -121,10 +281,43 @@ where
     }
 }
 
+impl<TInput, TOutput, TP> Route<TInput, TOutput, TP>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: Clone + TickDataProvider,
+{
+    /// Returns a copy of the route whose pools charge the given LP fees instead of their own, for
+    /// what-if quoting, e.g. with [`Trade::from_route`]
+    ///
+    /// Only the fee charged on swaps changes: the `pool_key` and `pool_id` of each pool still
+    /// identify the original pool, so the route encodes the same path as before and must not be
+    /// used to build calldata for pools with the overridden fees.
+    ///
+    /// ## Arguments
+    ///
+    /// * `fees`: The LP fee of each pool, in the order of [`Route::pools`]
+    ///
+    /// ## Returns
+    ///
+    /// The route, or [`Error::FeeOverridesLengthMismatch`] if there isn't one fee per pool
+    #[inline]
+    pub fn with_fee_overrides(&self, fees: &[U24]) -> Result<Self, Error> {
+        self.check_fee_overrides(fees)?;
+        let mut route = self.clone();
+        for (pool, &fee) in route.pools.iter_mut().zip(fees) {
+            pool.fee = fee;
+        }
+        route._mid_price = None;
+        Ok(route)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Pool;
-    use crate::{create_route, tests::*};
+    use super::{Error, Pool, Route};
+    use crate::{create_route, prelude::encode_route_to_path, tests::*};
+    use alloy_primitives::aliases::U24;
     use once_cell::sync::Lazy;
     use uniswap_sdk_core::{prelude::*, token};
     use uniswap_v3_sdk::prelude::*;
@@ -253,15 +446,224 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "PATH")]
     fn does_not_support_weth_to_eth_conversion_without_trading_through_an_eth_to_weth_pool() {
-        create_route!(POOL_0_WETH, POOL_1_ETH; CURRENCY0, CURRENCY1);
+        let result = Route::new(
+            vec![POOL_0_WETH.clone(), POOL_1_ETH.clone()],
+            CURRENCY0.clone(),
+            CURRENCY1.clone(),
+        );
+        assert!(matches!(result, Err(Error::DisconnectedPath)));
     }
 
     #[test]
-    #[should_panic(expected = "PATH")]
     fn does_not_support_eth_to_weth_conversion_without_trading_through_an_eth_to_weth_pool() {
-        create_route!(POOL_1_ETH, POOL_0_WETH; CURRENCY1, CURRENCY0);
+        let result = Route::new(
+            vec![POOL_1_ETH.clone(), POOL_0_WETH.clone()],
+            CURRENCY1.clone(),
+            CURRENCY0.clone(),
+        );
+        assert!(matches!(result, Err(Error::DisconnectedPath)));
+    }
+
+    #[test]
+    fn throws_for_disconnected_pools() {
+        let result = Route::new(
+            vec![POOL_0_1.clone(), POOL_0_1.clone()],
+            CURRENCY0.clone(),
+            CURRENCY1.clone(),
+        );
+        assert!(matches!(result, Err(Error::DisconnectedPath)));
+    }
+
+    #[test]
+    fn throws_for_empty_pools() {
+        let result = Route::<Currency, Currency, NoTickDataProvider>::new(
+            vec![],
+            CURRENCY0.clone(),
+            CURRENCY1.clone(),
+        );
+        assert!(matches!(result, Err(Error::EmptyPools)));
+    }
+
+    #[test]
+    fn throws_for_pools_on_different_chains() {
+        let currency3: Currency =
+            token!(3, "0000000000000000000000000000000000000003", 18, "t3").into();
+        let currency4: Currency =
+            token!(3, "0000000000000000000000000000000000000004", 18, "t4").into();
+        let pool_3_4 = Pool::new(
+            currency3,
+            currency4,
+            FeeAmount::MEDIUM.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            0,
+        )
+        .unwrap();
+        let result = Route::new(
+            vec![POOL_0_1.clone(), pool_3_4],
+            CURRENCY0.clone(),
+            CURRENCY1.clone(),
+        );
+        assert!(matches!(result, Err(Error::MismatchedChains)));
+    }
+
+    mod requires_mid_path_wrap {
+        use super::*;
+
+        #[test]
+        fn false_for_routes_without_eth_weth_pools() {
+            assert!(!create_route!(POOL_0_1, CURRENCY0, CURRENCY1).requires_mid_path_wrap());
+            assert!(!create_route!(POOL_0_WETH, ETHER, CURRENCY0).requires_mid_path_wrap());
+        }
+
+        #[test]
+        fn true_for_routes_through_an_eth_weth_pool() {
+            let route = create_route!(POOL_0_WETH, POOL_ETH_WETH, POOL_1_ETH; CURRENCY0, CURRENCY1);
+            assert!(route.requires_mid_path_wrap());
+            let route = create_route!(POOL_0_WETH, POOL_ETH_WETH; CURRENCY0, ETHER);
+            assert!(route.requires_mid_path_wrap());
+        }
+    }
+
+    mod hook_data {
+        use super::*;
+        use alloy_primitives::{bytes, Bytes};
+
+        #[test]
+        fn defaults_to_empty_per_pool() {
+            let route = create_route!(POOL_0_1, POOL_1_ETH; CURRENCY0, ETHER);
+            assert_eq!(route.hook_data, vec![Bytes::new(); 2]);
+        }
+
+        #[test]
+        fn throws_if_length_does_not_match_pools() {
+            let result = Route::new_with_hook_data(
+                vec![POOL_0_1.clone(), POOL_1_ETH.clone()],
+                CURRENCY0.clone(),
+                ETHER.clone(),
+                vec![bytes!("01")],
+            );
+            assert!(matches!(
+                result,
+                Err(Error::HookDataLengthMismatch {
+                    pools: 2,
+                    hook_data: 1
+                })
+            ));
+        }
+
+        #[test]
+        fn follows_pools_when_reversed() {
+            let route = Route::new_with_hook_data(
+                vec![POOL_0_1.clone(), POOL_1_ETH.clone()],
+                CURRENCY0.clone(),
+                ETHER.clone(),
+                vec![bytes!("01"), bytes!("02")],
+            )
+            .unwrap();
+            let reversed = route.reversed().unwrap();
+            assert_eq!(reversed.hook_data, vec![bytes!("02"), bytes!("01")]);
+        }
+
+        #[test]
+        fn is_encoded_into_path_keys() {
+            let route = Route::new_with_hook_data(
+                vec![POOL_0_1.clone(), POOL_1_ETH.clone()],
+                CURRENCY0.clone(),
+                ETHER.clone(),
+                vec![bytes!("01"), bytes!("02")],
+            )
+            .unwrap();
+            for exact_output in [false, true] {
+                let path_keys = route.to_path_keys(exact_output);
+                assert_eq!(path_keys[0].hookData, bytes!("01"));
+                assert_eq!(path_keys[1].hookData, bytes!("02"));
+            }
+        }
+    }
+
+    #[test]
+    fn intermediate_currencies_excludes_input_and_output() {
+        let route = create_route!(POOL_0_1, POOL_1_ETH; CURRENCY0, ETHER);
+        assert_eq!(route.intermediate_currencies(), vec![CURRENCY1.clone()]);
+        let route = create_route!(POOL_0_1, CURRENCY0, CURRENCY1);
+        assert!(route.intermediate_currencies().is_empty());
+    }
+
+    #[test]
+    fn to_path_keys_encodes_route_to_path() {
+        let route = create_route!(POOL_0_1, POOL_1_ETH; CURRENCY0, ETHER);
+        assert_eq!(
+            route.to_path_keys(false),
+            encode_route_to_path(&route, false)
+        );
+        assert_eq!(route.to_path_keys(true), encode_route_to_path(&route, true));
+        assert_eq!(
+            route.to_path_keys(false)[0].intermediateCurrency,
+            CURRENCY1.address()
+        );
+        assert_eq!(
+            route.to_path_keys(true)[0].intermediateCurrency,
+            CURRENCY0.address()
+        );
+    }
+
+    #[test]
+    fn min_pool_liquidity_returns_liquidity_of_thinnest_pool() {
+        let pool_0_1 = Pool {
+            liquidity: 3_000,
+            ..POOL_0_1.clone()
+        };
+        let pool_1_eth = Pool {
+            liquidity: 1_000,
+            ..POOL_1_ETH.clone()
+        };
+        let route = create_route!(pool_0_1, pool_1_eth; CURRENCY0, ETHER);
+        assert_eq!(route.min_pool_liquidity(), 1_000);
+    }
+
+    mod reversed {
+        use super::*;
+
+        #[test]
+        fn reverses_pools_and_currencies() {
+            let route = create_route!(POOL_0_1, POOL_1_ETH; CURRENCY0, ETHER)
+                .reversed()
+                .unwrap();
+            assert_eq!(route.pools, vec![POOL_1_ETH.clone(), POOL_0_1.clone()]);
+            assert_eq!(route.input, ETHER.clone());
+            assert_eq!(route.output, CURRENCY0.clone());
+            assert_eq!(route.path_input, Currency::from(ETHER.clone()));
+            assert_eq!(route.path_output, CURRENCY0.clone());
+            assert_eq!(
+                route.currency_path(),
+                vec![ETHER.clone().into(), CURRENCY1.clone(), CURRENCY0.clone()]
+            );
+        }
+
+        #[test]
+        fn keeps_wrapped_path_currencies() {
+            let route = create_route!(POOL_0_WETH; ETHER, CURRENCY0);
+            assert_eq!(route.path_input, Currency::from(WETH.clone()));
+            let reversed = route.clone().reversed().unwrap();
+            assert_eq!(reversed.input, CURRENCY0.clone());
+            assert_eq!(reversed.output, ETHER.clone());
+            assert_eq!(reversed.path_input, CURRENCY0.clone());
+            assert_eq!(reversed.path_output, Currency::from(WETH.clone()));
+            assert_eq!(reversed.reversed().unwrap(), route);
+        }
+
+        #[test]
+        fn drops_cached_mid_price() {
+            let mut route = create_route!(POOL_0_1, CURRENCY0, CURRENCY1);
+            route.mid_price_cached().unwrap();
+            assert!(route._mid_price.is_some());
+            let reversed = route.reversed().unwrap();
+            assert!(reversed._mid_price.is_none());
+            assert_eq!(reversed, create_route!(POOL_0_1, CURRENCY1, CURRENCY0));
+        }
     }
 
     #[test]
@@ -401,11 +803,83 @@ mod tests {
             assert!(price.quote_currency.equals(&ETHER.clone()));
         }
 
+        #[test]
+        fn inverted_is_input_per_output() {
+            let route = create_route!(POOL_0_ETH, ETHER, CURRENCY0);
+            let price = route.mid_price_inverted().unwrap();
+            assert_eq!(price.to_fixed(4, None), "0.3333");
+            assert!(price.base_currency.equals(&CURRENCY0.clone()));
+            assert!(price.quote_currency.equals(&ETHER.clone()));
+        }
+
+        #[test]
+        fn inverted_reports_declared_currencies_on_a_weth_pool() {
+            let route = create_route!(POOL_0_WETH, ETHER, CURRENCY0);
+            let price = route.mid_price_inverted().unwrap();
+            assert_eq!(price.to_fixed(4, None), "1.0000");
+            assert!(price.base_currency.equals(&CURRENCY0.clone()));
+            assert!(price.quote_currency.equals(&ETHER.clone()));
+        }
+
+        #[test]
+        fn price_in_terms_of_output_is_mid_price() {
+            let route = create_route!(POOL_0_1, POOL_1_ETH; CURRENCY0, ETHER);
+            let quote: Currency = ETHER.clone().into();
+            let price = route.price_in_terms_of(&quote).unwrap();
+            let mid_price = route.mid_price().unwrap();
+            assert_eq!(price.numerator, mid_price.numerator);
+            assert_eq!(price.denominator, mid_price.denominator);
+            assert!(price.base_currency.equals(&CURRENCY0.clone()));
+            assert!(price.quote_currency.equals(&ETHER.clone()));
+        }
+
+        #[test]
+        fn price_in_terms_of_other_currency_errors() {
+            let route = create_route!(POOL_0_1, POOL_1_ETH; CURRENCY0, ETHER);
+            assert!(matches!(
+                route.price_in_terms_of(&CURRENCY1),
+                Err(Error::InvalidCurrency)
+            ));
+            let weth: Currency = WETH.clone().into();
+            assert!(matches!(
+                route.price_in_terms_of(&weth),
+                Err(Error::InvalidCurrency)
+            ));
+        }
+
+        #[test]
+        fn fee_overrides_do_not_move_mid_price() {
+            let route = create_route!(POOL_0_1, POOL_1_2; CURRENCY0, CURRENCY2);
+            let fees = [U24::from(FeeAmount::LOWEST), U24::from(FeeAmount::HIGH)];
+            assert_eq!(
+                route.mid_price_with_fee_overrides(&fees).unwrap(),
+                route.mid_price().unwrap()
+            );
+            let overridden = route.with_fee_overrides(&fees).unwrap();
+            assert_eq!(overridden.pools[0].fee, fees[0]);
+            assert_eq!(overridden.pools[1].fee, fees[1]);
+            assert_eq!(overridden.pools[0].pool_id, route.pools[0].pool_id);
+        }
+
+        #[test]
+        fn fee_overrides_must_match_pools() {
+            let route = create_route!(POOL_0_1, POOL_1_2; CURRENCY0, CURRENCY2);
+            let fees = [U24::from(FeeAmount::LOWEST)];
+            assert!(matches!(
+                route.mid_price_with_fee_overrides(&fees),
+                Err(Error::FeeOverridesLengthMismatch { pools: 2, fees: 1 })
+            ));
+            assert!(matches!(
+                route.with_fee_overrides(&fees),
+                Err(Error::FeeOverridesLengthMismatch { pools: 2, fees: 1 })
+            ));
+        }
+
         #[test]
         fn can_be_constructed_with_ether_as_input_on_a_weth_pool() {
             let route = create_route!(POOL_0_WETH, ETHER, CURRENCY0);
             assert_eq!(route.input, ETHER.clone());
-            assert_eq!(route.path_input, WETH.clone().into());
+            assert_eq!(route.path_input, Currency::from(WETH.clone()));
             assert_eq!(route.output, CURRENCY0.clone());
             assert_eq!(route.path_output, CURRENCY0.clone());
         }
@@ -414,7 +888,7 @@ mod tests {
         fn can_be_constructed_with_weth_as_input_on_a_eth_pool() {
             let route = create_route!(POOL_0_ETH, WETH, CURRENCY0);
             assert_eq!(route.input, WETH.clone());
-            assert_eq!(route.path_input, ETHER.clone().into());
+            assert_eq!(route.path_input, Currency::from(ETHER.clone()));
             assert_eq!(route.output, CURRENCY0.clone());
             assert_eq!(route.path_output, CURRENCY0.clone());
         }
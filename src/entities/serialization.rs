@@ -0,0 +1,782 @@
+//! ## Serialization
+//! [`serde`] support for the entities, gated behind the `serde` feature.
+//!
+//! Currencies are serialized by chain id and address along with their metadata, native currency
+//! having the zero address. Tick data is serialized as the list of initialized ticks, so only tick
+//! data providers implementing [`SerializableTickDataProvider`] can be serialized. Entities are
+//! rebuilt through their constructors when deserialized, so derived fields such as the pool id and
+//! pool key are recomputed. Hook swap simulators and dynamic fee resolvers are not serialized, and
+//! neither are the buy and sell fees of tokens, which are zero once deserialized.
+
+use crate::prelude::{PathKey, Pool, PoolKey, Position, Route, Swap, Trade, DYANMIC_FEE_FLAG};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use alloy_primitives::{
+    aliases::{I24, U24},
+    Address, Bytes, U160, U256,
+};
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use uniswap_sdk_core::prelude::*;
+use uniswap_v3_sdk::prelude::*;
+
+/// A tick data provider that can be serialized as its list of initialized ticks
+pub trait SerializableTickDataProvider: TickDataProvider + Sized {
+    /// Returns the initialized ticks
+    fn ticks(&self) -> Vec<Tick<Self::Index>>;
+
+    /// Rebuilds the tick data provider from its initialized ticks
+    fn from_ticks(ticks: Vec<Tick<Self::Index>>, tick_spacing: Self::Index) -> Self;
+}
+
+impl SerializableTickDataProvider for NoTickDataProvider {
+    #[inline]
+    fn ticks(&self) -> Vec<Tick<Self::Index>> {
+        Vec::new()
+    }
+
+    #[inline]
+    fn from_ticks(_: Vec<Tick<Self::Index>>, _: Self::Index) -> Self {
+        Self
+    }
+}
+
+impl<I: TickIndex> SerializableTickDataProvider for Vec<Tick<I>> {
+    #[inline]
+    fn ticks(&self) -> Vec<Tick<Self::Index>> {
+        self.clone()
+    }
+
+    #[inline]
+    fn from_ticks(ticks: Vec<Tick<Self::Index>>, _: Self::Index) -> Self {
+        ticks
+    }
+}
+
+impl<I: TickIndex> SerializableTickDataProvider for TickListDataProvider<I> {
+    #[inline]
+    fn ticks(&self) -> Vec<Tick<Self::Index>> {
+        self.to_vec()
+    }
+
+    #[inline]
+    fn from_ticks(ticks: Vec<Tick<Self::Index>>, tick_spacing: Self::Index) -> Self {
+        Self::new(ticks, tick_spacing)
+    }
+}
+
+/// A currency type that can be rebuilt from a deserialized [`Currency`]
+pub trait FromCurrency: BaseCurrency + Sized {
+    /// Returns the currency as `Self`, or `None` if it is of another kind
+    fn from_currency(currency: Currency) -> Option<Self>;
+}
+
+impl FromCurrency for Currency {
+    #[inline]
+    fn from_currency(currency: Currency) -> Option<Self> {
+        Some(currency)
+    }
+}
+
+impl FromCurrency for Token {
+    #[inline]
+    fn from_currency(currency: Currency) -> Option<Self> {
+        match currency {
+            Currency::Token(token) => Some(token),
+            Currency::NativeCurrency(_) => None,
+        }
+    }
+}
+
+impl FromCurrency for Ether {
+    #[inline]
+    fn from_currency(currency: Currency) -> Option<Self> {
+        match currency {
+            Currency::NativeCurrency(ether) => Some(ether),
+            Currency::Token(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurrencyRepr {
+    chain_id: u64,
+    address: Address,
+    decimals: u8,
+    symbol: Option<String>,
+    name: Option<String>,
+}
+
+impl CurrencyRepr {
+    fn new(currency: &impl BaseCurrency) -> Self {
+        Self {
+            chain_id: currency.chain_id(),
+            address: crate::prelude::to_address(currency),
+            decimals: currency.decimals(),
+            symbol: currency.symbol().map(|symbol| symbol.to_string()),
+            name: currency.name().map(|name| name.to_string()),
+        }
+    }
+
+    /// Rebuilds the currency, with zero buy and sell fees for tokens since they aren't serialized
+    fn into_currency<C: FromCurrency, E: de::Error>(self) -> Result<C, E> {
+        let currency = if self.address.is_zero() {
+            Currency::NativeCurrency(Ether::on_chain(self.chain_id))
+        } else {
+            Currency::Token(Token::new(
+                self.chain_id,
+                self.address,
+                self.decimals,
+                self.symbol,
+                self.name,
+                0,
+                0,
+            ))
+        };
+        C::from_currency(currency).ok_or_else(|| E::custom("unexpected currency kind"))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TickRepr {
+    index: i32,
+    liquidity_gross: u128,
+    liquidity_net: i128,
+}
+
+fn index_from_i32<I: TickIndex, E: de::Error>(index: i32) -> Result<I, E> {
+    I24::try_from(index)
+        .map(I::from_i24)
+        .map_err(|_| E::custom("tick index out of range"))
+}
+
+/// Checks the ticks like [`TickListDataProvider::new`] does, so that malformed tick data is
+/// rejected rather than panicking: the tick spacing is positive and the ticks are sorted, on the
+/// tick spacing and have net liquidity summing to zero.
+fn validate_ticks<I: TickIndex, E: de::Error>(ticks: &[Tick<I>], tick_spacing: I) -> Result<(), E> {
+    if tick_spacing.to_i24().as_i32() <= 0 {
+        return Err(E::custom("tick spacing must be positive"));
+    }
+    if !ticks.windows(2).all(|pair| pair[0].index < pair[1].index) {
+        return Err(E::custom("ticks are not sorted"));
+    }
+    if !ticks
+        .iter()
+        .all(|tick| (tick.index % tick_spacing).is_zero())
+    {
+        return Err(E::custom("tick is not on the tick spacing"));
+    }
+    let net_liquidity = ticks.iter().try_fold(0_i128, |sum, tick| {
+        sum.checked_add(tick.liquidity_net)
+            .ok_or_else(|| E::custom("net liquidity overflows"))
+    })?;
+    if net_liquidity != 0 {
+        return Err(E::custom("net liquidity of ticks doesn't sum to zero"));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AmountRepr {
+    currency: CurrencyRepr,
+    numerator: String,
+    denominator: String,
+}
+
+impl AmountRepr {
+    fn new(amount: &CurrencyAmount<impl BaseCurrency>) -> Self {
+        Self {
+            currency: CurrencyRepr::new(&amount.currency),
+            numerator: amount.numerator.to_string(),
+            denominator: amount.denominator.to_string(),
+        }
+    }
+
+    fn into_amount<C: FromCurrency, E: de::Error>(self) -> Result<CurrencyAmount<C>, E> {
+        let numerator = self.numerator.parse::<BigInt>().map_err(E::custom)?;
+        let denominator = self.denominator.parse::<BigInt>().map_err(E::custom)?;
+        CurrencyAmount::from_fractional_amount(
+            self.currency.into_currency()?,
+            numerator,
+            denominator,
+        )
+        .map_err(E::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PoolRepr {
+    currency0: CurrencyRepr,
+    currency1: CurrencyRepr,
+    fee: u32,
+    tick_spacing: i32,
+    hooks: Address,
+    sqrt_price_x96: U160,
+    liquidity: u128,
+    ticks: Vec<TickRepr>,
+    #[serde(default)]
+    protocol_fee: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PoolKeyRepr {
+    currency0: Address,
+    currency1: Address,
+    fee: u32,
+    tick_spacing: i32,
+    hooks: Address,
+}
+
+impl Serialize for PoolKey {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PoolKeyRepr {
+            currency0: self.currency0,
+            currency1: self.currency1,
+            fee: self.fee.to(),
+            tick_spacing: self.tickSpacing.as_i32(),
+            hooks: self.hooks,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PoolKey {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PoolKeyRepr::deserialize(deserializer)?;
+        Ok(Self {
+            currency0: repr.currency0,
+            currency1: repr.currency1,
+            fee: U24::try_from(repr.fee).map_err(de::Error::custom)?,
+            tickSpacing: I24::try_from(repr.tick_spacing).map_err(de::Error::custom)?,
+            hooks: repr.hooks,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PathKeyRepr {
+    intermediate_currency: Address,
+    fee: u32,
+    tick_spacing: i32,
+    hooks: Address,
+    hook_data: Bytes,
+}
+
+impl Serialize for PathKey {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PathKeyRepr {
+            intermediate_currency: self.intermediateCurrency,
+            fee: u32::try_from(self.fee).map_err(serde::ser::Error::custom)?,
+            tick_spacing: self.tickSpacing.as_i32(),
+            hooks: self.hooks,
+            hook_data: self.hookData.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PathKey {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PathKeyRepr::deserialize(deserializer)?;
+        Ok(Self {
+            intermediateCurrency: repr.intermediate_currency,
+            fee: U256::from(repr.fee),
+            tickSpacing: I24::try_from(repr.tick_spacing).map_err(de::Error::custom)?,
+            hooks: repr.hooks,
+            hookData: repr.hook_data,
+        })
+    }
+}
+
+impl<TP: SerializableTickDataProvider> Serialize for Pool<TP> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PoolRepr {
+            currency0: CurrencyRepr::new(&self.currency0),
+            currency1: CurrencyRepr::new(&self.currency1),
+            fee: self.fee.to(),
+            tick_spacing: self.tick_spacing.to_i24().as_i32(),
+            hooks: self.hooks,
+            sqrt_price_x96: self.sqrt_price_x96,
+            liquidity: self.liquidity,
+            ticks: self
+                .tick_data_provider
+                .ticks()
+                .into_iter()
+                .map(|tick| TickRepr {
+                    index: tick.index.to_i24().as_i32(),
+                    liquidity_gross: tick.liquidity_gross,
+                    liquidity_net: tick.liquidity_net,
+                })
+                .collect(),
+            protocol_fee: self.protocol_fee.to(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, TP: SerializableTickDataProvider> Deserialize<'de> for Pool<TP> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PoolRepr::deserialize(deserializer)?;
+        let tick_spacing: TP::Index = index_from_i32(repr.tick_spacing)?;
+        let ticks = repr
+            .ticks
+            .into_iter()
+            .map(|tick| {
+                Ok(Tick {
+                    index: index_from_i32(tick.index)?,
+                    liquidity_gross: tick.liquidity_gross,
+                    liquidity_net: tick.liquidity_net,
+                })
+            })
+            .collect::<Result<Vec<_>, D::Error>>()?;
+        validate_ticks(&ticks, tick_spacing)?;
+        let fee = U24::try_from(repr.fee).map_err(de::Error::custom)?;
+        // checks what `Pool::new_with_tick_data_provider` asserts
+        if fee != DYANMIC_FEE_FLAG && fee >= U24::from(1_000_000) {
+            return Err(de::Error::custom("fee must be below 100%"));
+        }
+        if fee == DYANMIC_FEE_FLAG && repr.hooks.is_zero() {
+            return Err(de::Error::custom("dynamic fee pool requires a hook"));
+        }
+        let protocol_fee = U24::try_from(repr.protocol_fee).map_err(de::Error::custom)?;
+        Self::new_with_tick_data_provider(
+            repr.currency0.into_currency()?,
+            repr.currency1.into_currency()?,
+            fee,
+            tick_spacing,
+            repr.hooks,
+            repr.sqrt_price_x96,
+            repr.liquidity,
+            TP::from_ticks(ticks, tick_spacing),
+        )
+        .and_then(|pool| pool.with_protocol_fee(protocol_fee))
+        .map_err(de::Error::custom)
+    }
+}
+
+impl<TP: SerializableTickDataProvider> Serialize for Position<TP> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Position", 4)?;
+        state.serialize_field("pool", &self.pool)?;
+        state.serialize_field("tickLower", &self.tick_lower.to_i24().as_i32())?;
+        state.serialize_field("tickUpper", &self.tick_upper.to_i24().as_i32())?;
+        state.serialize_field("liquidity", &self.liquidity)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(deserialize = "Pool<TP>: Deserialize<'de>"))]
+struct PositionRepr<TP: TickDataProvider> {
+    pool: Pool<TP>,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+}
+
+impl<'de, TP: SerializableTickDataProvider> Deserialize<'de> for Position<TP> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PositionRepr::<TP>::deserialize(deserializer)?;
+        let tick_lower: TP::Index = index_from_i32(repr.tick_lower)?;
+        let tick_upper: TP::Index = index_from_i32(repr.tick_upper)?;
+        if tick_lower >= tick_upper
+            || tick_lower < TP::Index::from_i24(MIN_TICK)
+            || tick_upper > TP::Index::from_i24(MAX_TICK)
+            || !(tick_lower % repr.pool.tick_spacing).is_zero()
+            || !(tick_upper % repr.pool.tick_spacing).is_zero()
+        {
+            return Err(de::Error::custom("invalid position ticks"));
+        }
+        Ok(Self::new(repr.pool, repr.liquidity, tick_lower, tick_upper))
+    }
+}
+
+impl<TInput, TOutput, TP> Serialize for Route<TInput, TOutput, TP>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: SerializableTickDataProvider,
+{
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Route", 4)?;
+        state.serialize_field("pools", &self.pools)?;
+        state.serialize_field("input", &CurrencyRepr::new(&self.input))?;
+        state.serialize_field("output", &CurrencyRepr::new(&self.output))?;
+        state.serialize_field("hookData", &self.hook_data)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(
+    rename_all = "camelCase",
+    bound(deserialize = "Pool<TP>: Deserialize<'de>")
+)]
+struct RouteRepr<TP: TickDataProvider> {
+    pools: Vec<Pool<TP>>,
+    input: CurrencyRepr,
+    output: CurrencyRepr,
+    #[serde(default)]
+    hook_data: Option<Vec<Bytes>>,
+}
+
+impl<'de, TInput, TOutput, TP> Deserialize<'de> for Route<TInput, TOutput, TP>
+where
+    TInput: FromCurrency,
+    TOutput: FromCurrency,
+    TP: SerializableTickDataProvider,
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = RouteRepr::<TP>::deserialize(deserializer)?;
+        let input = repr.input.into_currency()?;
+        let output = repr.output.into_currency()?;
+        match repr.hook_data {
+            Some(hook_data) => Self::new_with_hook_data(repr.pools, input, output, hook_data),
+            None => Self::new(repr.pools, input, output),
+        }
+        .map_err(de::Error::custom)
+    }
+}
+
+impl<TInput, TOutput, TP> Serialize for Swap<TInput, TOutput, TP>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: SerializableTickDataProvider,
+{
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Swap", 3)?;
+        state.serialize_field("route", &self.route)?;
+        state.serialize_field("inputAmount", &AmountRepr::new(&self.input_amount))?;
+        state.serialize_field("outputAmount", &AmountRepr::new(&self.output_amount))?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(deserialize = "Route<TInput, TOutput, TP>: Deserialize<'de>"))]
+struct SwapRepr<TInput, TOutput, TP>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: TickDataProvider,
+{
+    route: Route<TInput, TOutput, TP>,
+    input_amount: AmountRepr,
+    output_amount: AmountRepr,
+}
+
+impl<'de, TInput, TOutput, TP> Deserialize<'de> for Swap<TInput, TOutput, TP>
+where
+    TInput: FromCurrency,
+    TOutput: FromCurrency,
+    TP: SerializableTickDataProvider,
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = SwapRepr::<TInput, TOutput, TP>::deserialize(deserializer)?;
+        let input_amount: CurrencyAmount<TInput> = repr.input_amount.into_amount()?;
+        let output_amount: CurrencyAmount<TOutput> = repr.output_amount.into_amount()?;
+        if !input_amount.currency.equals(&repr.route.input)
+            || !output_amount.currency.equals(&repr.route.output)
+        {
+            return Err(de::Error::custom(
+                "swap amounts don't match route currencies",
+            ));
+        }
+        Ok(Self::new(repr.route, input_amount, output_amount))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum TradeTypeRepr {
+    ExactInput,
+    ExactOutput,
+}
+
+impl<TInput, TOutput, TP> Serialize for Trade<TInput, TOutput, TP>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: SerializableTickDataProvider,
+{
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let trade_type = match self.trade_type {
+            TradeType::ExactInput => TradeTypeRepr::ExactInput,
+            TradeType::ExactOutput => TradeTypeRepr::ExactOutput,
+        };
+        let mut state = serializer.serialize_struct("Trade", 2)?;
+        state.serialize_field("swaps", self.swaps())?;
+        state.serialize_field("tradeType", &trade_type)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(deserialize = "Swap<TInput, TOutput, TP>: Deserialize<'de>"))]
+struct TradeRepr<TInput, TOutput, TP>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: TickDataProvider,
+{
+    swaps: Vec<Swap<TInput, TOutput, TP>>,
+    trade_type: TradeTypeRepr,
+}
+
+impl<'de, TInput, TOutput, TP> Deserialize<'de> for Trade<TInput, TOutput, TP>
+where
+    TInput: FromCurrency,
+    TOutput: FromCurrency,
+    TP: SerializableTickDataProvider,
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = TradeRepr::<TInput, TOutput, TP>::deserialize(deserializer)?;
+        let trade_type = match repr.trade_type {
+            TradeTypeRepr::ExactInput => TradeType::ExactInput,
+            TradeTypeRepr::ExactOutput => TradeType::ExactOutput,
+        };
+        // check what the trade constructor asserts
+        let Some(first) = repr.swaps.first() else {
+            return Err(de::Error::custom("trade has no swaps"));
+        };
+        if !repr.swaps.iter().all(|swap| {
+            first.input_currency().equals(&swap.route.input)
+                && first.output_currency().equals(&swap.route.output)
+        }) {
+            return Err(de::Error::custom(
+                "swaps don't share input and output currencies",
+            ));
+        }
+        let mut pool_ids: Vec<_> = repr
+            .swaps
+            .iter()
+            .flat_map(|swap| swap.route.pools.iter().map(|pool| pool.pool_id))
+            .collect();
+        let num_pools = pool_ids.len();
+        pool_ids.sort_unstable();
+        pool_ids.dedup();
+        if pool_ids.len() != num_pools {
+            return Err(de::Error::custom("pools are duplicated across swaps"));
+        }
+        Self::create_unchecked_trade_with_multiple_routes(repr.swaps, trade_type)
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_route, currency_amount, tests::*};
+    use once_cell::sync::Lazy;
+
+    static POOL: Lazy<Pool<TickListDataProvider>> = Lazy::new(|| {
+        Pool::new_with_tick_data_provider(
+            Currency::NativeCurrency(ETHER.clone()),
+            Currency::Token(USDC.clone()),
+            FeeAmount::MEDIUM.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            ONE_ETHER,
+            TickListDataProvider::new(TICK_LIST.clone(), 10),
+        )
+        .unwrap()
+    });
+
+    #[test]
+    fn pool_round_trip() {
+        let json = serde_json::to_string(&*POOL).unwrap();
+        let pool: Pool<TickListDataProvider> = serde_json::from_str(&json).unwrap();
+        assert_eq!(pool, *POOL);
+        assert_eq!(pool.pool_id, POOL.pool_id);
+        assert_eq!(pool.pool_key, POOL.pool_key);
+        assert_eq!(
+            pool.tick_data_provider.ticks(),
+            POOL.tick_data_provider.ticks()
+        );
+        assert_eq!(serde_json::to_string(&pool).unwrap(), json);
+    }
+
+    #[test]
+    fn position_round_trip() {
+        let position = Position::new(POOL.clone(), ONE_ETHER, -100, 100);
+        let json = serde_json::to_string(&position).unwrap();
+        let deserialized: Position<TickListDataProvider> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.pool, position.pool);
+        assert_eq!(deserialized.tick_lower, position.tick_lower);
+        assert_eq!(deserialized.tick_upper, position.tick_upper);
+        assert_eq!(deserialized.liquidity, position.liquidity);
+    }
+
+    #[test]
+    fn trade_round_trip() {
+        let trade = Trade::create_unchecked_trade(
+            create_route!(POOL, ETHER, USDC),
+            currency_amount!(ETHER, 100),
+            currency_amount!(USDC, 98),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&trade).unwrap();
+        let deserialized: Trade<Ether, Token, TickListDataProvider> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.trade_type, trade.trade_type);
+        assert_eq!(deserialized.route().pools, trade.route().pools);
+        assert_eq!(deserialized.input_currency(), trade.input_currency());
+        assert_eq!(deserialized.output_currency(), trade.output_currency());
+        assert_eq!(
+            deserialized.input_amount().unwrap(),
+            trade.input_amount().unwrap()
+        );
+        assert_eq!(
+            deserialized.output_amount().unwrap(),
+            trade.output_amount().unwrap()
+        );
+    }
+
+    #[test]
+    fn route_round_trip_with_hook_data() {
+        let route = Route::new_with_hook_data(
+            vec![POOL.clone()],
+            ETHER.clone(),
+            USDC.clone(),
+            vec![Bytes::from_static(&[1, 2, 3])],
+        )
+        .unwrap();
+        let json = serde_json::to_string(&route).unwrap();
+        let deserialized: Route<Ether, Token, TickListDataProvider> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.hook_data, route.hook_data);
+        assert_eq!(deserialized.pools, route.pools);
+    }
+
+    #[test]
+    fn pool_key_round_trip() {
+        let key = PoolKey {
+            currency0: DAI.address(),
+            currency1: USDC.address(),
+            fee: FeeAmount::LOW.into(),
+            tickSpacing: I24::try_from(-10).unwrap(),
+            hooks: Address::ZERO,
+        };
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(
+            json,
+            r#"{"currency0":"0x6b175474e89094c44da98b954eedeac495271d0f","currency1":"0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48","fee":500,"tickSpacing":-10,"hooks":"0x0000000000000000000000000000000000000000"}"#
+        );
+        assert_eq!(key.to_string(), json);
+        assert_eq!(serde_json::from_str::<PoolKey>(&json).unwrap(), key);
+    }
+
+    #[test]
+    fn path_key_round_trip() {
+        let key = PathKey {
+            intermediateCurrency: USDC.address(),
+            fee: U256::from(3000),
+            tickSpacing: I24::try_from(-60).unwrap(),
+            hooks: Address::ZERO,
+            hookData: Bytes::from_static(&[0xab, 0xcd]),
+        };
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(
+            json,
+            r#"{"intermediateCurrency":"0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48","fee":3000,"tickSpacing":-60,"hooks":"0x0000000000000000000000000000000000000000","hookData":"0xabcd"}"#
+        );
+        assert_eq!(serde_json::from_str::<PathKey>(&json).unwrap(), key);
+    }
+
+    #[test]
+    fn rejects_malformed_tick_data() {
+        let mut json = serde_json::to_value(&*POOL).unwrap();
+        let ticks = json["ticks"].as_array_mut().unwrap();
+        // unbalanced
+        let upper = ticks.pop().unwrap();
+        assert!(serde_json::from_value::<Pool<TickListDataProvider>>(json.clone()).is_err());
+        // unsorted
+        let ticks = json["ticks"].as_array_mut().unwrap();
+        ticks.insert(0, upper);
+        assert!(serde_json::from_value::<Pool<TickListDataProvider>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_protocol_fee() {
+        let mut json = serde_json::to_value(&*POOL).unwrap();
+        json["protocolFee"] = 1001.into();
+        assert!(serde_json::from_value::<Pool<TickListDataProvider>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_fee() {
+        let mut json = serde_json::to_value(&*POOL).unwrap();
+        json["fee"] = 1_000_001.into();
+        assert!(serde_json::from_value::<Pool<TickListDataProvider>>(json.clone()).is_err());
+        // a dynamic fee without a hook
+        json["fee"] = DYANMIC_FEE_FLAG.to::<u32>().into();
+        assert!(serde_json::from_value::<Pool<TickListDataProvider>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_trade() {
+        let trade = Trade::create_unchecked_trade(
+            create_route!(POOL, ETHER, USDC),
+            currency_amount!(ETHER, 100),
+            currency_amount!(USDC, 98),
+            TradeType::ExactInput,
+        )
+        .unwrap();
+        let mut json = serde_json::to_value(&trade).unwrap();
+        let swap = json["swaps"][0].clone();
+
+        // duplicated pools
+        json["swaps"].as_array_mut().unwrap().push(swap.clone());
+        assert!(
+            serde_json::from_value::<Trade<Ether, Token, TickListDataProvider>>(json.clone())
+                .is_err()
+        );
+
+        // mismatched currencies
+        let mut mismatched = swap;
+        mismatched["outputAmount"] = mismatched["inputAmount"].clone();
+        json["swaps"] = serde_json::Value::Array(vec![mismatched]);
+        assert!(
+            serde_json::from_value::<Trade<Currency, Currency, TickListDataProvider>>(json.clone())
+                .is_err()
+        );
+
+        // no swaps
+        json["swaps"] = serde_json::Value::Array(Vec::new());
+        assert!(serde_json::from_value::<Trade<Ether, Token, TickListDataProvider>>(json).is_err());
+    }
+
+    #[test]
+    fn rejects_mismatched_currency_kind() {
+        let route = create_route!(POOL, ETHER, USDC);
+        let json = serde_json::to_string(&route).unwrap();
+        assert!(serde_json::from_str::<Route<Token, Token, TickListDataProvider>>(&json).is_err());
+    }
+}
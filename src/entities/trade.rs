@@ -1,19 +1,23 @@
-use crate::prelude::{amount_with_path_currency, Error, Pool, Route};
-use alloc::{boxed::Box, vec};
-use alloy_primitives::map::HashSet;
+use crate::prelude::{
+    amount_with_path_currency, has_swap_permissions, to_address, Error, Pool, Route,
+};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+};
+use alloy_primitives::{
+    map::{HashMap, HashSet},
+    Address, B256, U160,
+};
 use core::cmp::Ordering;
+use num_traits::ToPrimitive;
 use uniswap_sdk_core::prelude::*;
 use uniswap_v3_sdk::prelude::*;
 
-/// Trades comparator, an extension of the input output comparator that also considers other
-/// dimensions of the trade in ranking them
-///
-/// ## Arguments
-///
-/// * `a`: The first trade to compare
-/// * `b`: The second trade to compare
-#[inline]
-pub fn trade_comparator<TInput, TOutput, TP>(
+/// Compares trades by their input and output amounts only, returning [`Ordering::Equal`] if both
+/// are the same
+fn compare_amounts<TInput, TOutput, TP>(
     a: &Trade<TInput, TOutput, TP>,
     b: &Trade<TInput, TOutput, TP>,
 ) -> Ordering
@@ -37,18 +41,7 @@ where
     let b_output = b.output_amount().unwrap().as_fraction();
     if a_output == b_output {
         if a_input == b_input {
-            // consider the number of hops since each hop costs gas
-            let a_hops = a
-                .swaps
-                .iter()
-                .map(|s| s.route.pools.len() + 1)
-                .sum::<usize>();
-            let b_hops = b
-                .swaps
-                .iter()
-                .map(|s| s.route.pools.len() + 1)
-                .sum::<usize>();
-            return a_hops.cmp(&b_hops);
+            return Ordering::Equal;
         }
         // trade A requires less input than trade B, so A should come first
         if a_input < b_input {
@@ -66,12 +59,181 @@ where
     }
 }
 
+/// Trades comparator, an extension of the input output comparator that also considers other
+/// dimensions of the trade in ranking them
+///
+/// ## Arguments
+///
+/// * `a`: The first trade to compare
+/// * `b`: The second trade to compare
+#[inline]
+pub fn trade_comparator<TInput, TOutput, TP>(
+    a: &Trade<TInput, TOutput, TP>,
+    b: &Trade<TInput, TOutput, TP>,
+) -> Ordering
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: TickDataProvider,
+{
+    compare_amounts(a, b).then_with(|| {
+        // consider the number of hops since each hop costs gas
+        let a_hops = a
+            .swaps
+            .iter()
+            .map(|s| s.route.pools.len() + 1)
+            .sum::<usize>();
+        let b_hops = b
+            .swaps
+            .iter()
+            .map(|s| s.route.pools.len() + 1)
+            .sum::<usize>();
+        a_hops.cmp(&b_hops)
+    })
+}
+
+/// Trades comparator like [`trade_comparator`], but breaking ties between trades with the same
+/// input and output amounts on the gas the trades are expected to cost according to a
+/// [`GasModel`], instead of their number of hops
+///
+/// The results of [`Trade::best_trade_exact_in`] or [`Trade::best_trade_exact_out`] can be
+/// re-ranked with `trades.sort_by(|a, b| trade_comparator_with_gas(a, b, &gas_model))`.
+///
+/// ## Arguments
+///
+/// * `a`: The first trade to compare
+/// * `b`: The second trade to compare
+/// * `gas_model`: The gas weights of the pools
+#[inline]
+pub fn trade_comparator_with_gas<TInput, TOutput, TP>(
+    a: &Trade<TInput, TOutput, TP>,
+    b: &Trade<TInput, TOutput, TP>,
+    gas_model: &GasModel,
+) -> Ordering
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: TickDataProvider,
+{
+    compare_amounts(a, b).then_with(|| a.estimated_gas(gas_model).cmp(&b.estimated_gas(gas_model)))
+}
+
+/// Returns the trade with the highest output net of its gas cost, converted to output terms
+///
+/// The gas cost of a trade is `gas_price` times its [`Trade::estimated_gas`] under the given
+/// [`GasModel`]. Trades whose output amount can't be computed are skipped, and ties go to the
+/// earliest trade.
+///
+/// ## Arguments
+///
+/// * `trades`: The candidate trades
+/// * `gas_price`: The price of gas in raw units of the gas token
+/// * `gas_model`: The gas weights of routes and pools
+/// * `output_price_in_gas_token`: The raw amount of gas token per raw amount of output currency
+///
+/// ## Returns
+///
+/// The best trade, `None` if no trade has an output amount, or [`Error::ZeroPrice`] if
+/// `output_price_in_gas_token` is zero
+#[inline]
+pub fn best_trade_by_net_output<'a, TInput, TOutput, TP>(
+    trades: &'a [Trade<TInput, TOutput, TP>],
+    gas_price: u128,
+    gas_model: &GasModel,
+    output_price_in_gas_token: &Fraction,
+) -> Result<Option<&'a Trade<TInput, TOutput, TP>>, Error>
+where
+    TInput: BaseCurrency,
+    TOutput: BaseCurrency,
+    TP: TickDataProvider,
+{
+    if output_price_in_gas_token.numerator == BigInt::ZERO {
+        return Err(Error::ZeroPrice);
+    }
+    let mut best: Option<(Fraction, &Trade<TInput, TOutput, TP>)> = None;
+    for trade in trades {
+        let Ok(output_amount) = trade.output_amount() else {
+            continue;
+        };
+        let gas_cost = Fraction::new(
+            BigInt::from(gas_price) * BigInt::from(trade.estimated_gas(gas_model)),
+            1,
+        );
+        let net_output = output_amount.as_fraction() - gas_cost / output_price_in_gas_token.clone();
+        if best
+            .as_ref()
+            .is_none_or(|(best_net_output, _)| net_output > *best_net_output)
+        {
+            best = Some((net_output, trade));
+        }
+    }
+    Ok(best.map(|(_, trade)| trade))
+}
+
+/// Approximate gas weights used to rank trades whose amounts are otherwise equal
+///
+/// A hop through a pool whose hook has swap permissions costs more than a hop through a vanilla
+/// pool, since the hook is called during the swap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasModel {
+    /// The fixed cost of a route regardless of its pools
+    pub base_gas: u64,
+    /// The cost of each hop through a pool
+    pub per_hop_gas: u64,
+    /// The additional cost of a hop through a pool whose hook has swap permissions
+    pub hooked_pool_gas: u64,
+}
+
+impl Default for GasModel {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            base_gas: 60_000,
+            per_hop_gas: 80_000,
+            hooked_pool_gas: 50_000,
+        }
+    }
+}
+
+impl GasModel {
+    /// Returns the gas weight of a hop through the given pool
+    #[inline]
+    #[must_use]
+    pub const fn pool_gas<TP: TickDataProvider>(&self, pool: &Pool<TP>) -> u64 {
+        if has_swap_permissions(pool.hooks) {
+            self.per_hop_gas + self.hooked_pool_gas
+        } else {
+            self.per_hop_gas
+        }
+    }
+
+    /// Returns the gas weight of swapping through the given route
+    #[inline]
+    #[must_use]
+    pub fn route_gas<TInput, TOutput, TP>(&self, route: &Route<TInput, TOutput, TP>) -> u64
+    where
+        TInput: BaseCurrency,
+        TOutput: BaseCurrency,
+        TP: TickDataProvider,
+    {
+        self.base_gas
+            + route
+                .pools
+                .iter()
+                .map(|pool| self.pool_gas(pool))
+                .sum::<u64>()
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct BestTradeOptions {
     /// how many results to return
     pub max_num_results: Option<usize>,
     /// the maximum number of hops a trade should contain
     pub max_hops: Option<usize>,
+    /// the maximum number of distinct pools a split trade may touch, only used by
+    /// [`Trade::best_split_trade_exact_in`] and [`Trade::best_split_trade_exact_out`]
+    pub max_distinct_pools: Option<usize>,
 }
 
 /// Represents a swap through a route
@@ -126,6 +288,23 @@ where
     }
 }
 
+/// An owned summary of a [`Trade`] for structured logging, which does not borrow any pools
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeLogRecord {
+    /// The address of the input currency, zero for native
+    pub input_currency: Address,
+    /// The address of the output currency, zero for native
+    pub output_currency: Address,
+    /// The raw input amount
+    pub input_amount: String,
+    /// The raw output amount
+    pub output_amount: String,
+    /// The type of the trade, either exact in or exact out.
+    pub trade_type: TradeType,
+    /// The ids of the pools of each swap's route, in swap order
+    pub pool_ids: Vec<Vec<B256>>,
+}
+
 /// Represents a trade executed against a set of routes where some percentage of the input is split
 /// across each route.
 ///
@@ -142,7 +321,7 @@ where
 {
     /// The swaps of the trade, i.e. which routes and how much is swapped in each that make up the
     /// trade.
-    pub swaps: Vec<Swap<TInput, TOutput, TP>>,
+    swaps: Vec<Swap<TInput, TOutput, TP>>,
     /// The type of the trade, either exact in or exact out.
     pub trade_type: TradeType,
     /// The cached result of the input amount computation
@@ -182,19 +361,11 @@ where
             .iter()
             .map(|swap| swap.route.pools.len())
             .sum::<usize>();
+        // pools of the same market are duplicates regardless of their swap state
         let pool_ids = swaps
             .iter()
             .flat_map(|swap| swap.route.pools.iter())
-            .map(|pool| {
-                Pool::get_pool_id(
-                    &pool.currency0,
-                    &pool.currency1,
-                    pool.fee,
-                    pool.tick_spacing,
-                    pool.hooks,
-                )
-                .unwrap()
-            });
+            .map(|pool| pool.pool_id);
         let pool_id_set: HashSet<B256> = HashSet::from_iter(pool_ids);
         assert_eq!(num_pools, pool_id_set.len(), "POOLS_DUPLICATED");
         Ok(Self {
@@ -232,6 +403,34 @@ where
         Self::new(swaps, trade_type)
     }
 
+    /// Returns the swaps of the trade, i.e. which routes and how much is swapped in each that make
+    /// up the trade
+    #[inline]
+    pub fn swaps(&self) -> &[Swap<TInput, TOutput, TP>] {
+        &self.swaps
+    }
+
+    /// Constructs a trade of the same type from the given swaps, validating them like the other
+    /// constructors
+    ///
+    /// ## Arguments
+    ///
+    /// * `swaps`: The swaps of the new trade
+    #[inline]
+    pub fn with_swaps(&self, swaps: Vec<Swap<TInput, TOutput, TP>>) -> Result<Self, Error> {
+        Self::new(swaps, self.trade_type)
+    }
+
+    /// Clears the cached input amount, output amount, execution price and price impact, so that
+    /// the `_cached` methods recompute them
+    #[inline]
+    pub fn invalidate_cache(&mut self) {
+        self._input_amount = None;
+        self._output_amount = None;
+        self._execution_price = None;
+        self._price_impact = None;
+    }
+
     /// When the trade consists of just a single route, this returns the route of the trade.
     #[inline]
     pub fn route(&self) -> &Route<TInput, TOutput, TP> {
@@ -245,6 +444,16 @@ where
         self.swaps[0].input_currency()
     }
 
+    /// Returns true if the input currency has to be wrapped or unwrapped before the first pool of
+    /// any of the trade's routes, i.e. the input is native but the pool pairs the wrapped token,
+    /// or vice versa
+    #[inline]
+    pub fn requires_input_wrap(&self) -> bool {
+        self.swaps
+            .iter()
+            .any(|swap| swap.route.input.is_native() != swap.route.path_input.is_native())
+    }
+
     /// The input amount for the trade assuming no slippage.
     #[inline]
     pub fn input_amount(&self) -> Result<CurrencyAmount<TInput>, Error> {
@@ -374,6 +583,237 @@ where
         Ok(self._price_impact.clone().unwrap())
     }
 
+    /// Returns the shortfall of an executed trade's actual output versus the quoted
+    /// [`Self::output_amount`], as a percent of the quote
+    ///
+    /// The slippage is negative if the trade executed better than quoted.
+    ///
+    /// ## Arguments
+    ///
+    /// * `actual_output`: The output amount the trade actually received on-chain
+    #[inline]
+    pub fn realized_slippage(
+        &self,
+        actual_output: &CurrencyAmount<TOutput>,
+    ) -> Result<Percent, Error> {
+        let quoted_output = self.output_amount()?;
+        let slippage = quoted_output
+            .subtract(actual_output)?
+            .divide(&quoted_output)?;
+        Ok(Percent::new(slippage.numerator, slippage.denominator))
+    }
+
+    /// Returns the profit of a cyclic trade, i.e. its output amount minus its input amount
+    ///
+    /// The profit is negative if the cycle loses money. Returns [`Error::InvalidCurrency`] if the
+    /// input and output currencies of the trade differ.
+    #[inline]
+    pub fn arb_profit(&self) -> Result<CurrencyAmount<TOutput>, Error> {
+        if !self.input_currency().equals(self.output_currency()) {
+            return Err(Error::InvalidCurrency);
+        }
+        let profit = self.output_amount()?.as_fraction() - self.input_amount()?.as_fraction();
+        CurrencyAmount::from_fractional_amount(
+            self.output_currency().clone(),
+            profit.numerator,
+            profit.denominator,
+        )
+        .map_err(Error::Core)
+    }
+
+    /// Returns an estimate of the gas used by the trade, summing the gas of each swap's route under
+    /// the given model
+    ///
+    /// The estimate is only meant to rank candidate trades against each other.
+    ///
+    /// ## Arguments
+    ///
+    /// * `gas_model`: The gas weights of routes and pools
+    #[inline]
+    #[must_use]
+    pub fn estimated_gas(&self, gas_model: &GasModel) -> u64 {
+        self.swaps
+            .iter()
+            .map(|swap| gas_model.route_gas(&swap.route))
+            .sum()
+    }
+
+    /// Returns the fraction of the input amount lost by executing the trade and immediately
+    /// swapping its output back to the input currency through `reverse_route`
+    ///
+    /// The reverse swap is simulated against the current state of `reverse_route`'s pools, so to
+    /// include the price moved by the trade itself, pass a route over the pools as they are after
+    /// the trade.
+    ///
+    /// ## Arguments
+    ///
+    /// * `reverse_route`: The route from the trade's output currency back to its input currency
+    #[inline]
+    pub async fn round_trip_impact(
+        &self,
+        reverse_route: &Route<TOutput, TInput, TP>,
+    ) -> Result<Percent, Error> {
+        if !reverse_route.input.equals(self.output_currency())
+            || !reverse_route.output.equals(self.input_currency())
+        {
+            return Err(Error::InvalidCurrency);
+        }
+        let input_amount = self.input_amount()?;
+        let mut token_amount =
+            amount_with_path_currency(&self.output_amount()?, &reverse_route.pools[0])?;
+        for pool in &reverse_route.pools {
+            (token_amount, _) = pool.get_output_amount(&token_amount, None).await?;
+        }
+        let returned_amount = CurrencyAmount::from_fractional_amount(
+            self.input_currency().clone(),
+            token_amount.numerator,
+            token_amount.denominator,
+        )?;
+        let round_trip_impact = input_amount
+            .subtract(&returned_amount)?
+            .divide(&input_amount)?;
+        Ok(Percent::new(
+            round_trip_impact.numerator,
+            round_trip_impact.denominator,
+        ))
+    }
+
+    /// Returns the amount of each intermediate currency passing through the trade's routes, keyed
+    /// by currency address, with [`Address::ZERO`] for the native currency
+    ///
+    /// The swaps are resimulated hop by hop, forward from the input amount for exact input trades
+    /// and backward from the output amount for exact output trades. Amounts of a currency that
+    /// several swaps pass through are summed.
+    #[inline]
+    pub async fn intermediate_flows(
+        &self,
+    ) -> Result<HashMap<Address, CurrencyAmount<Currency>>, Error> {
+        let mut flows: HashMap<Address, CurrencyAmount<Currency>> = HashMap::default();
+        for swap in &self.swaps {
+            for amount in self.intermediate_amounts(swap).await? {
+                let address = to_address(&amount.currency);
+                let total = match flows.remove(&address) {
+                    Some(total) => total.add(&amount)?,
+                    None => amount,
+                };
+                flows.insert(address, total);
+            }
+        }
+        Ok(flows)
+    }
+
+    /// Returns each currency of the trade's route with the amount of it passing through, from the
+    /// input to the output, e.g. for a receipt like `1.5 WETH → 0.98 USDC → 0.97 DAI`
+    ///
+    /// The intermediate amounts are resimulated like in [`Self::intermediate_flows`], and every
+    /// amount is formatted exactly with the decimals of its currency.
+    ///
+    /// ## Returns
+    ///
+    /// The symbol of each currency, empty if it has none, and its formatted amount, or
+    /// [`Error::MultipleRoutes`] if the trade has several routes
+    #[inline]
+    pub async fn path_with_amounts(&self) -> Result<Vec<(String, String)>, Error> {
+        let (swap, intermediate_amounts) = self.single_route_intermediate_amounts().await?;
+        let mut path = Vec::with_capacity(intermediate_amounts.len() + 2);
+        path.push((
+            swap.input_currency().symbol().cloned().unwrap_or_default(),
+            swap.input_amount.to_exact(),
+        ));
+        for amount in intermediate_amounts {
+            path.push((
+                amount.currency.symbol().cloned().unwrap_or_default(),
+                amount.to_exact(),
+            ));
+        }
+        path.push((
+            swap.output_currency().symbol().cloned().unwrap_or_default(),
+            swap.output_amount.to_exact(),
+        ));
+        Ok(path)
+    }
+
+    /// Returns the amount coming out of each hop of the trade's route, in path order, ending with
+    /// the output amount
+    ///
+    /// The intermediate amounts are resimulated like in [`Self::intermediate_flows`]. Amounts are
+    /// denominated in the currency each pool swaps out, so the output of a native route through a
+    /// wrapped pool is in the wrapped token.
+    ///
+    /// ## Returns
+    ///
+    /// The hop outputs, or [`Error::MultipleRoutes`] if the trade has several routes
+    #[inline]
+    pub async fn hop_outputs(&self) -> Result<Vec<CurrencyAmount<Currency>>, Error> {
+        let (swap, mut hop_outputs) = self.single_route_intermediate_amounts().await?;
+        hop_outputs.push(amount_with_path_currency(
+            &swap.output_amount,
+            &swap.route.pools[swap.route.pools.len() - 1],
+        )?);
+        Ok(hop_outputs)
+    }
+
+    /// Resimulates the only swap of a single route trade like [`Self::intermediate_amounts`],
+    /// returning it with its intermediate amounts, or [`Error::MultipleRoutes`] if the trade has
+    /// several routes
+    async fn single_route_intermediate_amounts(
+        &self,
+    ) -> Result<(&Swap<TInput, TOutput, TP>, Vec<CurrencyAmount<Currency>>), Error> {
+        let [swap] = self.swaps.as_slice() else {
+            return Err(Error::MultipleRoutes);
+        };
+        Ok((swap, self.intermediate_amounts(swap).await?))
+    }
+
+    /// Resimulates a swap of the trade hop by hop, forward from the input amount for exact input
+    /// trades and backward from the output amount for exact output trades, and returns the amounts
+    /// of the intermediate currencies in path order
+    async fn intermediate_amounts(
+        &self,
+        swap: &Swap<TInput, TOutput, TP>,
+    ) -> Result<Vec<CurrencyAmount<Currency>>, Error> {
+        let Swap {
+            route,
+            input_amount,
+            output_amount,
+        } = swap;
+        let hops = route.pools.len();
+        let mut amounts = Vec::with_capacity(hops - 1);
+        match self.trade_type {
+            TradeType::ExactInput => {
+                let mut token_amount = amount_with_path_currency(input_amount, &route.pools[0])?;
+                for pool in &route.pools[..hops - 1] {
+                    (token_amount, _) = pool.get_output_amount(&token_amount, None).await?;
+                    amounts.push(token_amount.clone());
+                }
+            }
+            TradeType::ExactOutput => {
+                let mut token_amount =
+                    amount_with_path_currency(output_amount, &route.pools[hops - 1])?;
+                for pool in route.pools[1..].iter().rev() {
+                    (token_amount, _) = pool.get_input_amount(&token_amount, None).await?;
+                    amounts.push(token_amount.clone());
+                }
+                amounts.reverse();
+            }
+        }
+        Ok(amounts)
+    }
+
+    /// Returns the share of the trade's input amount routed through each swap, in the order of
+    /// [`Self::swaps`]
+    #[inline]
+    pub fn split_proportions(&self) -> Result<Vec<Percent>, Error> {
+        let total = self.input_amount()?;
+        self.swaps
+            .iter()
+            .map(|Swap { input_amount, .. }| {
+                let proportion = input_amount.divide(&total)?;
+                Ok(Percent::new(proportion.numerator, proportion.denominator))
+            })
+            .collect()
+    }
+
     /// Get the minimum amount that must be received from this trade for the given slippage
     /// tolerance
     ///
@@ -511,6 +951,58 @@ where
             self.minimum_amount_out_cached(slippage_tolerance, None)?,
         ))
     }
+
+    /// Returns an owned summary of the trade for structured logging
+    #[inline]
+    pub fn to_log_record(&self) -> Result<TradeLogRecord, Error> {
+        Ok(TradeLogRecord {
+            input_currency: to_address(self.input_currency()),
+            output_currency: to_address(self.output_currency()),
+            input_amount: self.input_amount()?.quotient().to_string(),
+            output_amount: self.output_amount()?.quotient().to_string(),
+            trade_type: self.trade_type,
+            pool_ids: self
+                .swaps
+                .iter()
+                .map(|swap| swap.route.pools.iter().map(|pool| pool.pool_id).collect())
+                .collect(),
+        })
+    }
+
+    /// Checks that every pool the trade was computed against still has a price within
+    /// `tolerance` of its current on-chain price, to catch stale quotes before sending
+    ///
+    /// ## Arguments
+    ///
+    /// * `lens`: The lens used to read each pool's current slot0
+    /// * `tolerance`: The maximum allowed relative divergence of each pool's price
+    /// * `block_id`: Optional block ID to query at
+    #[cfg(feature = "extensions")]
+    #[inline]
+    pub async fn validate_freshness(
+        &self,
+        lens: &crate::extensions::PoolManagerLens,
+        tolerance: &Percent,
+        block_id: Option<alloy::eips::BlockId>,
+    ) -> Result<(), Error> {
+        for Swap { route, .. } in &self.swaps {
+            for pool in &route.pools {
+                let (sqrt_price_x96, ..) = lens.get_slot0(pool.pool_id, block_id).await?;
+                // compare prices rather than square roots so the tolerance is in price terms
+                let assumed = pool.sqrt_price_x96.to_big_int().pow(2);
+                let current = sqrt_price_x96.to_big_int().pow(2);
+                let difference = if current > assumed {
+                    current - &assumed
+                } else {
+                    &assumed - current
+                };
+                if Percent::new(difference, assumed) > *tolerance {
+                    return Err(Error::StalePrice(pool.pool_id));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<TInput, TOutput, TP> Trade<TInput, TOutput, TP>
@@ -547,6 +1039,26 @@ where
         Self::from_route(route, amount_out, TradeType::ExactOutput).await
     }
 
+    /// Constructs an exact in trade through a single route made of the given pools, without
+    /// constructing the [`Route`] separately
+    ///
+    /// ## Arguments
+    ///
+    /// * `pools`: The pools of the route, in order from the input currency to the output currency
+    /// * `currency_in`: The input currency
+    /// * `amount_in`: The raw amount of input currency being passed in
+    /// * `currency_out`: The output currency
+    #[inline]
+    pub async fn from_pools_exact_in(
+        pools: Vec<Pool<TP>>,
+        currency_in: TInput,
+        amount_in: impl Into<BigInt>,
+        currency_out: TOutput,
+    ) -> Result<Self, Error> {
+        let amount_in = CurrencyAmount::from_raw_amount(currency_in.clone(), amount_in)?;
+        Self::exact_in(Route::new(pools, currency_in, currency_out)?, amount_in).await
+    }
+
     /// Constructs a trade by simulating swaps through the given route
     ///
     /// ## Arguments
@@ -554,22 +1066,55 @@ where
     /// * `route`: The route to swap through
     /// * `amount`: The amount specified, either input or output, depending on `trade_type`
     /// * `trade_type`: Whether the trade is an exact input or exact output swap
+    ///
+    /// Returns [`Error::EmptyPool`] if a pool of the route has no liquidity at its current price
+    /// and no liquidity ahead of it to fill the swap. A pool whose current liquidity is zero can
+    /// still swap by moving to its next initialized tick.
     #[inline]
-    #[allow(clippy::needless_pass_by_value)]
     pub async fn from_route(
         route: Route<TInput, TOutput, TP>,
         amount: CurrencyAmount<impl BaseCurrency>,
         trade_type: TradeType,
     ) -> Result<Self, Error> {
+        Ok(Self::from_route_with_state(route, amount, trade_type)
+            .await?
+            .0)
+    }
+
+    /// Constructs a trade by simulating swaps through the given route, also returning the state
+    /// of the route's pools after the swaps
+    ///
+    /// ## Arguments
+    ///
+    /// * `route`: The route to swap through
+    /// * `amount`: The amount specified, either input or output, depending on `trade_type`
+    /// * `trade_type`: Whether the trade is an exact input or exact output swap
+    ///
+    /// ## Returns
+    ///
+    /// The trade and the pools of the route after the swaps, in route order
+    #[inline]
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn from_route_with_state(
+        route: Route<TInput, TOutput, TP>,
+        amount: CurrencyAmount<impl BaseCurrency>,
+        trade_type: TradeType,
+    ) -> Result<(Self, Vec<Pool<TP>>), Error> {
         let input_amount: CurrencyAmount<TInput>;
         let output_amount: CurrencyAmount<TOutput>;
+        let mut pools_after = Vec::with_capacity(route.pools.len());
         match trade_type {
             TradeType::ExactInput => {
                 assert!(amount.currency.equals(&route.input), "INPUT");
                 // Account for trades that wrap/unwrap as a first step
                 let mut token_amount = amount_with_path_currency(&amount, &route.pools[0])?;
                 for pool in &route.pools {
-                    (token_amount, _) = pool.get_output_amount(&token_amount, None).await?;
+                    let pool_after;
+                    (token_amount, pool_after) = pool
+                        .get_output_amount(&token_amount, None)
+                        .await
+                        .map_err(|e| empty_pool_error(pool, e))?;
+                    pools_after.push(pool_after);
                 }
                 output_amount = CurrencyAmount::from_fractional_amount(
                     route.output.clone(),
@@ -588,8 +1133,14 @@ where
                 let mut token_amount =
                     amount_with_path_currency(&amount, route.pools.last().unwrap())?;
                 for pool in route.pools.iter().rev() {
-                    (token_amount, _) = pool.get_input_amount(&token_amount, None).await?;
+                    let pool_after;
+                    (token_amount, pool_after) = pool
+                        .get_input_amount(&token_amount, None)
+                        .await
+                        .map_err(|e| empty_pool_error(pool, e))?;
+                    pools_after.push(pool_after);
                 }
+                pools_after.reverse();
                 input_amount = CurrencyAmount::from_fractional_amount(
                     route.input.clone(),
                     token_amount.numerator,
@@ -602,24 +1153,152 @@ where
                 )?;
             }
         }
-        Self::new(
+        let trade = Self::new(
             vec![Swap::new(route, input_amount, output_amount)],
             trade_type,
-        )
+        )?;
+        Ok((trade, pools_after))
     }
 
-    /// Constructs a trade from routes by simulating swaps
+    /// Splits an exact input amount into `n` sequential exact input trades through the route,
+    /// e.g. for TWAP execution
+    ///
+    /// Each chunk is simulated against the pool state left by the previous chunk, so later chunks
+    /// get progressively worse prices. The remainder of the division goes to the last chunk.
     ///
     /// ## Arguments
     ///
-    /// * `routes`: The routes to swap through and how much of the amount should be routed through
-    ///   each
-    /// * `trade_type`: Whether the trade is an exact input or exact output swap
+    /// * `route`: The route to swap through
+    /// * `total_amount_in`: The total amount of input currency to spend
+    /// * `n`: The number of chunks
     #[inline]
-    pub async fn from_routes(
-        routes: Vec<(
-            CurrencyAmount<impl BaseCurrency>,
-            Route<TInput, TOutput, TP>,
+    pub async fn split_into_chunks(
+        mut route: Route<TInput, TOutput, TP>,
+        total_amount_in: CurrencyAmount<TInput>,
+        n: usize,
+    ) -> Result<Vec<Self>, Error> {
+        assert!(n > 0, "NUM_CHUNKS");
+        let chunk = total_amount_in.quotient() / BigInt::from(n as u64);
+        let remainder = total_amount_in.quotient() - chunk * BigInt::from(n as u64);
+        let input = route.input.clone();
+        let output = route.output.clone();
+        let hook_data = route.hook_data.clone();
+        let mut trades = Vec::with_capacity(n);
+        for i in 0..n {
+            let amount = if i == n - 1 { chunk + remainder } else { chunk };
+            let (trade, pools_after) = Self::from_route_with_state(
+                route,
+                CurrencyAmount::from_raw_amount(input.clone(), amount)?,
+                TradeType::ExactInput,
+            )
+            .await?;
+            trades.push(trade);
+            route = Route::new_with_hook_data(
+                pools_after,
+                input.clone(),
+                output.clone(),
+                hook_data.clone(),
+            )?;
+        }
+        Ok(trades)
+    }
+
+    /// Constructs the requested trade through the given route together with its counterpart of
+    /// the opposite trade type, in which the fixed and calculated amounts swap roles, e.g. an exact
+    /// output trade for the output amount of an exact input trade
+    ///
+    /// ## Arguments
+    ///
+    /// * `route`: The route to swap through
+    /// * `amount`: The amount specified, either input or output, depending on `trade_type`
+    /// * `trade_type`: The trade type of the requested trade
+    ///
+    /// ## Returns
+    ///
+    /// The requested trade and its counterpart
+    #[inline]
+    pub async fn both_directions(
+        route: Route<TInput, TOutput, TP>,
+        amount: CurrencyAmount<impl BaseCurrency>,
+        trade_type: TradeType,
+    ) -> Result<(Self, Self), Error> {
+        let trade = Self::from_route(route.clone(), amount, trade_type).await?;
+        let counterpart = match trade_type {
+            TradeType::ExactInput => {
+                Self::from_route(route, trade.output_amount()?, TradeType::ExactOutput).await?
+            }
+            TradeType::ExactOutput => {
+                Self::from_route(route, trade.input_amount()?, TradeType::ExactInput).await?
+            }
+        };
+        Ok((trade, counterpart))
+    }
+
+    /// Constructs a trade by simulating swaps through the given route, inferring the trade type
+    /// from the currency of `amount`
+    ///
+    /// ## Arguments
+    ///
+    /// * `route`: The route to swap through
+    /// * `amount`: The exact input amount if in the route's input currency, or the exact output
+    ///   amount if in the route's output currency
+    ///
+    /// Returns [`Error::InvalidCurrency`] if `amount` is in neither currency. Routes whose input
+    /// and output are the same currency are treated as exact input.
+    #[inline]
+    pub async fn from_route_auto(
+        route: Route<TInput, TOutput, TP>,
+        amount: CurrencyAmount<impl BaseCurrency>,
+    ) -> Result<Self, Error> {
+        let trade_type = if amount.currency.equals(&route.input) {
+            TradeType::ExactInput
+        } else if amount.currency.equals(&route.output) {
+            TradeType::ExactOutput
+        } else {
+            return Err(Error::InvalidCurrency);
+        };
+        Self::from_route(route, amount, trade_type).await
+    }
+
+    /// Constructs a trade by simulating swaps through the given route, rejecting it if its price
+    /// impact exceeds a cap
+    ///
+    /// ## Arguments
+    ///
+    /// * `route`: The route to swap through
+    /// * `amount`: The amount specified, either input or output, depending on `trade_type`
+    /// * `trade_type`: Whether the trade is an exact input or exact output swap
+    /// * `max_impact`: The maximum allowed [`Self::price_impact`] of the trade
+    ///
+    /// Returns [`Error::ExcessivePriceImpact`] if the price impact of the trade exceeds
+    /// `max_impact`.
+    #[inline]
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn from_route_with_max_impact(
+        route: Route<TInput, TOutput, TP>,
+        amount: CurrencyAmount<impl BaseCurrency>,
+        trade_type: TradeType,
+        max_impact: Percent,
+    ) -> Result<Self, Error> {
+        let trade = Self::from_route(route, amount, trade_type).await?;
+        if trade.price_impact()? > max_impact {
+            return Err(Error::ExcessivePriceImpact);
+        }
+        Ok(trade)
+    }
+
+    /// Constructs a trade from routes by simulating swaps
+    ///
+    /// ## Arguments
+    ///
+    /// * `routes`: The routes to swap through and how much of the amount should be routed through
+    ///   each
+    /// * `trade_type`: Whether the trade is an exact input or exact output swap
+    #[inline]
+    pub async fn from_routes(
+        routes: Vec<(
+            CurrencyAmount<impl BaseCurrency>,
+            Route<TInput, TOutput, TP>,
         )>,
         trade_type: TradeType,
     ) -> Result<Self, Error> {
@@ -631,6 +1310,40 @@ where
         Self::new(populated_routes, trade_type)
     }
 
+    /// Simulates the trade with every pool moved against the swap to the worst price allowed by
+    /// the slippage tolerance, and returns the total output received for the trade's input
+    ///
+    /// ## Note
+    ///
+    /// The counterfactual pools keep their current liquidity, so this is a stress test rather than
+    /// an exact quote.
+    ///
+    /// ## Arguments
+    ///
+    /// * `slippage_tolerance`: How far each pool's price may move against the swap
+    #[inline]
+    pub async fn simulate_at_worst_price(
+        &self,
+        slippage_tolerance: &Percent,
+    ) -> Result<CurrencyAmount<TOutput>, Error> {
+        let mut total = BigInt::ZERO;
+        for Swap {
+            route,
+            input_amount,
+            ..
+        } in &self.swaps
+        {
+            let mut token_amount = amount_with_path_currency(input_amount, &route.pools[0])?;
+            for pool in &route.pools {
+                let zero_for_one = token_amount.currency.equals(&pool.currency0);
+                let pool = worst_price_pool(pool, zero_for_one, slippage_tolerance)?;
+                (token_amount, _) = pool.get_output_amount(&token_amount, None).await?;
+            }
+            total += token_amount.quotient();
+        }
+        CurrencyAmount::from_raw_amount(self.output_currency().clone(), total).map_err(Error::Core)
+    }
+
     /// Given a list of pools, and a fixed amount in, returns the top `max_num_results` trades that
     /// go from an input token amount to an output token, making at most `max_hops` hops.
     ///
@@ -724,6 +1437,7 @@ where
                     BestTradeOptions {
                         max_num_results: Some(max_num_results),
                         max_hops: Some(max_hops - 1),
+                        ..best_trade_options
                     },
                     next_pools,
                     Some(&amount_out),
@@ -735,6 +1449,398 @@ where
         Ok(best_trades)
     }
 
+    /// Same as [`Self::best_trade_exact_in`], but borrows the pools and tracks the current path as
+    /// indices into them, only cloning pools into the route of a found trade
+    ///
+    /// ## Arguments
+    ///
+    /// * `pools`: The pools to consider in finding the best trade
+    /// * `currency_amount_in`: The exact amount of input currency to spend
+    /// * `currency_out`: The desired currency out
+    /// * `best_trade_options`: Maximum number of results to return and maximum number of hops a
+    ///   returned trade can make, e.g. 1 hop goes through a single pool
+    #[inline]
+    pub async fn best_trade_exact_in_ref(
+        pools: &[Pool<TP>],
+        currency_amount_in: &CurrencyAmount<TInput>,
+        currency_out: &TOutput,
+        best_trade_options: BestTradeOptions,
+    ) -> Result<Vec<Self>, Error> {
+        assert!(!pools.is_empty(), "POOLS");
+        let max_num_results = best_trade_options.max_num_results.unwrap_or(3);
+        let max_hops = best_trade_options.max_hops.unwrap_or(3);
+        assert!(max_hops > 0, "MAX_HOPS");
+        let mut best_trades = Vec::new();
+        Self::best_trade_exact_in_from_path(
+            pools,
+            currency_amount_in,
+            currency_out,
+            max_num_results,
+            max_hops,
+            &mut Vec::with_capacity(max_hops),
+            None,
+            &mut best_trades,
+        )
+        .await?;
+        Ok(best_trades)
+    }
+
+    /// Recursion of [`Self::best_trade_exact_in_ref`], extending the path of pool indices
+    /// `current_path` whose output is `next_amount_in`
+    #[allow(clippy::too_many_arguments)]
+    async fn best_trade_exact_in_from_path(
+        pools: &[Pool<TP>],
+        currency_amount_in: &CurrencyAmount<TInput>,
+        currency_out: &TOutput,
+        max_num_results: usize,
+        max_hops: usize,
+        current_path: &mut Vec<usize>,
+        next_amount_in: Option<&CurrencyAmount<Currency>>,
+        best_trades: &mut Vec<Self>,
+    ) -> Result<(), Error> {
+        for (i, pool) in pools.iter().enumerate() {
+            if current_path.contains(&i) {
+                continue;
+            }
+            // pool irrelevant
+            let amount_out = match next_amount_in {
+                Some(amount_in) => {
+                    if !pool.involves_token(&amount_in.currency) {
+                        continue;
+                    }
+                    pool.get_output_amount(amount_in, None).await
+                }
+                None => {
+                    if !pool.involves_token(&currency_amount_in.currency) {
+                        continue;
+                    }
+                    pool.get_output_amount(currency_amount_in, None).await
+                }
+            };
+            let amount_out = match amount_out {
+                Ok((amount_out, _)) => amount_out,
+                Err(Error::InsufficientLiquidity) => continue,
+                Err(e) => return Err(e),
+            };
+            // we have arrived at the output token, so this is the final trade of one of the paths
+            if amount_out.currency.equals(currency_out) {
+                let route_pools = current_path
+                    .iter()
+                    .chain([&i])
+                    .map(|&j| pools[j].clone())
+                    .collect();
+                let trade = Self::from_route(
+                    Route::new(
+                        route_pools,
+                        currency_amount_in.currency.clone(),
+                        currency_out.clone(),
+                    )?,
+                    currency_amount_in.clone(),
+                    TradeType::ExactInput,
+                )
+                .await?;
+                sorted_insert(best_trades, trade, max_num_results, trade_comparator);
+            } else if max_hops > 1 && pools.len() - current_path.len() > 1 {
+                // otherwise, consider all the other paths that lead from this token as long as we
+                // have not exceeded maxHops
+                current_path.push(i);
+                Box::pin(Self::best_trade_exact_in_from_path(
+                    pools,
+                    currency_amount_in,
+                    currency_out,
+                    max_num_results,
+                    max_hops - 1,
+                    current_path,
+                    Some(&amount_out),
+                    best_trades,
+                ))
+                .await?;
+                current_path.pop();
+            }
+        }
+        Ok(())
+    }
+
+    /// Given a list of pools, and a fixed amount in, returns a trade that splits the amount in
+    /// among the best routes to the output currency.
+    ///
+    /// ## Note
+    ///
+    /// The candidate routes are the ones found by [`Self::best_trade_exact_in`]. The amount in is
+    /// discretized into `num_splits` chunks, and each chunk is greedily assigned to the route giving
+    /// the best marginal output. Routes sharing a pool are never used together, so the same pool is
+    /// only reused across chunks of the same route, and no more routes are used than fit within
+    /// `max_distinct_pools`. `num_splits` is clamped to the raw amount in so that no chunk is
+    /// empty. Use [`Self::split_proportions`] to inspect the chosen split.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pools`: The pools to consider in finding the best trade
+    /// * `amount_in`: The exact amount of input currency to spend
+    /// * `currency_out`: The desired currency out
+    /// * `num_splits`: The number of chunks the amount in is split into
+    /// * `best_trade_options`: Maximum number of candidate routes, maximum number of hops a route
+    ///   can make and maximum number of distinct pools the trade can touch
+    ///
+    /// Returns [`Error::InsufficientLiquidity`] if no route can swap the amount in.
+    #[inline]
+    pub async fn best_split_trade_exact_in(
+        pools: Vec<Pool<TP>>,
+        amount_in: &CurrencyAmount<TInput>,
+        currency_out: &TOutput,
+        num_splits: usize,
+        best_trade_options: BestTradeOptions,
+    ) -> Result<Self, Error> {
+        assert!(num_splits > 0, "NUM_SPLITS");
+        let num_splits = Self::clamp_num_splits(num_splits, &amount_in.quotient());
+        let mut best_trades = Vec::new();
+        Self::best_trade_exact_in(
+            pools,
+            amount_in,
+            currency_out,
+            best_trade_options,
+            vec![],
+            None,
+            &mut best_trades,
+        )
+        .await?;
+        let routes: Vec<Route<TInput, TOutput, TP>> = best_trades
+            .into_iter()
+            .map(|trade| trade.swaps.into_iter().next().unwrap().route)
+            .collect();
+        if routes.is_empty() {
+            return Err(Error::InsufficientLiquidity);
+        }
+        let split_routes = Self::split_among_routes(
+            routes,
+            amount_in.quotient(),
+            TradeType::ExactInput,
+            num_splits,
+            best_trade_options.max_distinct_pools,
+        )
+        .await?
+        .into_iter()
+        .map(|(amount, route)| {
+            Ok((
+                CurrencyAmount::from_raw_amount(amount_in.currency.clone(), amount)?,
+                route,
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+        Self::from_routes(split_routes, TradeType::ExactInput).await
+    }
+
+    /// Clamps the number of chunks to the raw amount being split, so that no chunk is empty
+    fn clamp_num_splits(num_splits: usize, amount: &BigInt) -> usize {
+        if *amount < BigInt::from(num_splits as u64) {
+            amount.to_usize().unwrap_or_default().max(1)
+        } else {
+            num_splits
+        }
+    }
+
+    /// Splits the raw `amount` of a split trade among the candidate routes, in `num_splits` equal
+    /// chunks, and returns the amount of each route used
+    ///
+    /// Each chunk is greedily assigned to the route with the best marginal score, i.e. the most
+    /// output for exact input trades and the least input for exact output trades. Routes sharing
+    /// a pool are never used together, and no more routes are used than fit within
+    /// `max_distinct_pools`. The remainder of the discretization goes to the route with the most
+    /// chunks.
+    async fn split_among_routes(
+        routes: Vec<Route<TInput, TOutput, TP>>,
+        amount: BigInt,
+        trade_type: TradeType,
+        num_splits: usize,
+        max_distinct_pools: Option<usize>,
+    ) -> Result<Vec<(BigInt, Route<TInput, TOutput, TP>)>, Error> {
+        let pool_ids: Vec<HashSet<B256>> = routes
+            .iter()
+            .map(|route| route.pools.iter().map(|pool| pool.pool_id).collect())
+            .collect();
+
+        let chunk = amount / BigInt::from(num_splits as u64);
+        let mut chunks = vec![0_u64; routes.len()];
+        let mut scores = vec![BigInt::ZERO; routes.len()];
+        for _ in 0..num_splits {
+            let used_pools: usize = (0..routes.len())
+                .filter(|&j| chunks[j] > 0)
+                .map(|j| pool_ids[j].len())
+                .sum();
+            let mut best: Option<(usize, BigInt)> = None;
+            for (i, route) in routes.iter().enumerate() {
+                let shares_pool_with_used_route = (0..routes.len())
+                    .any(|j| j != i && chunks[j] > 0 && !pool_ids[i].is_disjoint(&pool_ids[j]));
+                if shares_pool_with_used_route {
+                    continue;
+                }
+                // routes already in use don't add pools, the others add all of theirs
+                if chunks[i] == 0
+                    && max_distinct_pools.is_some_and(|max| used_pools + pool_ids[i].len() > max)
+                {
+                    continue;
+                }
+                let Some(score) =
+                    Self::route_score(route, trade_type, chunk * BigInt::from(chunks[i] + 1))
+                        .await?
+                else {
+                    continue;
+                };
+                if best.is_none_or(|(j, best_score)| score - scores[i] > best_score - scores[j]) {
+                    best = Some((i, score));
+                }
+            }
+            let (i, score) = best.ok_or(Error::InsufficientLiquidity)?;
+            chunks[i] += 1;
+            scores[i] = score;
+        }
+
+        let remainder = amount - chunk * BigInt::from(num_splits as u64);
+        let mut most_chunks = 0;
+        for i in 1..routes.len() {
+            if chunks[i] > chunks[most_chunks] {
+                most_chunks = i;
+            }
+        }
+        Ok(routes
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, _)| chunks[i] > 0)
+            .map(|(i, route)| {
+                let mut route_amount = chunk * BigInt::from(chunks[i]);
+                if i == most_chunks {
+                    route_amount += remainder;
+                }
+                (route_amount, route)
+            })
+            .collect())
+    }
+
+    /// Scores swapping the raw `amount` of the route input for exact input trades, or of the route
+    /// output for exact output trades, through the route, such that a higher score is better: the
+    /// output amount, or the negated input amount. Returns `None` if the route has insufficient
+    /// liquidity.
+    async fn route_score(
+        route: &Route<TInput, TOutput, TP>,
+        trade_type: TradeType,
+        amount: BigInt,
+    ) -> Result<Option<BigInt>, Error> {
+        Ok(match trade_type {
+            TradeType::ExactInput => {
+                let amount = CurrencyAmount::from_raw_amount(route.input.clone(), amount)?;
+                Self::route_output(route, &amount).await?
+            }
+            TradeType::ExactOutput => {
+                let amount = CurrencyAmount::from_raw_amount(route.output.clone(), amount)?;
+                Self::route_input(route, &amount).await?.map(|input| -input)
+            }
+        })
+    }
+
+    /// Returns the raw output amount of swapping `amount` through the route, or `None` if the
+    /// route has insufficient liquidity
+    async fn route_output(
+        route: &Route<TInput, TOutput, TP>,
+        amount: &CurrencyAmount<TInput>,
+    ) -> Result<Option<BigInt>, Error> {
+        let mut token_amount = amount_with_path_currency(amount, &route.pools[0])?;
+        for pool in &route.pools {
+            token_amount = match pool.get_output_amount(&token_amount, None).await {
+                Ok((token_amount, _)) => token_amount,
+                Err(Error::InsufficientLiquidity) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+        }
+        Ok(Some(token_amount.quotient()))
+    }
+
+    /// Given a list of pools, and a fixed amount out, returns a trade that splits the amount out
+    /// among the routes from the input currency that require the least total input.
+    ///
+    /// ## Note
+    ///
+    /// The candidate routes are the ones found by [`Self::best_trade_exact_out`] for a single chunk
+    /// of the amount out, so routes that can't even output one chunk are skipped. The amount out is
+    /// discretized into `num_splits` chunks, and each chunk is greedily assigned to the route
+    /// requiring the least marginal input. Routes sharing a pool are never used together, and no
+    /// more routes are used than fit within `max_distinct_pools`. `num_splits` is clamped to the
+    /// raw amount out so that no chunk is empty.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pools`: The pools to consider in finding the best trade
+    /// * `currency_in`: The currency to spend
+    /// * `amount_out`: The exact amount of output currency to receive
+    /// * `num_splits`: The number of chunks the amount out is split into
+    /// * `best_trade_options`: Maximum number of candidate routes, maximum number of hops a route
+    ///   can make and maximum number of distinct pools the trade can touch
+    ///
+    /// Returns [`Error::InsufficientLiquidity`] if no route can output the amount out.
+    #[inline]
+    pub async fn best_split_trade_exact_out(
+        pools: Vec<Pool<TP>>,
+        currency_in: &TInput,
+        amount_out: &CurrencyAmount<TOutput>,
+        num_splits: usize,
+        best_trade_options: BestTradeOptions,
+    ) -> Result<Self, Error> {
+        assert!(num_splits > 0, "NUM_SPLITS");
+        let num_splits = Self::clamp_num_splits(num_splits, &amount_out.quotient());
+        let chunk = amount_out.quotient() / BigInt::from(num_splits as u64);
+        let mut best_trades = Vec::new();
+        Self::best_trade_exact_out(
+            pools,
+            currency_in,
+            &CurrencyAmount::from_raw_amount(amount_out.currency.clone(), chunk)?,
+            best_trade_options,
+            vec![],
+            None,
+            &mut best_trades,
+        )
+        .await?;
+        let routes: Vec<Route<TInput, TOutput, TP>> = best_trades
+            .into_iter()
+            .map(|trade| trade.swaps.into_iter().next().unwrap().route)
+            .collect();
+        if routes.is_empty() {
+            return Err(Error::InsufficientLiquidity);
+        }
+        let split_routes = Self::split_among_routes(
+            routes,
+            amount_out.quotient(),
+            TradeType::ExactOutput,
+            num_splits,
+            best_trade_options.max_distinct_pools,
+        )
+        .await?
+        .into_iter()
+        .map(|(amount, route)| {
+            Ok((
+                CurrencyAmount::from_raw_amount(amount_out.currency.clone(), amount)?,
+                route,
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+        Self::from_routes(split_routes, TradeType::ExactOutput).await
+    }
+
+    /// Returns the raw input amount required to receive `amount` from the route, or `None` if the
+    /// route has insufficient liquidity
+    async fn route_input(
+        route: &Route<TInput, TOutput, TP>,
+        amount: &CurrencyAmount<TOutput>,
+    ) -> Result<Option<BigInt>, Error> {
+        let mut token_amount = amount_with_path_currency(amount, route.pools.last().unwrap())?;
+        for pool in route.pools.iter().rev() {
+            token_amount = match pool.get_input_amount(&token_amount, None).await {
+                Ok((token_amount, _)) => token_amount,
+                Err(Error::InsufficientLiquidity) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+        }
+        Ok(Some(token_amount.quotient()))
+    }
+
     /// Given a list of pools, and a fixed amount out, returns the top `max_num_results` trades that
     /// go from an input token to an output token amount, making at most `max_hops` hops.
     ///
@@ -827,6 +1933,7 @@ where
                     BestTradeOptions {
                         max_num_results: Some(max_num_results),
                         max_hops: Some(max_hops - 1),
+                        ..best_trade_options
                     },
                     next_pools,
                     Some(&amount_in),
@@ -839,6 +1946,46 @@ where
     }
 }
 
+/// Returns the pool moved to the worst price for a swap in the given direction that is allowed by
+/// the slippage tolerance
+fn worst_price_pool<TP: Clone + TickDataProvider>(
+    pool: &Pool<TP>,
+    zero_for_one: bool,
+    slippage_tolerance: &Percent,
+) -> Result<Pool<TP>, Error> {
+    let one = Percent::new(1, 1);
+    // selling currency0 lowers its price, selling currency1 raises it
+    let factor = if zero_for_one {
+        one - slippage_tolerance
+    } else {
+        one + slippage_tolerance
+    };
+    let price = pool.token0_price().as_fraction() * factor.as_fraction();
+    let sqrt_price_x96 =
+        if price >= Fraction::new(MAX_SQRT_RATIO.to_big_int().pow(2), Q192.to_big_int()) {
+            MAX_SQRT_RATIO - U160::from(1)
+        } else {
+            encode_sqrt_ratio_x96(price.numerator, price.denominator)
+                .max(MIN_SQRT_RATIO + U160::from(1))
+        };
+    let mut pool = pool.clone();
+    pool.sqrt_price_x96 = sqrt_price_x96;
+    pool.tick_current = TP::Index::from_i24(sqrt_price_x96.get_tick_at_sqrt_ratio()?);
+    Ok(pool)
+}
+
+/// Reports the insufficient liquidity of a pool without liquidity at its current price as
+/// [`Error::EmptyPool`]
+fn empty_pool_error<TP: TickDataProvider>(pool: &Pool<TP>, error: Error) -> Error {
+    if pool.liquidity == 0 && error.is_insufficient_liquidity() {
+        Error::EmptyPool {
+            pool_id: pool.pool_id,
+        }
+    } else {
+        error
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1035,25 +2182,906 @@ mod tests {
             assert_eq!(trade.input_currency().clone(), TOKEN0.clone());
             assert_eq!(trade.output_currency().clone(), ETHER.clone());
         }
+
+        #[tokio::test]
+        async fn throws_for_route_with_empty_pool() {
+            let empty_pool = Pool {
+                liquidity: 0,
+                ..POOL_1_2.clone()
+            };
+            let route = create_route!(POOL_0_1, empty_pool; TOKEN0, TOKEN2);
+            for (amount, trade_type) in [
+                (TOKEN0_AMOUNT_10000.clone(), TradeType::ExactInput),
+                (TOKEN2_AMOUNT_100.clone(), TradeType::ExactOutput),
+            ] {
+                let result = Trade::from_route(route.clone(), amount, trade_type).await;
+                assert!(matches!(
+                    result,
+                    Err(Error::EmptyPool { pool_id }) if pool_id == POOL_1_2.pool_id
+                ));
+            }
+        }
+
+        #[tokio::test]
+        async fn swaps_through_pool_with_liquidity_ahead_of_price() {
+            // no liquidity at the current price, but ranges on both sides of it
+            let liquidity = 100_000_000_000_u128;
+            let pool = Pool::new_with_tick_data_provider(
+                TOKEN1.clone().into(),
+                TOKEN2.clone().into(),
+                FeeAmount::MEDIUM.into(),
+                60,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+                TickListDataProvider::new(
+                    vec![
+                        Tick::new(-600, liquidity, liquidity as i128),
+                        Tick::new(-60, liquidity, -(liquidity as i128)),
+                        Tick::new(60, liquidity, liquidity as i128),
+                        Tick::new(600, liquidity, -(liquidity as i128)),
+                    ],
+                    60,
+                ),
+            )
+            .unwrap();
+            let route = create_route!(POOL_0_1, pool; TOKEN0, TOKEN2);
+            for (amount, trade_type) in [
+                (TOKEN0_AMOUNT_10000.clone(), TradeType::ExactInput),
+                (TOKEN2_AMOUNT_100.clone(), TradeType::ExactOutput),
+            ] {
+                let (trade, pools_after) =
+                    Trade::from_route_with_state(route.clone(), amount, trade_type)
+                        .await
+                        .unwrap();
+                assert!(trade.input_amount().unwrap().quotient() > BigInt::ZERO);
+                assert!(trade.output_amount().unwrap().quotient() > BigInt::ZERO);
+                assert_eq!(pools_after[1].liquidity, liquidity);
+            }
+        }
     }
 
-    mod from_routes {
+    mod from_pools_exact_in {
         use super::*;
 
         #[tokio::test]
-        async fn can_be_constructed_with_ether_as_input_with_multiple_routes() {
-            let trade = Trade::from_routes(
-                vec![(ETHER_AMOUNT_10000.clone(), ROUTE_ETH_0.clone())],
-                TradeType::ExactInput,
+        async fn matches_trade_from_route() {
+            let trade = Trade::from_pools_exact_in(
+                vec![POOL_0_1.clone(), POOL_1_2.clone()],
+                TOKEN0.clone(),
+                10000,
+                TOKEN2.clone(),
             )
             .await
             .unwrap();
-            assert_eq!(trade.input_currency().clone(), ETHER.clone());
-            assert_eq!(trade.output_currency().clone(), TOKEN0.clone());
+            let expected = trade_from_route!(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput
+            );
+            assert_eq!(
+                trade.input_amount().unwrap().quotient(),
+                expected.input_amount().unwrap().quotient()
+            );
+            assert_eq!(
+                trade.output_amount().unwrap().quotient(),
+                expected.output_amount().unwrap().quotient()
+            );
         }
 
         #[tokio::test]
-        async fn can_be_constructed_with_ether_as_input_for_exact_output_with_multiple_routes() {
+        async fn throws_for_disconnected_pools() {
+            let result = Trade::from_pools_exact_in(
+                vec![POOL_0_1.clone(), POOL_0_2.clone()],
+                TOKEN0.clone(),
+                10000,
+                TOKEN2.clone(),
+            )
+            .await;
+            assert!(matches!(result, Err(Error::DisconnectedPath)));
+        }
+    }
+
+    mod round_trip_impact {
+        use super::*;
+
+        #[tokio::test]
+        async fn loses_the_fees_of_both_legs() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let reverse_route = ROUTE_0_1_2.clone().reversed().unwrap();
+            let impact = trade.round_trip_impact(&reverse_route).await.unwrap();
+            assert!(impact > Percent::new(0, 1));
+            assert!(impact < Percent::new(1, 1));
+        }
+
+        #[tokio::test]
+        async fn uses_the_current_state_of_the_reverse_route() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let reverse_route = ROUTE_0_1_2.clone().reversed().unwrap();
+            // swap through the pools as they are after the trade, whose price moved against the
+            // reverse swap
+            let mut token_amount =
+                amount_with_path_currency(&TOKEN0_AMOUNT_10000, &ROUTE_0_1_2.pools[0]).unwrap();
+            let mut pools_after = Vec::new();
+            for pool in &ROUTE_0_1_2.pools {
+                let pool_after;
+                (token_amount, pool_after) =
+                    pool.get_output_amount(&token_amount, None).await.unwrap();
+                pools_after.insert(0, pool_after);
+            }
+            let reverse_route_after =
+                Route::new(pools_after, TOKEN2.clone(), TOKEN0.clone()).unwrap();
+            assert!(
+                trade.round_trip_impact(&reverse_route_after).await.unwrap()
+                    > trade.round_trip_impact(&reverse_route).await.unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn throws_for_mismatched_reverse_route() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let reverse_route = create_route!(POOL_0_2, TOKEN2, TOKEN0);
+            assert!(trade.round_trip_impact(&reverse_route).await.is_ok());
+            let reverse_route = create_route!(POOL_1_2, TOKEN2, TOKEN1);
+            assert!(matches!(
+                trade.round_trip_impact(&reverse_route).await,
+                Err(Error::InvalidCurrency)
+            ));
+        }
+    }
+
+    mod split_into_chunks {
+        use super::*;
+
+        #[tokio::test]
+        async fn later_chunks_get_worse_prices() {
+            let trades =
+                Trade::split_into_chunks(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone(), 4)
+                    .await
+                    .unwrap();
+            assert_eq!(trades.len(), 4);
+            for trade in &trades {
+                assert_eq!(trade.input_amount().unwrap().quotient(), BigInt::from(2500));
+            }
+            for pair in trades.windows(2) {
+                assert!(
+                    pair[1].execution_price().unwrap().as_fraction()
+                        < pair[0].execution_price().unwrap().as_fraction()
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn chunks_are_simulated_sequentially() {
+            let trades =
+                Trade::split_into_chunks(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone(), 2)
+                    .await
+                    .unwrap();
+            let (first, pools_after) = Trade::from_route_with_state(
+                ROUTE_0_1.clone(),
+                currency_amount!(TOKEN0, 5000),
+                TradeType::ExactInput,
+            )
+            .await
+            .unwrap();
+            let second = Trade::exact_in(
+                Route::new(pools_after, TOKEN0.clone(), TOKEN1.clone()).unwrap(),
+                currency_amount!(TOKEN0, 5000),
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                trades[0].output_amount().unwrap().quotient(),
+                first.output_amount().unwrap().quotient()
+            );
+            assert_eq!(
+                trades[1].output_amount().unwrap().quotient(),
+                second.output_amount().unwrap().quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn assigns_the_remainder_to_the_last_chunk() {
+            let trades =
+                Trade::split_into_chunks(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone(), 3)
+                    .await
+                    .unwrap();
+            let amounts: Vec<BigInt> = trades
+                .iter()
+                .map(|trade| trade.input_amount().unwrap().quotient())
+                .collect();
+            assert_eq!(
+                amounts,
+                vec![BigInt::from(3333), BigInt::from(3333), BigInt::from(3334)]
+            );
+        }
+    }
+
+    mod best_trade_by_net_output {
+        use super::*;
+
+        static ROUTE_0_3_1_2: Lazy<Route<Token, Token, TickListDataProvider>> =
+            Lazy::new(|| create_route!(POOL_0_3, POOL_1_3, POOL_1_2; TOKEN0, TOKEN2));
+
+        // the base gas is the same for every trade, so only the 100 gas per hop tells them apart
+        const GAS_MODEL: GasModel = GasModel {
+            base_gas: 50,
+            per_hop_gas: 100,
+            hooked_pool_gas: 0,
+        };
+
+        fn candidates() -> Vec<Trade<Token, Token, TickListDataProvider>> {
+            vec![
+                Trade::create_unchecked_trade(
+                    ROUTE_0_3_1_2.clone(),
+                    TOKEN0_AMOUNT_10000.clone(),
+                    currency_amount!(TOKEN2, 9100),
+                    TradeType::ExactInput,
+                )
+                .unwrap(),
+                Trade::create_unchecked_trade(
+                    ROUTE_0_2.clone(),
+                    TOKEN0_AMOUNT_10000.clone(),
+                    currency_amount!(TOKEN2, 9000),
+                    TradeType::ExactInput,
+                )
+                .unwrap(),
+            ]
+        }
+
+        #[test]
+        fn prefers_cheaper_gas_over_higher_output() {
+            let candidates = candidates();
+            let best = best_trade_by_net_output(&candidates, 1, &GAS_MODEL, &Fraction::new(1, 1))
+                .unwrap()
+                .unwrap();
+            assert_eq!(best.swaps()[0].route, *ROUTE_0_2);
+        }
+
+        #[test]
+        fn prefers_higher_output_when_gas_is_free() {
+            let candidates = candidates();
+            let best = best_trade_by_net_output(&candidates, 0, &GAS_MODEL, &Fraction::new(1, 1))
+                .unwrap()
+                .unwrap();
+            assert_eq!(best.swaps()[0].route, *ROUTE_0_3_1_2);
+        }
+
+        #[test]
+        fn converts_gas_cost_to_output_terms() {
+            // the output is worth 10 gas tokens, so the extra 200 gas of the longer route only
+            // costs 20 of output
+            let candidates = candidates();
+            let best = best_trade_by_net_output(&candidates, 1, &GAS_MODEL, &Fraction::new(10, 1))
+                .unwrap()
+                .unwrap();
+            assert_eq!(best.swaps()[0].route, *ROUTE_0_3_1_2);
+        }
+
+        #[test]
+        fn returns_none_without_trades() {
+            assert!(
+                best_trade_by_net_output::<Token, Token, TickListDataProvider>(
+                    &[],
+                    1,
+                    &GAS_MODEL,
+                    &Fraction::new(1, 1)
+                )
+                .unwrap()
+                .is_none()
+            );
+        }
+
+        #[test]
+        fn throws_for_zero_output_price() {
+            assert!(matches!(
+                best_trade_by_net_output(&candidates(), 1, &GAS_MODEL, &Fraction::new(0, 1)),
+                Err(Error::ZeroPrice)
+            ));
+        }
+    }
+
+    mod invalidate_cache {
+        use super::*;
+
+        #[tokio::test]
+        async fn clears_cached_values() {
+            let mut trade = Trade::exact_in(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            trade.input_amount_cached().unwrap();
+            trade.output_amount_cached().unwrap();
+            trade.execution_price_cached().unwrap();
+            trade.price_impact_cached().unwrap();
+            assert!(trade._input_amount.is_some());
+            assert!(trade._output_amount.is_some());
+            assert!(trade._execution_price.is_some());
+            assert!(trade._price_impact.is_some());
+
+            trade.invalidate_cache();
+            assert!(trade._input_amount.is_none());
+            assert!(trade._output_amount.is_none());
+            assert!(trade._execution_price.is_none());
+            assert!(trade._price_impact.is_none());
+        }
+    }
+
+    mod with_swaps {
+        use super::*;
+
+        #[tokio::test]
+        async fn rebuilds_the_trade_without_stale_caches() {
+            let mut trade = Trade::exact_in(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let cached_output = trade.output_amount_cached().unwrap();
+            let other = Trade::exact_in(ROUTE_0_1.clone(), TOKEN0_AMOUNT_100.clone())
+                .await
+                .unwrap();
+            let rebuilt = trade.with_swaps(other.swaps().to_vec()).unwrap();
+            assert_eq!(rebuilt.trade_type, TradeType::ExactInput);
+            assert_eq!(rebuilt.swaps(), other.swaps());
+            assert!(rebuilt._output_amount.is_none());
+            assert_ne!(
+                rebuilt.output_amount().unwrap().quotient(),
+                cached_output.quotient()
+            );
+        }
+
+        #[tokio::test]
+        #[should_panic(expected = "POOLS_DUPLICATED")]
+        async fn validates_the_swaps() {
+            let trade = Trade::exact_in(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let swap = trade.swaps()[0].clone();
+            let _ = trade.with_swaps(vec![swap.clone(), swap]);
+        }
+    }
+
+    mod hop_outputs {
+        use super::*;
+
+        #[tokio::test]
+        async fn returns_amount_after_each_hop() {
+            let route = create_route!(POOL_0_3, POOL_1_3, POOL_1_2; TOKEN0, TOKEN2);
+            let trade = Trade::exact_in(route, TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let hop_outputs = trade.hop_outputs().await.unwrap();
+            assert_eq!(hop_outputs.len(), 3);
+            for (amount, currency) in hop_outputs.iter().zip([&*TOKEN3, &*TOKEN1, &*TOKEN2]) {
+                assert!(amount.currency.equals(currency));
+            }
+            let mut previous = TOKEN0_AMOUNT_10000.quotient();
+            for amount in &hop_outputs {
+                assert!(amount.quotient() > BigInt::ZERO);
+                assert!(amount.quotient() < previous);
+                previous = amount.quotient();
+            }
+            assert_eq!(
+                hop_outputs[2].quotient(),
+                trade.output_amount().unwrap().quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn ends_with_output_amount_for_exact_output() {
+            let trade = Trade::exact_out(ROUTE_0_1_2.clone(), TOKEN2_AMOUNT_100.clone())
+                .await
+                .unwrap();
+            let hop_outputs = trade.hop_outputs().await.unwrap();
+            assert_eq!(hop_outputs.len(), 2);
+            assert!(hop_outputs[0].currency.equals(&TOKEN1.clone()));
+            assert!(hop_outputs[1].currency.equals(&TOKEN2.clone()));
+            assert_eq!(hop_outputs[1].quotient(), TOKEN2_AMOUNT_100.quotient());
+            assert!(hop_outputs[0].quotient() > hop_outputs[1].quotient());
+        }
+
+        #[tokio::test]
+        async fn throws_for_multi_route_trade() {
+            let trade = Trade::create_unchecked_trade_with_multiple_routes(
+                vec![
+                    Swap::new(
+                        ROUTE_0_1_2.clone(),
+                        TOKEN0_AMOUNT_50.clone(),
+                        currency_amount!(TOKEN2, 35),
+                    ),
+                    Swap::new(
+                        ROUTE_0_2.clone(),
+                        TOKEN0_AMOUNT_50.clone(),
+                        currency_amount!(TOKEN2, 34),
+                    ),
+                ],
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            assert!(matches!(
+                trade.hop_outputs().await,
+                Err(Error::MultipleRoutes)
+            ));
+        }
+    }
+
+    mod requires_input_wrap {
+        use super::*;
+
+        #[test]
+        fn true_for_ether_input_on_a_weth_pool() {
+            let trade = Trade::create_unchecked_trade(
+                create_route!(POOL_WETH_0, ETHER, TOKEN0),
+                ETHER_AMOUNT_10000.clone(),
+                currency_amount!(TOKEN0, 9000),
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            assert!(trade.requires_input_wrap());
+        }
+
+        #[test]
+        fn true_for_weth_input_on_an_eth_pool() {
+            let trade = Trade::create_unchecked_trade(
+                create_route!(POOL_ETH_0, WETH, TOKEN0),
+                currency_amount!(WETH, 10000),
+                currency_amount!(TOKEN0, 9000),
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            assert!(trade.requires_input_wrap());
+        }
+
+        #[test]
+        fn false_when_input_matches_pool() {
+            let trade = Trade::create_unchecked_trade(
+                ROUTE_ETH_0.clone(),
+                ETHER_AMOUNT_10000.clone(),
+                currency_amount!(TOKEN0, 9000),
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            assert!(!trade.requires_input_wrap());
+            let trade = Trade::create_unchecked_trade(
+                ROUTE_0_1.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                currency_amount!(TOKEN1, 9000),
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            assert!(!trade.requires_input_wrap());
+        }
+    }
+
+    mod path_with_amounts {
+        use super::*;
+
+        #[tokio::test]
+        async fn lists_each_currency_with_its_amount() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let (intermediate_amount, _) = POOL_0_1
+                .get_output_amount(&TOKEN0_AMOUNT_10000, None)
+                .await
+                .unwrap();
+            let path = trade.path_with_amounts().await.unwrap();
+            assert_eq!(path.len(), 3);
+            assert_eq!(
+                path,
+                vec![
+                    ("t0".to_string(), "0.00000000000001".to_string()),
+                    ("t1".to_string(), intermediate_amount.to_exact()),
+                    ("t2".to_string(), trade.output_amount().unwrap().to_exact()),
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn lists_currencies_in_path_order_for_exact_output() {
+            let trade = Trade::exact_out(ROUTE_0_1_2.clone(), TOKEN2_AMOUNT_100.clone())
+                .await
+                .unwrap();
+            let symbols: Vec<String> = trade
+                .path_with_amounts()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|(symbol, _)| symbol)
+                .collect();
+            assert_eq!(symbols, ["t0", "t1", "t2"]);
+        }
+
+        #[tokio::test]
+        async fn throws_for_multi_route_trade() {
+            let trade = Trade::create_unchecked_trade_with_multiple_routes(
+                vec![
+                    Swap::new(
+                        ROUTE_0_1_2.clone(),
+                        TOKEN0_AMOUNT_50.clone(),
+                        currency_amount!(TOKEN2, 35),
+                    ),
+                    Swap::new(
+                        ROUTE_0_2.clone(),
+                        TOKEN0_AMOUNT_50.clone(),
+                        currency_amount!(TOKEN2, 34),
+                    ),
+                ],
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            assert!(matches!(
+                trade.path_with_amounts().await,
+                Err(Error::MultipleRoutes)
+            ));
+        }
+    }
+
+    mod arb_profit {
+        use super::*;
+
+        #[tokio::test]
+        async fn returns_output_minus_input_for_cycles() {
+            let route = Route::new(
+                vec![POOL_ETH_0.clone(), POOL_0_1.clone(), POOL_ETH_1.clone()],
+                ETHER.clone(),
+                ETHER.clone(),
+            )
+            .unwrap();
+            let trade = Trade::exact_in(route, ETHER_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let profit = trade.arb_profit().unwrap();
+            assert!(profit.currency.equals(&ETHER.clone()));
+            assert_eq!(
+                profit.quotient(),
+                trade.output_amount().unwrap().quotient() - ETHER_AMOUNT_10000.quotient()
+            );
+            // balanced pools with fees can't be arbitraged
+            assert!(profit.quotient() < BigInt::ZERO);
+        }
+
+        #[tokio::test]
+        async fn throws_if_currencies_differ() {
+            let trade = Trade::exact_in(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            assert!(matches!(trade.arb_profit(), Err(Error::InvalidCurrency)));
+        }
+    }
+
+    mod intermediate_flows {
+        use super::*;
+
+        #[tokio::test]
+        async fn records_intermediate_amount_for_exact_input() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let (expected, _) = POOL_0_1
+                .get_output_amount(&TOKEN0_AMOUNT_10000, None)
+                .await
+                .unwrap();
+            let flows = trade.intermediate_flows().await.unwrap();
+            assert_eq!(flows.len(), 1);
+            assert_eq!(flows[&TOKEN1.address()].quotient(), expected.quotient());
+            assert!(flows[&TOKEN1.address()].currency.equals(&TOKEN1.clone()));
+        }
+
+        #[tokio::test]
+        async fn records_intermediate_amount_for_exact_output() {
+            let trade = Trade::exact_out(ROUTE_0_1_2.clone(), TOKEN2_AMOUNT_100.clone())
+                .await
+                .unwrap();
+            let (expected, _) = POOL_1_2
+                .get_input_amount(&TOKEN2_AMOUNT_100, None)
+                .await
+                .unwrap();
+            let flows = trade.intermediate_flows().await.unwrap();
+            assert_eq!(flows.len(), 1);
+            assert_eq!(flows[&TOKEN1.address()].quotient(), expected.quotient());
+        }
+
+        #[tokio::test]
+        async fn is_empty_for_single_hop_trades() {
+            let trade = Trade::exact_in(ROUTE_0_1.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            assert!(trade.intermediate_flows().await.unwrap().is_empty());
+        }
+    }
+
+    mod realized_slippage {
+        use super::*;
+
+        #[tokio::test]
+        async fn is_positive_for_output_below_quote() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let quoted = trade.output_amount().unwrap().quotient();
+            let actual = currency_amount!(TOKEN2, quoted - BigInt::from(70));
+            let slippage = trade.realized_slippage(&actual).unwrap();
+            assert!(slippage > Percent::new(0, 1));
+            assert_eq!(slippage.numerator, BigInt::from(70));
+            assert_eq!(slippage.denominator, quoted);
+        }
+
+        #[tokio::test]
+        async fn is_zero_for_output_matching_quote() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let slippage = trade
+                .realized_slippage(&trade.output_amount().unwrap())
+                .unwrap();
+            assert_eq!(slippage.numerator, BigInt::ZERO);
+        }
+
+        #[tokio::test]
+        async fn is_negative_for_output_above_quote() {
+            let trade = Trade::exact_in(ROUTE_0_1_2.clone(), TOKEN0_AMOUNT_10000.clone())
+                .await
+                .unwrap();
+            let quoted = trade.output_amount().unwrap().quotient();
+            let actual = currency_amount!(TOKEN2, quoted + BigInt::from(70));
+            assert!(trade.realized_slippage(&actual).unwrap() < Percent::new(0, 1));
+        }
+    }
+
+    mod fee_overrides {
+        use super::*;
+        use alloy_primitives::aliases::U24;
+
+        #[tokio::test]
+        async fn lower_fees_quote_more_output() {
+            let trade = Trade::from_route(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+            )
+            .await
+            .unwrap();
+            let fees = [U24::from(FeeAmount::LOWEST); 2];
+            let overridden = Trade::from_route(
+                ROUTE_0_1_2.with_fee_overrides(&fees).unwrap(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+            )
+            .await
+            .unwrap();
+            assert!(
+                overridden.output_amount().unwrap().quotient()
+                    > trade.output_amount().unwrap().quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn original_fees_quote_the_same_output() {
+            let trade = Trade::from_route(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+            )
+            .await
+            .unwrap();
+            let fees: Vec<_> = ROUTE_0_1_2.pools.iter().map(|pool| pool.fee).collect();
+            let overridden = Trade::from_route(
+                ROUTE_0_1_2.with_fee_overrides(&fees).unwrap(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                overridden.output_amount().unwrap().quotient(),
+                trade.output_amount().unwrap().quotient()
+            );
+        }
+    }
+
+    mod both_directions {
+        use super::*;
+
+        #[tokio::test]
+        async fn exact_input_counterpart_requires_the_same_input() {
+            let (trade, counterpart) = Trade::both_directions(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.trade_type, TradeType::ExactInput);
+            assert_eq!(counterpart.trade_type, TradeType::ExactOutput);
+            assert_eq!(
+                counterpart.output_amount().unwrap().quotient(),
+                trade.output_amount().unwrap().quotient()
+            );
+            // the amounts only differ by rounding at each hop
+            let difference = counterpart.input_amount().unwrap().quotient()
+                - trade.input_amount().unwrap().quotient();
+            assert!(difference.abs() <= BigInt::from(5_u64));
+        }
+
+        #[tokio::test]
+        async fn exact_output_counterpart_returns_the_same_output() {
+            let (trade, counterpart) = Trade::both_directions(
+                ROUTE_0_1_2.clone(),
+                TOKEN2_AMOUNT_100.clone(),
+                TradeType::ExactOutput,
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.trade_type, TradeType::ExactOutput);
+            assert_eq!(counterpart.trade_type, TradeType::ExactInput);
+            assert_eq!(
+                counterpart.input_amount().unwrap().quotient(),
+                trade.input_amount().unwrap().quotient()
+            );
+            let difference = trade.output_amount().unwrap().quotient()
+                - counterpart.output_amount().unwrap().quotient();
+            assert!(difference.abs() <= BigInt::from(5_u64));
+        }
+    }
+
+    mod from_route_auto {
+        use super::*;
+
+        #[tokio::test]
+        async fn infers_exact_input_from_the_input_currency() {
+            let trade = Trade::from_route_auto(ROUTE_0_1.clone(), TOKEN0_AMOUNT_100.clone())
+                .await
+                .unwrap();
+            let expected = trade_from_route!(
+                ROUTE_0_1.clone(),
+                TOKEN0_AMOUNT_100.clone(),
+                TradeType::ExactInput
+            );
+            assert_eq!(trade.trade_type, TradeType::ExactInput);
+            assert_eq!(
+                trade.input_amount().unwrap(),
+                expected.input_amount().unwrap()
+            );
+            assert_eq!(
+                trade.output_amount().unwrap(),
+                expected.output_amount().unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn infers_exact_output_from_the_output_currency() {
+            let amount = currency_amount!(TOKEN1, 100);
+            let trade = Trade::from_route_auto(ROUTE_0_1.clone(), amount.clone())
+                .await
+                .unwrap();
+            let expected = trade_from_route!(ROUTE_0_1.clone(), amount, TradeType::ExactOutput);
+            assert_eq!(trade.trade_type, TradeType::ExactOutput);
+            assert_eq!(
+                trade.input_amount().unwrap(),
+                expected.input_amount().unwrap()
+            );
+            assert_eq!(
+                trade.output_amount().unwrap(),
+                expected.output_amount().unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn throws_if_amount_is_in_neither_currency() {
+            let result = Trade::from_route_auto(ROUTE_0_1.clone(), TOKEN2_AMOUNT_100.clone()).await;
+            assert!(matches!(result, Err(Error::InvalidCurrency)));
+        }
+    }
+
+    mod from_route_with_max_impact {
+        use super::*;
+
+        #[tokio::test]
+        async fn succeeds_within_cap() {
+            let expected = trade_from_route!(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput
+            );
+            let price_impact = expected.price_impact().unwrap();
+            let trade = Trade::from_route_with_max_impact(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+                price_impact.clone(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.price_impact().unwrap(), price_impact);
+            assert_eq!(
+                trade.output_amount().unwrap().quotient(),
+                expected.output_amount().unwrap().quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn throws_over_cap() {
+            let expected = trade_from_route!(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput
+            );
+            assert!(expected.price_impact().unwrap() > Percent::new(0, 1));
+            let result = Trade::from_route_with_max_impact(
+                ROUTE_0_1_2.clone(),
+                TOKEN0_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+                Percent::new(0, 1),
+            )
+            .await;
+            assert!(matches!(result, Err(Error::ExcessivePriceImpact)));
+        }
+    }
+
+    mod simulate_at_worst_price {
+        use super::*;
+
+        #[tokio::test]
+        async fn worst_case_output_is_at_most_nominal_output() {
+            for trade in [
+                trade_from_route!(ROUTE_0_1_2, TOKEN0_AMOUNT_100, TradeType::ExactInput),
+                trade_from_route!(ROUTE_0_2_1, TOKEN0_AMOUNT_100, TradeType::ExactInput),
+                trade_from_route!(ROUTE_0_2, TOKEN2_AMOUNT_100, TradeType::ExactOutput),
+            ] {
+                let nominal = trade.output_amount().unwrap();
+                let worst = trade
+                    .simulate_at_worst_price(&Percent::new(5, 100))
+                    .await
+                    .unwrap();
+                assert!(worst.currency.equals(&nominal.currency));
+                assert!(worst.quotient() < nominal.quotient());
+            }
+        }
+
+        #[tokio::test]
+        async fn zero_slippage_reproduces_nominal_output() {
+            let trade = trade_from_route!(ROUTE_0_1_2, TOKEN0_AMOUNT_100, TradeType::ExactInput);
+            let worst = trade
+                .simulate_at_worst_price(&Percent::default())
+                .await
+                .unwrap();
+            assert_eq!(worst.quotient(), trade.output_amount().unwrap().quotient());
+        }
+
+        #[tokio::test]
+        async fn handles_ether_output() {
+            let trade = trade_from_route!(ROUTE_0_1_ETH, TOKEN0_AMOUNT_100, TradeType::ExactInput);
+            let worst = trade
+                .simulate_at_worst_price(&Percent::new(1, 100))
+                .await
+                .unwrap();
+            assert!(worst.currency.is_native());
+            assert!(worst.quotient() <= trade.output_amount().unwrap().quotient());
+        }
+    }
+
+    mod from_routes {
+        use super::*;
+
+        #[tokio::test]
+        async fn can_be_constructed_with_ether_as_input_with_multiple_routes() {
+            let trade = Trade::from_routes(
+                vec![(ETHER_AMOUNT_10000.clone(), ROUTE_ETH_0.clone())],
+                TradeType::ExactInput,
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.input_currency().clone(), ETHER.clone());
+            assert_eq!(trade.output_currency().clone(), TOKEN0.clone());
+        }
+
+        #[tokio::test]
+        async fn can_be_constructed_with_ether_as_input_for_exact_output_with_multiple_routes() {
             let trade = Trade::from_routes(
                 vec![
                     (currency_amount!(TOKEN0, 3000), ROUTE_ETH_0.clone()),
@@ -1100,6 +3128,42 @@ mod tests {
             assert_eq!(trade.output_currency().clone(), ETHER.clone());
         }
 
+        #[tokio::test]
+        async fn exact_output_input_amount_is_the_sum_of_swap_inputs() {
+            let trade = Trade::from_routes(
+                vec![
+                    (currency_amount!(ETHER, 3333), ROUTE_0_ETH.clone()),
+                    (currency_amount!(ETHER, 3333), ROUTE_0_1_ETH.clone()),
+                    (
+                        currency_amount!(ETHER, 3334),
+                        create_route!(POOL_0_2, POOL_ETH_2; TOKEN0, ETHER),
+                    ),
+                ],
+                TradeType::ExactOutput,
+            )
+            .await
+            .unwrap();
+            let sum_of_inputs = trade.swaps.iter().fold(BigInt::from(0), |sum, swap| {
+                sum + swap.input_amount.quotient()
+            });
+            let input_amount = trade.input_amount().unwrap();
+            assert_eq!(input_amount.quotient(), sum_of_inputs);
+            assert_eq!(input_amount.denominator, BigInt::from(1));
+            assert_eq!(trade.output_amount().unwrap().quotient(), 10000.into());
+
+            let slippage_tolerance = Percent::new(1, 100);
+            let maximum_amount_in = trade
+                .maximum_amount_in(slippage_tolerance.clone(), None)
+                .unwrap();
+            let expected = trade
+                .maximum_amount_in(
+                    slippage_tolerance,
+                    Some(currency_amount!(TOKEN0, sum_of_inputs)),
+                )
+                .unwrap();
+            assert_eq!(maximum_amount_in.quotient(), expected.quotient());
+        }
+
         #[tokio::test]
         #[should_panic(expected = "POOLS_DUPLICATED")]
         async fn throws_if_pools_are_reused_between_routes() {
@@ -1118,6 +3182,113 @@ mod tests {
         }
     }
 
+    mod trade_comparator_with_gas {
+        use super::*;
+        use alloy_primitives::address;
+
+        const SWAP_HOOK: Address = address!("0000000000000000000000000000000000000080");
+
+        fn pool(currency0: &Token, currency1: &Token, hooks: Address) -> Pool {
+            Pool::new(
+                currency0.clone().into(),
+                currency1.clone().into(),
+                FeeAmount::MEDIUM.into(),
+                60,
+                hooks,
+                *SQRT_PRICE_1_1,
+                ONE_ETHER,
+            )
+            .unwrap()
+        }
+
+        fn trade(pools: Vec<Pool>) -> Trade<Token, Token, NoTickDataProvider> {
+            Trade::create_unchecked_trade(
+                Route::new(pools, TOKEN0.clone(), TOKEN2.clone()).unwrap(),
+                TOKEN0_AMOUNT_100.clone(),
+                TOKEN2_AMOUNT_100.clone(),
+                TradeType::ExactInput,
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn prefers_vanilla_pool_over_hooked_pool_with_same_hops() {
+            let vanilla = trade(vec![pool(&TOKEN0, &TOKEN2, Address::ZERO)]);
+            let hooked = trade(vec![pool(&TOKEN0, &TOKEN2, SWAP_HOOK)]);
+            let gas_model = GasModel::default();
+
+            assert_eq!(trade_comparator(&vanilla, &hooked), Ordering::Equal);
+            assert_eq!(
+                trade_comparator_with_gas(&vanilla, &hooked, &gas_model),
+                Ordering::Less
+            );
+            assert_eq!(
+                trade_comparator_with_gas(&hooked, &vanilla, &gas_model),
+                Ordering::Greater
+            );
+        }
+
+        #[test]
+        fn estimated_gas_adds_surcharge_for_hooked_pools() {
+            let gas_model = GasModel::default();
+            let vanilla = trade(vec![pool(&TOKEN0, &TOKEN2, Address::ZERO)]);
+            let hooked = trade(vec![pool(&TOKEN0, &TOKEN2, SWAP_HOOK)]);
+            let two_hops = trade(vec![
+                pool(&TOKEN0, &TOKEN1, Address::ZERO),
+                pool(&TOKEN1, &TOKEN2, SWAP_HOOK),
+            ]);
+
+            assert_eq!(vanilla.estimated_gas(&gas_model), 60_000 + 80_000);
+            assert_eq!(hooked.estimated_gas(&gas_model), 60_000 + 80_000 + 50_000);
+            assert_eq!(
+                two_hops.estimated_gas(&gas_model),
+                60_000 + 2 * 80_000 + 50_000
+            );
+        }
+
+        #[test]
+        fn uses_gas_weight_instead_of_hop_count() {
+            let two_hops = trade(vec![
+                pool(&TOKEN0, &TOKEN1, Address::ZERO),
+                pool(&TOKEN1, &TOKEN2, Address::ZERO),
+            ]);
+            let hooked = trade(vec![pool(&TOKEN0, &TOKEN2, SWAP_HOOK)]);
+            let gas_model = GasModel {
+                base_gas: 0,
+                per_hop_gas: 100,
+                hooked_pool_gas: 200,
+            };
+
+            assert_eq!(trade_comparator(&hooked, &two_hops), Ordering::Less);
+            assert_eq!(
+                trade_comparator_with_gas(&two_hops, &hooked, &gas_model),
+                Ordering::Less
+            );
+        }
+
+        #[test]
+        fn still_ranks_by_output_first() {
+            let vanilla = trade(vec![pool(&TOKEN0, &TOKEN2, Address::ZERO)]);
+            let hooked = Trade::create_unchecked_trade(
+                Route::new(
+                    vec![pool(&TOKEN0, &TOKEN2, SWAP_HOOK)],
+                    TOKEN0.clone(),
+                    TOKEN2.clone(),
+                )
+                .unwrap(),
+                TOKEN0_AMOUNT_100.clone(),
+                TOKEN2_AMOUNT_10000.clone(),
+                TradeType::ExactInput,
+            )
+            .unwrap();
+
+            assert_eq!(
+                trade_comparator_with_gas(&hooked, &vanilla, &GasModel::default()),
+                Ordering::Less
+            );
+        }
+    }
+
     mod create_unchecked_trade {
         use super::*;
 
@@ -1920,6 +4091,443 @@ mod tests {
         }
     }
 
+    mod best_trade_exact_in_ref {
+        use super::*;
+
+        #[tokio::test]
+        #[should_panic(expected = "POOLS")]
+        async fn throws_with_empty_pools() {
+            let _ = Trade::<Token, Token, NoTickDataProvider>::best_trade_exact_in_ref(
+                &[],
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        #[tokio::test]
+        async fn matches_best_trade_exact_in() {
+            let pools = vec![
+                POOL_0_1.clone(),
+                POOL_0_2.clone(),
+                POOL_0_3.clone(),
+                POOL_1_2.clone(),
+                POOL_1_3.clone(),
+            ];
+            let options = [
+                BestTradeOptions::default(),
+                BestTradeOptions {
+                    max_hops: Some(1),
+                    ..Default::default()
+                },
+                BestTradeOptions {
+                    max_num_results: Some(1),
+                    ..Default::default()
+                },
+                BestTradeOptions {
+                    max_num_results: Some(10),
+                    max_hops: Some(4),
+                    ..Default::default()
+                },
+            ];
+            for currency_out in [&*TOKEN2, &*TOKEN3] {
+                for options in options {
+                    let mut expected = vec![];
+                    Trade::best_trade_exact_in(
+                        pools.clone(),
+                        &TOKEN0_AMOUNT_10000.clone(),
+                        currency_out,
+                        options,
+                        vec![],
+                        None,
+                        &mut expected,
+                    )
+                    .await
+                    .unwrap();
+                    let result = Trade::best_trade_exact_in_ref(
+                        &pools,
+                        &TOKEN0_AMOUNT_10000.clone(),
+                        currency_out,
+                        options,
+                    )
+                    .await
+                    .unwrap();
+
+                    assert!(!result.is_empty());
+                    assert_eq!(result.len(), expected.len());
+                    for (trade, expected) in result.iter().zip(&expected) {
+                        assert_eq!(trade.swaps[0].route, expected.swaps[0].route);
+                        assert_eq!(
+                            trade.output_amount().unwrap().quotient(),
+                            expected.output_amount().unwrap().quotient()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    mod best_split_trade_exact_in {
+        use super::*;
+
+        static POOLS: Lazy<Vec<Pool<TickListDataProvider>>> = Lazy::new(|| {
+            vec![
+                POOL_0_1.clone(),
+                POOL_0_2.clone(),
+                POOL_0_3.clone(),
+                POOL_1_2.clone(),
+                POOL_1_3.clone(),
+            ]
+        });
+
+        async fn best_single_route_output() -> CurrencyAmount<Token> {
+            let mut best_trades = vec![];
+            Trade::best_trade_exact_in(
+                POOLS.clone(),
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                BestTradeOptions::default(),
+                vec![],
+                None,
+                &mut best_trades,
+            )
+            .await
+            .unwrap();
+            best_trades[0].output_amount().unwrap()
+        }
+
+        #[tokio::test]
+        #[should_panic(expected = "NUM_SPLITS")]
+        async fn throws_with_zero_splits() {
+            let _ = Trade::best_split_trade_exact_in(
+                POOLS.clone(),
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                0,
+                BestTradeOptions::default(),
+            )
+            .await;
+        }
+
+        #[tokio::test]
+        async fn clamps_splits_to_the_amount() {
+            let trade = Trade::best_split_trade_exact_in(
+                POOLS.clone(),
+                &currency_amount!(TOKEN0, 3),
+                &TOKEN2.clone(),
+                10,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.input_amount().unwrap().quotient(), 3.into());
+            for swap in &trade.swaps {
+                assert!(swap.input_amount.quotient() > BigInt::ZERO);
+            }
+        }
+
+        #[tokio::test]
+        async fn single_split_matches_best_trade() {
+            let trade = Trade::best_split_trade_exact_in(
+                POOLS.clone(),
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                1,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.swaps.len(), 1);
+            assert_eq!(
+                trade.output_amount().unwrap().quotient(),
+                best_single_route_output().await.quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn splits_large_amounts_across_disjoint_routes() {
+            let trade = Trade::best_split_trade_exact_in(
+                POOLS.clone(),
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                10,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.trade_type, TradeType::ExactInput);
+            assert!(trade.swaps.len() > 1);
+            assert_eq!(
+                trade.input_amount().unwrap().quotient(),
+                TOKEN0_AMOUNT_10000.quotient()
+            );
+            assert!(
+                trade.output_amount().unwrap().quotient()
+                    > best_single_route_output().await.quotient()
+            );
+
+            let proportions = trade.split_proportions().unwrap();
+            assert_eq!(proportions.len(), trade.swaps.len());
+            let total = proportions
+                .into_iter()
+                .fold(Percent::default(), |total, proportion| total + proportion);
+            assert_eq!(total.numerator, total.denominator);
+        }
+
+        #[tokio::test]
+        async fn respects_max_distinct_pools() {
+            for max_distinct_pools in 1..=4 {
+                let trade = Trade::best_split_trade_exact_in(
+                    POOLS.clone(),
+                    &TOKEN0_AMOUNT_10000.clone(),
+                    &TOKEN2.clone(),
+                    10,
+                    BestTradeOptions {
+                        max_distinct_pools: Some(max_distinct_pools),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+                let distinct_pools: usize =
+                    trade.swaps.iter().map(|swap| swap.route.pools.len()).sum();
+                assert!(distinct_pools <= max_distinct_pools);
+                assert_eq!(
+                    trade.input_amount().unwrap().quotient(),
+                    TOKEN0_AMOUNT_10000.quotient()
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn uses_fewer_splits_under_a_tight_cap() {
+            let unconstrained = Trade::best_split_trade_exact_in(
+                POOLS.clone(),
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                10,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            let capped = Trade::best_split_trade_exact_in(
+                POOLS.clone(),
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                10,
+                BestTradeOptions {
+                    max_distinct_pools: Some(2),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert!(capped.swaps.len() < unconstrained.swaps.len());
+            assert!(
+                capped.output_amount().unwrap().quotient()
+                    <= unconstrained.output_amount().unwrap().quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn throws_if_no_route_fits_the_cap() {
+            let result = Trade::best_split_trade_exact_in(
+                vec![POOL_0_1.clone(), POOL_1_2.clone()],
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                2,
+                BestTradeOptions {
+                    max_distinct_pools: Some(1),
+                    ..Default::default()
+                },
+            )
+            .await;
+            assert!(matches!(result, Err(Error::InsufficientLiquidity)));
+        }
+
+        #[tokio::test]
+        async fn throws_without_routes() {
+            let result = Trade::best_split_trade_exact_in(
+                vec![POOL_0_1.clone()],
+                &TOKEN0_AMOUNT_10000.clone(),
+                &TOKEN2.clone(),
+                2,
+                BestTradeOptions::default(),
+            )
+            .await;
+            assert!(matches!(result, Err(Error::InsufficientLiquidity)));
+        }
+    }
+
+    mod best_split_trade_exact_out {
+        use super::*;
+
+        static POOLS: Lazy<Vec<Pool<TickListDataProvider>>> = Lazy::new(|| {
+            vec![
+                POOL_0_1.clone(),
+                POOL_0_2.clone(),
+                POOL_0_3.clone(),
+                POOL_1_2.clone(),
+                POOL_1_3.clone(),
+            ]
+        });
+
+        async fn best_single_route_input() -> CurrencyAmount<Token> {
+            let mut best_trades = vec![];
+            Trade::best_trade_exact_out(
+                POOLS.clone(),
+                &TOKEN0.clone(),
+                &TOKEN2_AMOUNT_10000.clone(),
+                BestTradeOptions::default(),
+                vec![],
+                None,
+                &mut best_trades,
+            )
+            .await
+            .unwrap();
+            best_trades[0].input_amount().unwrap()
+        }
+
+        #[tokio::test]
+        #[should_panic(expected = "NUM_SPLITS")]
+        async fn throws_with_zero_splits() {
+            let _ = Trade::best_split_trade_exact_out(
+                POOLS.clone(),
+                &TOKEN0.clone(),
+                &TOKEN2_AMOUNT_10000.clone(),
+                0,
+                BestTradeOptions::default(),
+            )
+            .await;
+        }
+
+        #[tokio::test]
+        async fn clamps_splits_to_the_amount() {
+            let trade = Trade::best_split_trade_exact_out(
+                POOLS.clone(),
+                &TOKEN0.clone(),
+                &currency_amount!(TOKEN2, 3),
+                10,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.output_amount().unwrap().quotient(), 3.into());
+            for swap in &trade.swaps {
+                assert!(swap.output_amount.quotient() > BigInt::ZERO);
+            }
+        }
+
+        #[tokio::test]
+        async fn single_split_matches_best_trade() {
+            let trade = Trade::best_split_trade_exact_out(
+                POOLS.clone(),
+                &TOKEN0.clone(),
+                &TOKEN2_AMOUNT_10000.clone(),
+                1,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.swaps.len(), 1);
+            assert_eq!(
+                trade.input_amount().unwrap().quotient(),
+                best_single_route_input().await.quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn splits_large_amounts_across_disjoint_routes() {
+            let trade = Trade::best_split_trade_exact_out(
+                POOLS.clone(),
+                &TOKEN0.clone(),
+                &TOKEN2_AMOUNT_10000.clone(),
+                10,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.trade_type, TradeType::ExactOutput);
+            assert!(trade.swaps.len() > 1);
+            assert_eq!(
+                trade.output_amount().unwrap().quotient(),
+                TOKEN2_AMOUNT_10000.quotient()
+            );
+            assert!(
+                trade.input_amount().unwrap().quotient()
+                    < best_single_route_input().await.quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn respects_max_distinct_pools() {
+            for max_distinct_pools in 1..=4 {
+                let trade = Trade::best_split_trade_exact_out(
+                    POOLS.clone(),
+                    &TOKEN0.clone(),
+                    &TOKEN2_AMOUNT_10000.clone(),
+                    10,
+                    BestTradeOptions {
+                        max_distinct_pools: Some(max_distinct_pools),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+                let distinct_pools: usize =
+                    trade.swaps.iter().map(|swap| swap.route.pools.len()).sum();
+                assert!(distinct_pools <= max_distinct_pools);
+                assert_eq!(
+                    trade.output_amount().unwrap().quotient(),
+                    TOKEN2_AMOUNT_10000.quotient()
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn skips_pools_too_shallow_for_a_slice() {
+            let shallow_pool = v2_style_pool(
+                currency_amount!(Currency::from(TOKEN0.clone()), 100),
+                currency_amount!(Currency::from(TOKEN2.clone()), 100),
+                Some(FeeAmount::LOW),
+            );
+            let trade = Trade::best_split_trade_exact_out(
+                vec![shallow_pool.clone(), POOL_0_2.clone()],
+                &TOKEN0.clone(),
+                &TOKEN2_AMOUNT_10000.clone(),
+                10,
+                BestTradeOptions::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trade.swaps.len(), 1);
+            assert_eq!(trade.swaps[0].route.pools, vec![POOL_0_2.clone()]);
+            assert_eq!(
+                trade.output_amount().unwrap().quotient(),
+                TOKEN2_AMOUNT_10000.quotient()
+            );
+        }
+
+        #[tokio::test]
+        async fn throws_if_no_pool_can_output_a_slice() {
+            let shallow_pool = v2_style_pool(
+                currency_amount!(Currency::from(TOKEN0.clone()), 100),
+                currency_amount!(Currency::from(TOKEN2.clone()), 100),
+                Some(FeeAmount::LOW),
+            );
+            let result = Trade::best_split_trade_exact_out(
+                vec![shallow_pool],
+                &TOKEN0.clone(),
+                &TOKEN2_AMOUNT_10000.clone(),
+                10,
+                BestTradeOptions::default(),
+            )
+            .await;
+            assert!(matches!(result, Err(Error::InsufficientLiquidity)));
+        }
+    }
     mod maximum_amount_in {
         use super::*;
 
@@ -2433,4 +5041,81 @@ mod tests {
             assert_eq!(result[1].output_currency().clone(), ETHER.clone());
         }
     }
+
+    mod to_log_record {
+        use super::*;
+
+        #[tokio::test]
+        async fn is_constructed_from_an_exact_in_trade() {
+            let trade = trade_from_route!(
+                ROUTE_0_1_ETH.clone(),
+                TOKEN0_AMOUNT_100.clone(),
+                TradeType::ExactInput
+            );
+            let record = trade.to_log_record().unwrap();
+            assert_eq!(record.input_currency, TOKEN0.address);
+            assert_eq!(record.output_currency, Address::ZERO);
+            assert_eq!(record.input_amount, "100");
+            assert_eq!(
+                record.output_amount,
+                trade.output_amount().unwrap().quotient().to_string()
+            );
+            assert_eq!(record.trade_type, TradeType::ExactInput);
+            assert_eq!(
+                record.pool_ids,
+                vec![vec![POOL_0_1.pool_id, POOL_ETH_1.pool_id]]
+            );
+        }
+    }
+
+    #[cfg(feature = "extensions")]
+    mod validate_freshness {
+        use super::*;
+        use alloy_primitives::U160;
+
+        fn eth_usdc_trade(sqrt_price_x96: U160) -> Trade<Ether, Token, NoTickDataProvider> {
+            let pool = Pool::new(
+                ETHER.clone().into(),
+                USDC.clone().into(),
+                FeeAmount::LOW.into(),
+                10,
+                Address::ZERO,
+                sqrt_price_x96,
+                ONE_ETHER,
+            )
+            .unwrap();
+            Trade::create_unchecked_trade(
+                create_route!(pool, ETHER, USDC),
+                currency_amount!(ETHER, ONE_ETHER),
+                currency_amount!(USDC, 1_000_000),
+                TradeType::ExactInput,
+            )
+            .unwrap()
+        }
+
+        #[tokio::test]
+        async fn passes_for_pool_at_current_price() {
+            let (sqrt_price_x96, ..) = POOL_MANAGER
+                .get_slot0(*POOL_ID_ETH_USDC, BLOCK_ID)
+                .await
+                .unwrap();
+            let trade = eth_usdc_trade(sqrt_price_x96);
+            trade
+                .validate_freshness(&POOL_MANAGER, &Percent::new(0, 100), BLOCK_ID)
+                .await
+                .unwrap();
+        }
+
+        #[tokio::test]
+        async fn throws_for_stale_pool_price() {
+            let trade = eth_usdc_trade(*SQRT_PRICE_1_1);
+            let result = trade
+                .validate_freshness(&POOL_MANAGER, &Percent::new(1, 100), BLOCK_ID)
+                .await;
+            assert!(matches!(
+                result,
+                Err(Error::StalePrice(pool_id)) if pool_id == *POOL_ID_ETH_USDC
+            ));
+        }
+    }
 }
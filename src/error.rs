@@ -3,6 +3,11 @@
 #[cfg(doc)]
 use crate::prelude::*;
 
+use crate::utils::HookPermissions;
+use alloy_primitives::{
+    aliases::{I24, U24},
+    Address, ChainId, B256,
+};
 use alloy_sol_types::Error as SolError;
 use uniswap_sdk_core::error::Error as CoreError;
 use uniswap_v3_sdk::error::Error as V3Error;
@@ -22,27 +27,181 @@ pub enum Error {
     #[error("{0}")]
     Sol(#[from] SolError),
 
+    /// Thrown when a pool's tick spacing is not within
+    /// [`MIN_TICK_SPACING`]..=[`MAX_TICK_SPACING`].
+    #[error("Invalid tick spacing")]
+    InvalidTickSpacing,
+
+    /// Thrown when the lower tick of a position is not below its upper tick.
+    #[error("Invalid tick order")]
+    InvalidTickOrder,
+
+    /// Thrown when a tick of a position is outside `MIN_TICK..=MAX_TICK` or not a multiple
+    /// of the pool's tick spacing.
+    #[error("Tick {0} is not a usable tick of the pool")]
+    TickNotOnSpacing(I24),
+
+    /// Thrown when a decimal string passed to [`Pool::new_from_strings`] can't be parsed into
+    /// the named field.
+    #[error("Failed to parse {0}")]
+    ParseError(&'static str),
+
+    /// Thrown when ticks can't form a tick list because they are unsorted, off the tick spacing,
+    /// or their net liquidity doesn't sum to zero, e.g. when a snapshotted tick range cuts through
+    /// positions.
+    #[error("Invalid tick list")]
+    InvalidTickList,
+
     /// Thrown when the action is not supported.
     #[error("Unsupported action {0}")]
     InvalidAction(u8),
 
+    /// Thrown when the data of an action has trailing bytes that its params don't consume.
+    #[error("Trailing data for action {0}")]
+    TrailingActionData(u8),
+
+    /// Thrown when encoded planner data doesn't have exactly one params entry per action.
+    #[error("Encoded {actions} actions with {params} params")]
+    ActionsLengthMismatch { actions: usize, params: usize },
+
+    /// Thrown when the `modifyLiquidities` calldata of a [`V4PositionPlanner`] exceeds the byte
+    /// limit passed to [`V4PositionPlanner::warn_if_exceeds`].
+    #[error("Calldata of {size} bytes exceeds limit of {limit} bytes")]
+    CalldataTooLarge { size: usize, limit: usize },
+
+    /// Thrown when migrating a V3 position with [`AddLiquidityOptions`] that don't mint a new
+    /// position.
+    #[error("Migration must mint a new position")]
+    MigrateRequiresMint,
+
+    /// Thrown when an amount doesn't fit in the `uint128` field of the router params it is
+    /// encoded into.
+    #[error("Amount overflows uint128 field {0}")]
+    AmountOverflow(&'static str),
+
+    /// Thrown when constructing a [`Route`] with hook data that doesn't have exactly one entry per
+    /// pool.
+    #[error("Route of {pools} pools with {hook_data} hook data")]
+    HookDataLengthMismatch { pools: usize, hook_data: usize },
+
+    /// Thrown when overriding the fees of a [`Route`] without exactly one fee per pool.
+    #[error("Route of {pools} pools with {fees} fee overrides")]
+    FeeOverridesLengthMismatch { pools: usize, fees: usize },
+
+    /// Thrown when a trade with several routes is used where a single route is required.
+    #[error("Multiple routes")]
+    MultipleRoutes,
+
+    /// Thrown when a trade of the wrong trade type is encoded, e.g. an exact input trade with
+    /// [`V4Planner::encode_exact_out_swap`].
+    #[error("Invalid trade type")]
+    InvalidTradeType,
+
+    /// Thrown when constructing a [`Route`] without pools.
+    #[error("Empty pools")]
+    EmptyPools,
+
+    /// Thrown when the pools of a [`Route`] are not all on the same chain.
+    #[error("Mismatched chains")]
+    MismatchedChains,
+
+    /// Thrown when the pools of a [`Route`] don't form a path from its input to its output
+    /// currency.
+    #[error("Disconnected path")]
+    DisconnectedPath,
+
     /// Thrown when the currency passed to [`get_path_currency`] is not one of the pool's
     /// currencies.
     #[error("Invalid currency")]
     InvalidCurrency,
 
-    /// Thrown when trying to simulate a swap with an unsupported hook.
-    #[error("Unsupported hook")]
-    UnsupportedHook,
+    /// Thrown when trying to simulate a swap with an unsupported hook, carrying the hook's
+    /// address and the permissions it was deployed with.
+    #[error("Unsupported hook {address} with permissions {permissions:?}")]
+    UnsupportedHook {
+        address: Address,
+        permissions: HookPermissions,
+    },
+
+    /// Thrown when no salt giving a hook address with the requested permissions is found within
+    /// the iteration cap of [`mine_hook_address`].
+    #[error("Hook address not found")]
+    HookAddressNotFound,
 
     #[error("Insufficient liquidity")]
     InsufficientLiquidity,
 
+    /// Thrown when a route passed to [`Trade::from_route`] contains a pool without liquidity.
+    #[error("Pool {pool_id} has no liquidity")]
+    EmptyPool { pool_id: B256 },
+
+    /// Thrown when the LP fee of a swap is not below 100%, e.g. when the fee of a dynamic fee
+    /// pool is unresolved.
+    #[error("Invalid fee {0}")]
+    InvalidFee(U24),
+
+    /// Thrown when converting amounts with a zero price.
+    #[error("Zero price")]
+    ZeroPrice,
+
+    /// Thrown when the protocol fee of either swap direction exceeds 1000 pips.
+    #[error("Invalid protocol fee {0}")]
+    InvalidProtocolFee(U24),
+
+    /// Thrown when a pool's on-chain price has diverged from the price a trade was computed
+    /// against by more than the allowed tolerance.
+    #[error("Stale price for pool {0}")]
+    StalePrice(B256),
+
+    /// Thrown when the price impact of a trade built by [`Trade::from_route_with_max_impact`]
+    /// exceeds the cap.
+    #[error("Excessive price impact")]
+    ExcessivePriceImpact,
+
+    /// Thrown when a pool read from the pool manager has not been initialized, i.e. its
+    /// `sqrtPriceX96` is zero.
+    #[error("Pool {0} is not initialized")]
+    PoolNotInitialized(B256),
+
+    /// Thrown when a chain has no known deployment of the contract being looked up.
+    #[error("Unsupported chain {0}")]
+    UnsupportedChain(ChainId),
+
     #[cfg(feature = "extensions")]
     #[error("{0}")]
     ContractError(#[from] alloy::contract::Error),
 }
 
+impl Error {
+    /// Returns the wrapped [`CoreError`], including one wrapped by a v3 library error.
+    pub const fn core_error(&self) -> Option<&CoreError> {
+        match self {
+            Self::Core(e) | Self::V3(V3Error::Core(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Whether the error was thrown because the chain IDs of currencies don't match.
+    pub const fn is_chain_id_mismatch(&self) -> bool {
+        matches!(self.core_error(), Some(CoreError::ChainIdMismatch(..)))
+    }
+
+    /// Whether the error was thrown because two currencies have the same address.
+    pub const fn is_equal_addresses(&self) -> bool {
+        matches!(self.core_error(), Some(CoreError::EqualAddresses))
+    }
+
+    /// Whether the error was thrown because a pool doesn't have enough liquidity for a swap.
+    pub const fn is_insufficient_liquidity(&self) -> bool {
+        matches!(
+            self,
+            Self::InsufficientLiquidity
+                | Self::EmptyPool { .. }
+                | Self::V3(V3Error::InsufficientLiquidity)
+        )
+    }
+}
+
 #[cfg(feature = "extensions")]
 pub fn map_contract_error(e: Error) -> V3Error {
     match e {
@@ -50,3 +209,73 @@ pub fn map_contract_error(e: Error) -> V3Error {
         _ => panic!("Unexpected error: {e:?}"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CoreError, Error, V3Error};
+    use crate::{prelude::*, tests::*};
+    use alloy_primitives::{Address, B256};
+    use uniswap_sdk_core::prelude::*;
+    use uniswap_v3_sdk::prelude::FeeAmount;
+
+    #[test]
+    fn is_chain_id_mismatch() {
+        assert!(Error::Core(CoreError::ChainIdMismatch(1, 3)).is_chain_id_mismatch());
+        assert!(Error::V3(V3Error::Core(CoreError::ChainIdMismatch(1, 3))).is_chain_id_mismatch());
+        assert!(!Error::Core(CoreError::EqualAddresses).is_chain_id_mismatch());
+        let error = Pool::new(
+            Currency::Token(USDC.clone()),
+            Currency::Token(WETH9::on_chain(3).unwrap()),
+            FeeAmount::MEDIUM.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            0,
+        )
+        .unwrap_err();
+        assert!(error.is_chain_id_mismatch());
+    }
+
+    #[test]
+    fn is_equal_addresses() {
+        assert!(Error::Core(CoreError::EqualAddresses).is_equal_addresses());
+        assert!(Error::V3(V3Error::Core(CoreError::EqualAddresses)).is_equal_addresses());
+        assert!(!Error::Core(CoreError::ChainIdMismatch(1, 3)).is_equal_addresses());
+        let error = Pool::new(
+            Currency::Token(USDC.clone()),
+            Currency::Token(USDC.clone()),
+            FeeAmount::MEDIUM.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            0,
+        )
+        .unwrap_err();
+        assert!(error.is_equal_addresses());
+    }
+
+    #[test]
+    fn is_insufficient_liquidity() {
+        assert!(Error::InsufficientLiquidity.is_insufficient_liquidity());
+        assert!(Error::V3(V3Error::InsufficientLiquidity).is_insufficient_liquidity());
+        assert!(Error::EmptyPool {
+            pool_id: B256::ZERO
+        }
+        .is_insufficient_liquidity());
+        assert!(!Error::InvalidCurrency.is_insufficient_liquidity());
+    }
+
+    #[test]
+    fn core_error() {
+        assert!(matches!(
+            Error::Core(CoreError::EqualAddresses).core_error(),
+            Some(CoreError::EqualAddresses)
+        ));
+        assert!(Error::UnsupportedHook {
+            address: Address::ZERO,
+            permissions: HookPermissions::default(),
+        }
+        .core_error()
+        .is_none());
+    }
+}
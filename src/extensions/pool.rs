@@ -0,0 +1,291 @@
+//! ## Pool
+//! Constructs a [`Pool`] from the state of the pool manager read with [`PoolManagerLens`].
+//! Quotes swaps through a [`Pool`]'s real hook with the V4 `Quoter` contract.
+
+use crate::prelude::{
+    pool_id_from_pool_key, Error, IERC20Metadata, IV4Quoter, Pool, PoolKey, PoolManagerLens,
+    QuoteExactSingleParams, SimpleTickDataProvider,
+};
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    providers::{DynProvider, Provider},
+    rpc::types::TransactionRequest,
+};
+use alloy_primitives::{Address, Bytes, ChainId, U256};
+use alloy_sol_types::SolCall;
+use uniswap_sdk_core::prelude::*;
+use uniswap_v3_sdk::prelude::*;
+
+impl<I: TickIndex> Pool<SimpleTickDataProvider<I>> {
+    /// Reads the state of the pool with the given key with [`PoolManagerLens::load_pool_from_key`]
+    /// and constructs a [`Pool`] whose tick data is fetched on the fly with the same lens
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_key`: The key of the pool
+    /// * `lens`: The lens for querying the pool manager
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The pool, or [`Error::PoolNotInitialized`] if the pool has not been initialized
+    #[inline]
+    pub async fn from_pool_key(
+        pool_key: PoolKey,
+        lens: PoolManagerLens,
+        block_id: Option<BlockId>,
+    ) -> Result<Self, Error> {
+        let provider = lens.manager.provider().clone();
+        let chain_id = provider
+            .get_chain_id()
+            .await
+            .map_err(alloy::contract::Error::TransportError)?;
+        let currency0 = currency_at(pool_key.currency0, chain_id, &provider, block_id).await?;
+        let currency1 = currency_at(pool_key.currency1, chain_id, &provider, block_id).await?;
+        let state = lens
+            .load_pool_from_key(&pool_key, currency0, currency1, block_id)
+            .await?;
+
+        let pool_id = pool_id_from_pool_key(&pool_key);
+        let mut pool = Self::new_with_tick_data_provider(
+            state.currency0,
+            state.currency1,
+            pool_key.fee,
+            I::from_i24(pool_key.tickSpacing),
+            pool_key.hooks,
+            state.sqrt_price_x96,
+            state.liquidity,
+            SimpleTickDataProvider::new(*lens.manager.address(), pool_id, provider, block_id),
+        )?;
+        pool.tick_current = I::from_i24(state.tick_current.to_i24());
+        pool.protocol_fee = state.protocol_fee;
+        Ok(pool)
+    }
+}
+
+impl<TP: TickDataProvider> Pool<TP> {
+    /// Given an input amount of a currency, return the output amount quoted by the V4 `Quoter`
+    /// contract and its gas estimate
+    ///
+    /// Unlike [`Pool::get_output_amount`], the quote runs the pool's real hook and fee on-chain, so
+    /// it is correct for any hook at the cost of an RPC call, and doesn't depend on the pool's
+    /// local state or tick data.
+    ///
+    /// ## Note
+    ///
+    /// The deployed `Quoter` reverts the simulated swap and doesn't return the pool state after
+    /// it, so no updated pool is returned. Read the pool again after executing the swap, e.g. with
+    /// [`Pool::from_pool_key`], before quoting a following hop.
+    ///
+    /// ## Arguments
+    ///
+    /// * `input_amount`: The input amount for which to quote the output amount
+    /// * `hook_data`: The data passed to the pool's hook
+    /// * `quoter`: The address of the V4 `Quoter` contract
+    /// * `provider`: The provider for the chain of the pool
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The output amount and the gas estimate of the swap
+    #[inline]
+    pub async fn get_output_amount_onchain(
+        &self,
+        input_amount: &CurrencyAmount<impl BaseCurrency>,
+        hook_data: Bytes,
+        quoter: Address,
+        provider: DynProvider,
+        block_id: Option<BlockId>,
+    ) -> Result<(CurrencyAmount<Currency>, U256), Error> {
+        if !self.involves_currency(&input_amount.currency) {
+            return Err(Error::InvalidCurrency);
+        }
+        let zero_for_one = input_amount.currency.equals(&self.currency0);
+        let exact_amount = U256::from_big_int(input_amount.quotient());
+        let calldata = IV4Quoter::quoteExactInputSingleCall {
+            params: QuoteExactSingleParams {
+                poolKey: self.pool_key.clone(),
+                zeroForOne: zero_for_one,
+                exactAmount: u128::try_from(exact_amount)
+                    .map_err(|_| Error::AmountOverflow("exactAmount"))?,
+                hookData: hook_data,
+            },
+        }
+        .abi_encode();
+        let returns = provider
+            .call(
+                TransactionRequest::default()
+                    .to(quoter)
+                    .input(Bytes::from(calldata).into()),
+            )
+            .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+            .await
+            .map_err(alloy::contract::Error::TransportError)?;
+        let IV4Quoter::quoteExactInputSingleReturn {
+            amountOut: amount_out,
+            gasEstimate: gas_estimate,
+        } = IV4Quoter::quoteExactInputSingleCall::abi_decode_returns(&returns)?;
+        let output_currency = if zero_for_one {
+            self.currency1.clone()
+        } else {
+            self.currency0.clone()
+        };
+        Ok((
+            CurrencyAmount::from_raw_amount(output_currency, amount_out.to_big_int())?,
+            gas_estimate,
+        ))
+    }
+}
+
+/// Returns the native currency for the zero address, otherwise the token with the decimals read
+/// from its contract
+async fn currency_at(
+    address: Address,
+    chain_id: ChainId,
+    provider: &DynProvider,
+    block_id: Option<BlockId>,
+) -> Result<Currency, Error> {
+    if address.is_zero() {
+        return Ok(Ether::on_chain(chain_id).into());
+    }
+    let decimals = IERC20Metadata::new(address, provider.clone())
+        .decimals()
+        .block(block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest)))
+        .call()
+        .await?;
+    Ok(Token::new(chain_id, address, decimals, None, None, 0, 0).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::DYANMIC_FEE_FLAG, tests::*};
+    use alloy_primitives::{address, U160};
+
+    #[tokio::test]
+    async fn test_from_pool_key() {
+        let pool_key = Pool::get_pool_key(
+            &ETHER.clone().into(),
+            &USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            10,
+            Address::ZERO,
+        )
+        .unwrap();
+        let pool = Pool::<SimpleTickDataProvider>::from_pool_key(
+            pool_key.clone(),
+            POOL_MANAGER.clone(),
+            BLOCK_ID,
+        )
+        .await
+        .unwrap();
+        let (sqrt_price_x96, tick, ..) = POOL_MANAGER
+            .get_slot0(*POOL_ID_ETH_USDC, BLOCK_ID)
+            .await
+            .unwrap();
+        let liquidity = POOL_MANAGER
+            .get_liquidity(*POOL_ID_ETH_USDC, BLOCK_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(pool.pool_id, *POOL_ID_ETH_USDC);
+        assert_eq!(pool.pool_key, pool_key);
+        assert!(pool.currency0.is_native());
+        assert!(pool.currency1.equals(&USDC.clone()));
+        assert_eq!(pool.currency1.decimals(), 6);
+        assert_eq!(pool.sqrt_price_x96, sqrt_price_x96);
+        assert_eq!(pool.tick_current, tick);
+        assert_eq!(pool.liquidity, liquidity);
+        assert_eq!(pool.tick_data_provider.pool_id, *POOL_ID_ETH_USDC);
+        assert_ne!(pool.sqrt_price_x96, U160::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_get_output_amount_onchain() {
+        let pool_key = Pool::get_pool_key(
+            &ETHER.clone().into(),
+            &USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            10,
+            Address::ZERO,
+        )
+        .unwrap();
+        let pool =
+            Pool::<SimpleTickDataProvider>::from_pool_key(pool_key, POOL_MANAGER.clone(), BLOCK_ID)
+                .await
+                .unwrap();
+        let input_amount = CurrencyAmount::from_raw_amount(ETHER.clone(), ONE_ETHER).unwrap();
+        let (output_amount, gas_estimate) = pool
+            .get_output_amount_onchain(
+                &input_amount,
+                Bytes::new(),
+                *V4_QUOTER,
+                PROVIDER.clone(),
+                BLOCK_ID,
+            )
+            .await
+            .unwrap();
+        let (expected_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+        assert!(output_amount.currency.equals(&USDC.clone()));
+        assert_eq!(output_amount.quotient(), expected_amount.quotient());
+        assert!(gas_estimate > U256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_get_output_amount_onchain_without_local_simulation() {
+        let pool_key = Pool::get_pool_key(
+            &ETHER.clone().into(),
+            &USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            10,
+            Address::ZERO,
+        )
+        .unwrap();
+        let pool =
+            Pool::<SimpleTickDataProvider>::from_pool_key(pool_key, POOL_MANAGER.clone(), BLOCK_ID)
+                .await
+                .unwrap();
+        let input_amount = CurrencyAmount::from_raw_amount(ETHER.clone(), ONE_ETHER).unwrap();
+        let (expected_amount, _) = pool.get_output_amount(&input_amount, None).await.unwrap();
+
+        // a dynamic fee pool with a swap hook and no liquidity at its current price can't be
+        // simulated locally, but the quote only depends on the on-chain pool of the key
+        let mut unsimulatable = pool.clone();
+        unsimulatable.fee = DYANMIC_FEE_FLAG;
+        unsimulatable.hooks = address!("0000000000000000000000000000000000000080");
+        unsimulatable.liquidity = 0;
+        assert!(unsimulatable
+            .get_output_amount(&input_amount, None)
+            .await
+            .is_err());
+
+        let (output_amount, _) = unsimulatable
+            .get_output_amount_onchain(
+                &input_amount,
+                Bytes::new(),
+                *V4_QUOTER,
+                PROVIDER.clone(),
+                BLOCK_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(output_amount.quotient(), expected_amount.quotient());
+    }
+
+    #[tokio::test]
+    async fn test_from_pool_key_not_initialized() {
+        let pool_key = Pool::get_pool_key(
+            &ETHER.clone().into(),
+            &USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            7,
+            Address::ZERO,
+        )
+        .unwrap();
+        let pool_id = pool_id_from_pool_key(&pool_key);
+        let result =
+            Pool::<SimpleTickDataProvider>::from_pool_key(pool_key, POOL_MANAGER.clone(), BLOCK_ID)
+                .await;
+        assert!(matches!(result, Err(Error::PoolNotInitialized(id)) if id == pool_id));
+    }
+}
@@ -4,17 +4,25 @@
 //! does the slot calculation and ABI decoding in Rust instead of Solidity. It does not require
 //! contract deployment and uses `extsload` to read the state under the hood.
 
-use crate::prelude::{Error, IExtsload};
+use crate::prelude::{
+    calculate_position_key, pool_id_from_pool_key, Error, IExtsload, IPoolManager, Pool, PoolKey,
+};
+use alloc::vec::Vec;
 use alloy::{
     eips::{BlockId, BlockNumberOrTag},
-    providers::DynProvider,
+    providers::{DynProvider, Provider},
+    rpc::types::Filter,
     uint,
 };
 use alloy_primitives::{
     aliases::{I24, U24},
-    keccak256, Address, B256, U160, U256,
+    keccak256,
+    map::HashSet,
+    Address, ChainId, B256, U160, U256,
 };
-use alloy_sol_types::SolValue;
+use alloy_sol_types::{SolEvent, SolValue};
+use core::ops::RangeInclusive;
+use uniswap_sdk_core::{addresses::CHAIN_TO_ADDRESSES_MAP, prelude::Currency};
 use uniswap_v3_sdk::prelude::*;
 
 const POOLS_SLOT: U256 = uint!(6_U256);
@@ -23,6 +31,9 @@ const LIQUIDITY_OFFSET: U256 = uint!(3_U256);
 const TICKS_OFFSET: U256 = uint!(4_U256);
 const TICK_BITMAP_OFFSET: U256 = uint!(5_U256);
 const POSITIONS_OFFSET: U256 = uint!(6_U256);
+/// The maximum number of slots read in a single `extsload` call, to stay within node calldata
+/// limits
+const MAX_SLOTS_PER_CALL: usize = 1020;
 
 fn get_pool_state_slot(pool_id: B256) -> U256 {
     U256::from_be_bytes(keccak256((pool_id, POOLS_SLOT).abi_encode()).0)
@@ -61,6 +72,21 @@ impl PoolManagerLens {
         }
     }
 
+    /// Creates a new `PoolManagerLens` for the pool manager deployed on the given chain
+    ///
+    /// ## Arguments
+    ///
+    /// * `chain_id`: The chain of the pool manager
+    /// * `provider`: The provider for the chain
+    #[inline]
+    pub fn on_chain(chain_id: ChainId, provider: DynProvider) -> Result<Self, Error> {
+        let manager = CHAIN_TO_ADDRESSES_MAP
+            .get(&chain_id)
+            .and_then(|addresses| addresses.v4_pool_manager)
+            .ok_or(Error::UnsupportedChain(chain_id))?;
+        Ok(Self::new(manager, provider))
+    }
+
     /// Retrieves the Slot0 of a pool: sqrtPriceX96, tick, protocolFee, lpFee
     ///
     /// ## Arguments
@@ -88,20 +114,43 @@ impl PoolManagerLens {
             .block(block_id)
             .call()
             .await?;
+        Ok(decode_slot0(data))
+    }
 
-        let sqrt_price_x96 = U160::from_be_slice(&data[12..32]);
-
-        let tick_bytes = unsafe { (data.as_ptr().add(9) as *const [u8; 3]).read_unaligned() };
-        let tick = I24::from_be_bytes(tick_bytes);
-
-        let protocol_fee_bytes =
-            unsafe { (data.as_ptr().add(6) as *const [u8; 3]).read_unaligned() };
-        let protocol_fee = U24::from_be_bytes(protocol_fee_bytes);
-
-        let lp_fee_bytes = unsafe { (data.as_ptr().add(3) as *const [u8; 3]).read_unaligned() };
-        let lp_fee = U24::from_be_bytes(lp_fee_bytes);
-
-        Ok((sqrt_price_x96, tick, protocol_fee, lp_fee))
+    /// Retrieves the Slot0 of multiple pools, reading all their state slots with
+    /// `extsload(bytes32[])` in as few calls as possible
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_ids`: The IDs of the pools
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The `(sqrtPriceX96, tick, protocol_fee, lp_fee)` of each pool, in the same order as
+    /// `pool_ids`. See [`Self::get_slot0`].
+    #[inline]
+    pub async fn get_slot0_batch(
+        &self,
+        pool_ids: &[B256],
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<(U160, I24, U24, U24)>, Error> {
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+        let mut slot0s = Vec::with_capacity(pool_ids.len());
+        for chunk in pool_ids.chunks(MAX_SLOTS_PER_CALL) {
+            let slots = chunk
+                .iter()
+                .map(|&pool_id| B256::from(get_pool_state_slot(pool_id)))
+                .collect();
+            let data = self
+                .manager
+                .extsload_2(slots)
+                .block(block_id)
+                .call()
+                .await?;
+            slot0s.extend(data.into_iter().map(decode_slot0));
+        }
+        Ok(slot0s)
     }
 
     /// Retrieves full tick information from a pool at a specific tick
@@ -149,6 +198,55 @@ impl PoolManagerLens {
         ))
     }
 
+    /// Retrieves full tick information from a pool at multiple ticks, reading all their slots with
+    /// `extsload(bytes32[])` in as few calls as possible
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_id`: The ID of the pool
+    /// * `ticks`: The ticks to retrieve information for
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The `(liquidity_gross, liquidity_net, fee_growth_outside0_x128, fee_growth_outside1_x128)`
+    /// of each tick, in the same order as `ticks`. See [`Self::get_tick_info`].
+    #[inline]
+    pub async fn get_ticks_info<I: TickIndex>(
+        &self,
+        pool_id: B256,
+        ticks: &[I],
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<(u128, i128, U256, U256)>, Error> {
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+        let mut ticks_info = Vec::with_capacity(ticks.len());
+        for chunk in ticks.chunks(MAX_SLOTS_PER_CALL / 3) {
+            let slots = chunk
+                .iter()
+                .flat_map(|&tick| {
+                    let slot = get_tick_info_slot(pool_id, tick);
+                    [slot, slot + uint!(1_U256), slot + uint!(2_U256)].map(B256::from)
+                })
+                .collect();
+            let data = self
+                .manager
+                .extsload_2(slots)
+                .block(block_id)
+                .call()
+                .await?;
+            ticks_info.extend(data.chunks_exact(3).map(|words| {
+                let (liquidity_gross, liquidity_net) = decode_liquidity_gross_and_net(words[0]);
+                (
+                    liquidity_gross,
+                    liquidity_net,
+                    U256::from_be_bytes(words[1].0),
+                    U256::from_be_bytes(words[2].0),
+                )
+            }));
+        }
+        Ok(ticks_info)
+    }
+
     /// Retrieves the liquidity information of a pool at a specific tick
     ///
     /// ## Arguments
@@ -249,6 +347,31 @@ impl PoolManagerLens {
         Ok((fee_growth_global0, fee_growth_global1))
     }
 
+    /// Retrieves the LP fee of a pool, i.e. the `lpFee` field of its Slot0
+    ///
+    /// ## Note
+    ///
+    /// For dynamic fee pools, this is the fee last set by the hook via `updateDynamicLPFee`, which
+    /// the hook may still override per swap.
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_id`: The ID of the pool
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// * `lp_fee`: The swap fee of the pool
+    #[inline]
+    pub async fn get_current_lp_fee(
+        &self,
+        pool_id: B256,
+        block_id: Option<BlockId>,
+    ) -> Result<U24, Error> {
+        let (.., lp_fee) = self.get_slot0(pool_id, block_id).await?;
+        Ok(lp_fee)
+    }
+
     /// Retrieves the total liquidity of a pool
     ///
     /// ## Arguments
@@ -271,6 +394,51 @@ impl PoolManagerLens {
         Ok(decode_liquidity(value))
     }
 
+    /// Reads the slot0 and liquidity of the pool with the given key and constructs a [`Pool`]
+    /// without tick data
+    ///
+    /// ## Arguments
+    ///
+    /// * `key`: The key of the pool
+    /// * `currency0`: The currency at `key.currency0`
+    /// * `currency1`: The currency at `key.currency1`
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The pool, [`Error::InvalidCurrency`] if the currencies don't match the key, or
+    /// [`Error::PoolNotInitialized`] if the pool has not been initialized
+    #[inline]
+    pub async fn load_pool_from_key(
+        &self,
+        key: &PoolKey,
+        currency0: Currency,
+        currency1: Currency,
+        block_id: Option<BlockId>,
+    ) -> Result<Pool, Error> {
+        let pool_id = pool_id_from_pool_key(key);
+        let (sqrt_price_x96, tick, protocol_fee, _) = self.get_slot0(pool_id, block_id).await?;
+        if sqrt_price_x96.is_zero() {
+            return Err(Error::PoolNotInitialized(pool_id));
+        }
+        let liquidity = self.get_liquidity(pool_id, block_id).await?;
+        let mut pool = Pool::new(
+            currency0,
+            currency1,
+            key.fee,
+            key.tickSpacing.as_i32(),
+            key.hooks,
+            sqrt_price_x96,
+            liquidity,
+        )?;
+        if pool.pool_id != pool_id {
+            return Err(Error::InvalidCurrency);
+        }
+        pool.tick_current = tick.as_i32();
+        pool.protocol_fee = protocol_fee;
+        Ok(pool)
+    }
+
     /// Retrieves the tick bitmap of a pool at a specific tick
     ///
     /// ## Arguments
@@ -296,6 +464,117 @@ impl PoolManagerLens {
         Ok(U256::from_be_bytes(word.0))
     }
 
+    /// Enumerates the initialized ticks of a pool within a tick range by reading the words of its
+    /// tick bitmap in batched `extsload(bytes32[])` calls, without probing every usable tick
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_id`: The ID of the pool
+    /// * `tick_range`: The inclusive range of ticks to scan
+    /// * `tick_spacing`: The tick spacing of the pool
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The initialized ticks in ascending order, or [`Error::InvalidTickSpacing`] if the tick
+    /// spacing isn't positive
+    #[inline]
+    pub async fn get_initialized_ticks<I: TickIndex>(
+        &self,
+        pool_id: B256,
+        tick_range: RangeInclusive<I>,
+        tick_spacing: I,
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<I>, Error> {
+        let tick_spacing = tick_spacing.to_i24().as_i32();
+        if tick_spacing <= 0 {
+            return Err(Error::InvalidTickSpacing);
+        }
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+        let tick_lower = tick_range.start().to_i24().as_i32();
+        let tick_upper = tick_range.end().to_i24().as_i32();
+        // compressed ticks round towards negative infinity, so both the division and the shift
+        // must floor for negative ticks
+        let word_lower = tick_lower.div_euclid(tick_spacing) >> 8;
+        let word_upper = tick_upper.div_euclid(tick_spacing) >> 8;
+        let words: Vec<i32> = (word_lower..=word_upper).collect();
+        let mut ticks = Vec::new();
+        for chunk in words.chunks(MAX_SLOTS_PER_CALL) {
+            let slots = chunk
+                .iter()
+                .map(|&word| B256::from(get_tick_bitmap_slot(pool_id, word)))
+                .collect();
+            let bitmaps = self
+                .manager
+                .extsload_2(slots)
+                .block(block_id)
+                .call()
+                .await?;
+            for (&word, bitmap) in chunk.iter().zip(bitmaps) {
+                let mut bitmap = U256::from_be_bytes(bitmap.0);
+                while bitmap != U256::ZERO {
+                    let bit_pos = bitmap.trailing_zeros() as i32;
+                    // clear the lowest set bit
+                    bitmap &= bitmap - U256::from(1);
+                    let tick = ((word << 8) + bit_pos) * tick_spacing;
+                    if tick > tick_upper {
+                        break;
+                    }
+                    if tick >= tick_lower {
+                        ticks.push(I::from_i24(I24::unchecked_from(tick)));
+                    }
+                }
+            }
+        }
+        Ok(ticks)
+    }
+
+    /// Snapshots the initialized ticks of a pool within a tick range into a
+    /// [`TickListDataProvider`], so that swaps can be simulated locally without further RPC calls
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_id`: The ID of the pool
+    /// * `tick_spacing`: The tick spacing of the pool
+    /// * `tick_range`: The inclusive range of ticks to snapshot, usually `MIN_TICK..=MAX_TICK`
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The tick list, or [`Error::InvalidTickList`] if the net liquidity of the ticks in range
+    /// doesn't sum to zero, i.e. if the range cuts through positions
+    #[inline]
+    pub async fn build_tick_list<I: TickIndex>(
+        &self,
+        pool_id: B256,
+        tick_spacing: I,
+        tick_range: RangeInclusive<I>,
+        block_id: Option<BlockId>,
+    ) -> Result<TickListDataProvider<I>, Error> {
+        let indices = self
+            .get_initialized_ticks(pool_id, tick_range, tick_spacing, block_id)
+            .await?;
+        let ticks_info = self.get_ticks_info(pool_id, &indices, block_id).await?;
+        let ticks = indices
+            .into_iter()
+            .zip(ticks_info)
+            .map(|(index, (liquidity_gross, liquidity_net, _, _))| Tick {
+                index,
+                liquidity_gross,
+                liquidity_net,
+            })
+            .collect::<Vec<_>>();
+        // check what `TickListDataProvider::new` asserts
+        let sorted = ticks.windows(2).all(|pair| pair[0].index < pair[1].index);
+        let net_liquidity = ticks
+            .iter()
+            .try_fold(0_i128, |sum, tick| sum.checked_add(tick.liquidity_net));
+        if !sorted || net_liquidity != Some(0) {
+            return Err(Error::InvalidTickList);
+        }
+        Ok(TickListDataProvider::new(ticks, tick_spacing))
+    }
+
     /// Retrieves the position information of a pool at a specific position ID
     ///
     /// ## Arguments
@@ -423,6 +702,71 @@ impl PoolManagerLens {
 
         Ok((fee_growth_inside0_x128, fee_growth_inside1_x128))
     }
+
+    /// Retrieves the IDs of the positions modified in a pool within a block range by scanning the
+    /// `ModifyLiquidity` events emitted by the pool manager
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_id`: The ID of the pool
+    /// * `from_block`: The first block to scan, inclusive
+    /// * `to_block`: The last block to scan, inclusive
+    ///
+    /// ## Returns
+    ///
+    /// The deduplicated position IDs, in the order they were first modified
+    #[inline]
+    pub async fn get_position_ids(
+        &self,
+        pool_id: B256,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<B256>, Error> {
+        let filter = Filter::new()
+            .from_block(from_block)
+            .to_block(to_block)
+            .event_signature(IPoolManager::ModifyLiquidity::SIGNATURE_HASH)
+            .address(*self.manager.address())
+            .topic1(pool_id);
+        let logs = self
+            .manager
+            .provider()
+            .get_logs(&filter)
+            .await
+            .map_err(alloy::contract::Error::TransportError)?;
+
+        let mut seen = HashSet::<B256>::default();
+        let mut position_ids = Vec::with_capacity(logs.len());
+        for log in logs {
+            let IPoolManager::ModifyLiquidity {
+                sender,
+                tickLower,
+                tickUpper,
+                salt,
+                ..
+            } = IPoolManager::ModifyLiquidity::decode_log_data(log.data())?;
+            let position_id = calculate_position_key(sender, tickLower, tickUpper, salt);
+            if seen.insert(position_id) {
+                position_ids.push(position_id);
+            }
+        }
+        Ok(position_ids)
+    }
+}
+
+fn decode_slot0(data: B256) -> (U160, I24, U24, U24) {
+    let sqrt_price_x96 = U160::from_be_slice(&data[12..32]);
+
+    let tick_bytes = unsafe { (data.as_ptr().add(9) as *const [u8; 3]).read_unaligned() };
+    let tick = I24::from_be_bytes(tick_bytes);
+
+    let protocol_fee_bytes = unsafe { (data.as_ptr().add(6) as *const [u8; 3]).read_unaligned() };
+    let protocol_fee = U24::from_be_bytes(protocol_fee_bytes);
+
+    let lp_fee_bytes = unsafe { (data.as_ptr().add(3) as *const [u8; 3]).read_unaligned() };
+    let lp_fee = U24::from_be_bytes(lp_fee_bytes);
+
+    (sqrt_price_x96, tick, protocol_fee, lp_fee)
 }
 
 const fn decode_liquidity_gross_and_net(word: B256) -> (u128, i128) {
@@ -451,23 +795,20 @@ const fn decode_liquidity(word: B256) -> u128 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{prelude::calculate_position_key, tests::*};
-    use alloy::{providers::Provider, rpc::types::Filter};
-    use alloy_sol_types::{sol, SolEvent};
-    use once_cell::sync::Lazy;
-    use uniswap_sdk_core::addresses::CHAIN_TO_ADDRESSES_MAP;
+    use crate::tests::*;
+    use alloy_sol_types::sol;
 
     const TICK_SPACING: i32 = 10;
-    static POOL_MANAGER: Lazy<PoolManagerLens> = Lazy::new(|| {
-        PoolManagerLens::new(
-            CHAIN_TO_ADDRESSES_MAP
-                .get(&1)
-                .unwrap()
-                .v4_pool_manager
-                .unwrap(),
-            PROVIDER.clone(),
-        )
-    });
+
+    #[test]
+    fn test_on_chain() {
+        let lens = PoolManagerLens::on_chain(1, PROVIDER.clone()).unwrap();
+        assert_eq!(lens.manager.address(), POOL_MANAGER.manager.address());
+        assert!(matches!(
+            PoolManagerLens::on_chain(0, PROVIDER.clone()),
+            Err(Error::UnsupportedChain(0))
+        ));
+    }
 
     #[tokio::test]
     async fn test_get_slot0() {
@@ -561,6 +902,125 @@ mod tests {
         assert_tick_info_match!(*POOL_ID_ETH_USDC, tick, BLOCK_ID);
     }
 
+    #[tokio::test]
+    async fn test_load_pool_from_key() {
+        let key = Pool::get_pool_key(
+            &ETHER.clone().into(),
+            &USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            TICK_SPACING,
+            Address::ZERO,
+        )
+        .unwrap();
+        let pool = POOL_MANAGER
+            .load_pool_from_key(&key, ETHER.clone().into(), USDC.clone().into(), BLOCK_ID)
+            .await
+            .unwrap();
+        let (sqrt_price_x96, tick, ..) = POOL_MANAGER
+            .get_slot0(*POOL_ID_ETH_USDC, BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(pool.pool_id, *POOL_ID_ETH_USDC);
+        assert_eq!(pool.sqrt_price_x96, sqrt_price_x96);
+        assert_eq!(pool.tick_current, tick.as_i32());
+        assert_eq!(
+            pool.liquidity,
+            POOL_MANAGER
+                .get_liquidity(*POOL_ID_ETH_USDC, BLOCK_ID)
+                .await
+                .unwrap()
+        );
+
+        let result = POOL_MANAGER
+            .load_pool_from_key(&key, ETHER.clone().into(), DAI.clone().into(), BLOCK_ID)
+            .await;
+        assert!(matches!(result, Err(Error::InvalidCurrency)));
+    }
+
+    #[tokio::test]
+    async fn test_get_slot0_batch() {
+        let pool_ids = [*POOL_ID_ETH_USDC, B256::ZERO, *POOL_ID_ETH_USDC];
+
+        let slot0s = POOL_MANAGER
+            .get_slot0_batch(&pool_ids, BLOCK_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(slot0s.len(), pool_ids.len());
+        for (pool_id, slot0) in pool_ids.into_iter().zip(slot0s) {
+            let expected = POOL_MANAGER.get_slot0(pool_id, BLOCK_ID).await.unwrap();
+            assert_eq!(slot0, expected, "slot0 mismatch for pool {pool_id}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_slot0_batch_chunked() {
+        let pool_ids = vec![*POOL_ID_ETH_USDC; MAX_SLOTS_PER_CALL + 1];
+
+        let slot0s = POOL_MANAGER
+            .get_slot0_batch(&pool_ids, BLOCK_ID)
+            .await
+            .unwrap();
+
+        let expected = POOL_MANAGER
+            .get_slot0(*POOL_ID_ETH_USDC, BLOCK_ID)
+            .await
+            .unwrap();
+        assert_eq!(slot0s.len(), pool_ids.len());
+        assert_eq!(slot0s[0], expected);
+        assert_eq!(slot0s[MAX_SLOTS_PER_CALL], expected);
+    }
+
+    #[tokio::test]
+    async fn test_get_ticks_info() {
+        let slot0 = STATE_VIEW
+            .getSlot0(*POOL_ID_ETH_USDC)
+            .block(BLOCK_ID.unwrap())
+            .call()
+            .await
+            .unwrap();
+        let ticks = [
+            nearest_populated_tick(slot0.tick).await,
+            nearest_usable_tick(MIN_TICK_I32, TICK_SPACING),
+            nearest_usable_tick(MAX_TICK_I32, TICK_SPACING),
+        ];
+
+        let ticks_info = POOL_MANAGER
+            .get_ticks_info(*POOL_ID_ETH_USDC, &ticks, BLOCK_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(ticks_info.len(), ticks.len());
+        for (tick, tick_info) in ticks.into_iter().zip(ticks_info) {
+            let expected = POOL_MANAGER
+                .get_tick_info(*POOL_ID_ETH_USDC, tick, BLOCK_ID)
+                .await
+                .unwrap();
+            assert_eq!(tick_info, expected, "tick info mismatch at tick {tick}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_ticks_info_chunked() {
+        let ticks: Vec<i32> = (0..MAX_SLOTS_PER_CALL as i32)
+            .map(|i| (i - 500) * TICK_SPACING)
+            .collect();
+
+        let ticks_info = POOL_MANAGER
+            .get_ticks_info(*POOL_ID_ETH_USDC, &ticks, BLOCK_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(ticks_info.len(), ticks.len());
+        for &i in &[0, MAX_SLOTS_PER_CALL / 3, ticks.len() - 1] {
+            let expected = POOL_MANAGER
+                .get_tick_info(*POOL_ID_ETH_USDC, ticks[i], BLOCK_ID)
+                .await
+                .unwrap();
+            assert_eq!(ticks_info[i], expected);
+        }
+    }
+
     macro_rules! assert_tick_liquidity_match {
         ($pool_id:expr, $tick:expr, $block_id:expr) => {
             let (liquidity_gross_lens, liquidity_net_lens) = POOL_MANAGER
@@ -672,6 +1132,21 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_get_current_lp_fee() {
+        let lp_fee = POOL_MANAGER
+            .get_current_lp_fee(*POOL_ID_ETH_USDC, BLOCK_ID)
+            .await
+            .unwrap();
+        let (.., lp_fee_slot0) = POOL_MANAGER
+            .get_slot0(*POOL_ID_ETH_USDC, BLOCK_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(lp_fee, lp_fee_slot0);
+        assert_eq!(lp_fee, U24::from(FeeAmount::LOW));
+    }
+
     #[tokio::test]
     async fn test_get_liquidity() {
         let liquidity_lens = POOL_MANAGER
@@ -727,6 +1202,62 @@ mod tests {
         assert_tick_bitmap_match!(*POOL_ID_ETH_USDC, word, BLOCK_ID);
     }
 
+    #[tokio::test]
+    async fn test_get_initialized_ticks() {
+        let ticks = POOL_MANAGER
+            .get_initialized_ticks(*POOL_ID_ETH_USDC, -210000..=-200000, TICK_SPACING, BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(ticks.contains(&-202270));
+        assert!(ticks.contains(&-202300));
+        assert!(ticks.windows(2).all(|w| w[0] < w[1]));
+        assert!(ticks
+            .iter()
+            .all(|tick| (-210000..=-200000).contains(tick) && tick % TICK_SPACING == 0));
+        let ticks_info = POOL_MANAGER
+            .get_ticks_info(*POOL_ID_ETH_USDC, &ticks, BLOCK_ID)
+            .await
+            .unwrap();
+        assert!(ticks_info
+            .iter()
+            .all(|&(liquidity_gross, ..)| liquidity_gross != 0));
+    }
+
+    #[tokio::test]
+    async fn test_get_initialized_ticks_invalid_tick_spacing() {
+        for tick_spacing in [0, -TICK_SPACING] {
+            let result = POOL_MANAGER
+                .get_initialized_ticks(*POOL_ID_ETH_USDC, -210000..=-200000, tick_spacing, BLOCK_ID)
+                .await;
+            assert!(matches!(result, Err(Error::InvalidTickSpacing)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_tick_list() {
+        let tick_list = POOL_MANAGER
+            .build_tick_list(
+                *POOL_ID_ETH_USDC,
+                TICK_SPACING,
+                MIN_TICK_I32..=MAX_TICK_I32,
+                BLOCK_ID,
+            )
+            .await
+            .unwrap();
+        let tick = tick_list.get_tick(-202270).await.unwrap();
+        assert_eq!(tick.liquidity_gross, 847325330774525298);
+        assert_eq!(tick.liquidity_net, -847325330774525298);
+    }
+
+    #[tokio::test]
+    async fn test_build_tick_list_partial_range() {
+        // the range cuts through the positions ending at the tick
+        let result = POOL_MANAGER
+            .build_tick_list(*POOL_ID_ETH_USDC, TICK_SPACING, -202270..=-202270, BLOCK_ID)
+            .await;
+        assert!(matches!(result, Err(Error::InvalidTickList)));
+    }
+
     async fn get_position_ids() -> Vec<B256> {
         sol! {
             type PoolId is bytes32;
@@ -759,6 +1290,46 @@ mod tests {
             .collect()
     }
 
+    #[tokio::test]
+    async fn test_get_position_ids() {
+        let to_block = BLOCK_ID.unwrap().as_u64().unwrap();
+        let position_ids = POOL_MANAGER
+            .get_position_ids(*POOL_ID_ETH_USDC, to_block - 499, to_block)
+            .await
+            .unwrap();
+        assert!(!position_ids.is_empty());
+
+        let unique = position_ids.iter().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), position_ids.len());
+
+        // the lens also returns positions whose liquidity was only removed in the range
+        let added = get_position_ids().await;
+        assert!(!added.is_empty());
+        assert!(added.iter().all(|position_id| unique.contains(position_id)));
+
+        // every position touched in the range must be known to the pool manager
+        for position_id in position_ids {
+            let (_, fee_growth_inside0_last_x128, fee_growth_inside1_last_x128) = POOL_MANAGER
+                .get_position_info(*POOL_ID_ETH_USDC, position_id, BLOCK_ID)
+                .await
+                .unwrap();
+            let position_info = STATE_VIEW
+                .getPositionInfo_1(*POOL_ID_ETH_USDC, position_id)
+                .block(BLOCK_ID.unwrap())
+                .call()
+                .await
+                .unwrap();
+            assert_eq!(
+                fee_growth_inside0_last_x128,
+                position_info.feeGrowthInside0LastX128
+            );
+            assert_eq!(
+                fee_growth_inside1_last_x128,
+                position_info.feeGrowthInside1LastX128
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_get_position_info() {
         let position_ids = get_position_ids().await;
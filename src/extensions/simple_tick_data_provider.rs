@@ -3,8 +3,10 @@
 //! using [`PoolManagerLens`].
 
 use crate::prelude::{map_contract_error, PoolManagerLens};
+use alloc::vec::Vec;
 use alloy::{eips::BlockId, providers::DynProvider};
 use alloy_primitives::{aliases::I24, Address, B256, U256};
+use core::ops::RangeInclusive;
 use uniswap_v3_sdk::prelude::*;
 
 #[derive(Clone, Debug)]
@@ -48,6 +50,34 @@ where
         self.pool_id = pool_id;
         self
     }
+
+    /// Enumerates the initialized ticks of a pool within a tick range by scanning the words of its
+    /// tick bitmap, without probing every usable tick. See
+    /// [`PoolManagerLens::get_initialized_ticks`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `pool_id`: The ID of the pool
+    /// * `tick_range`: The inclusive range of ticks to scan
+    /// * `tick_spacing`: The tick spacing of the pool
+    /// * `block_id`: Optional block ID to query at
+    ///
+    /// ## Returns
+    ///
+    /// The initialized ticks in ascending order, or the error of
+    /// [`PoolManagerLens::get_initialized_ticks`]
+    #[inline]
+    pub async fn initialized_ticks(
+        &self,
+        pool_id: B256,
+        tick_range: RangeInclusive<I>,
+        tick_spacing: I,
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<I>, crate::error::Error> {
+        self.lens
+            .get_initialized_ticks(pool_id, tick_range, tick_spacing, block_id)
+            .await
+    }
 }
 
 impl<I> TickBitMapProvider for SimpleTickDataProvider<I>
@@ -189,4 +219,36 @@ mod tests {
         assert!(initialized);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_initialized_ticks() -> Result<(), crate::error::Error> {
+        let provider = super::SimpleTickDataProvider::<i32>::new(
+            CHAIN_TO_ADDRESSES_MAP
+                .get(&1)
+                .unwrap()
+                .v4_pool_manager
+                .unwrap(),
+            *POOL_ID_ETH_USDC,
+            PROVIDER.clone(),
+            BLOCK_ID,
+        );
+        let ticks = provider
+            .initialized_ticks(*POOL_ID_ETH_USDC, -210000..=-200000, TICK_SPACING, BLOCK_ID)
+            .await?;
+        assert!(ticks.contains(&-202270));
+        assert!(ticks.contains(&-202300));
+        assert!(ticks.windows(2).all(|w| w[0] < w[1]));
+        assert!(ticks
+            .iter()
+            .all(|tick| (-210000..=-200000).contains(tick) && tick % TICK_SPACING == 0));
+        assert!(provider.get_tick(ticks[0]).await?.liquidity_gross > 0);
+
+        // a range within a single word only returns ticks in range
+        let ticks = provider
+            .initialized_ticks(*POOL_ID_ETH_USDC, -202300..=-202270, TICK_SPACING, BLOCK_ID)
+            .await?;
+        assert_eq!(ticks.first(), Some(&-202300));
+        assert_eq!(ticks.last(), Some(&-202270));
+        Ok(())
+    }
 }
@@ -1,12 +1,16 @@
 use crate::prelude::{Error, *};
-use alloc::vec::Vec;
-use alloy_primitives::{address, Address, Bytes, Signature, U160, U256};
-use alloy_sol_types::{eip712_domain, SolCall};
+use alloc::{string::String, vec::Vec};
+use alloy_primitives::{address, hex, Address, Bytes, Signature, U160, U256};
+use alloy_sol_types::{eip712_domain, SolCall, SolValue};
 use derive_more::{Deref, DerefMut, From};
 use num_traits::ToPrimitive;
 use uniswap_sdk_core::prelude::*;
-use uniswap_v3_sdk::prelude::{
-    IERC721Permit, MethodParameters, MintAmounts, TickDataProvider, TickIndex,
+use uniswap_v3_sdk::{
+    entities::Position as V3Position,
+    prelude::{
+        get_sqrt_ratio_at_tick, max_liquidity_for_amounts, IERC721Permit, MethodParameters,
+        MintAmounts, TickDataProvider, TickIndex,
+    },
 };
 
 pub use uniswap_v3_sdk::prelude::NFTPermitData;
@@ -14,6 +18,9 @@ pub use uniswap_v3_sdk::prelude::NFTPermitData;
 /// Shared Action Constants used in the v4 Router and v4 position manager
 pub const MSG_SENDER: Address = address!("0000000000000000000000000000000000000001");
 
+/// Shared Action Constants used in the v4 Router and v4 position manager
+pub const ADDRESS_THIS: Address = address!("0000000000000000000000000000000000000002");
+
 /// Used when unwrapping weth in positon manager
 pub const OPEN_DELTA: U256 = U256::ZERO;
 
@@ -107,7 +114,7 @@ impl Default for RemoveLiquidityOptions {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deref, DerefMut)]
+#[derive(Clone, Debug, PartialEq, Deref, DerefMut)]
 pub struct CollectOptions {
     #[deref]
     #[deref_mut]
@@ -116,6 +123,9 @@ pub struct CollectOptions {
     pub token_id: U256,
     /// The account that should receive the tokens.
     pub recipient: Address,
+    /// The fees of currency0 and currency1 expected to be collected. If set, the collect reverts
+    /// when less than these amounts minus the slippage tolerance is collectible.
+    pub expected_fees: Option<(CurrencyAmount<Currency>, CurrencyAmount<Currency>)>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -128,6 +138,84 @@ pub struct TransferOptions {
     pub token_id: U256,
 }
 
+/// Options for producing the calldata to migrate a V3 position to V4.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrateOptions {
+    /// The ID of the V3 position to migrate.
+    pub v3_token_id: U256,
+    /// Whether to burn the V3 position NFT once all of its liquidity is removed.
+    pub burn_v3_token: bool,
+    /// The V4 position manager, which receives the tokens collected from the V3 position.
+    pub v4_position_manager: Address,
+    /// The options for minting the V4 position, which must be [`MintSpecificOptions`]. The mint is
+    /// always treated as a migration.
+    pub add_liquidity_options: AddLiquidityOptions,
+}
+
+/// A position manager command of the Universal Router, which executes the commands passed to
+/// `execute` in order within a single transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RouterCommand {
+    /// `V3_POSITION_MANAGER_CALL`: a `decreaseLiquidity`, `collect` or `burn` call to the V3
+    /// `NonfungiblePositionManager`.
+    V3PositionManagerCall(Bytes),
+    /// `V4_INITIALIZE_POOL`: initializes a V4 pool at the given price.
+    V4InitializePool {
+        pool_key: PoolKey,
+        sqrt_price_x96: U160,
+    },
+    /// `V4_POSITION_MANAGER_CALL`: a `modifyLiquidities` call to the V4 position manager.
+    V4PositionManagerCall(Bytes),
+}
+
+impl RouterCommand {
+    /// Returns the command byte of the command.
+    #[inline]
+    #[must_use]
+    pub const fn command(&self) -> u8 {
+        match self {
+            Self::V3PositionManagerCall(_) => 0x12,
+            Self::V4InitializePool { .. } => 0x13,
+            Self::V4PositionManagerCall(_) => 0x14,
+        }
+    }
+
+    /// Returns the input of the command passed to `execute`.
+    #[inline]
+    #[must_use]
+    pub fn input(&self) -> Bytes {
+        match self {
+            Self::V3PositionManagerCall(calldata) | Self::V4PositionManagerCall(calldata) => {
+                calldata.clone()
+            }
+            Self::V4InitializePool {
+                pool_key,
+                sqrt_price_x96,
+            } => (pool_key.clone(), *sqrt_price_x96)
+                .abi_encode_params()
+                .into(),
+        }
+    }
+}
+
+/// The calldata for migrating a V3 position to V4 through the Universal Router.
+///
+/// The router only forwards single `decreaseLiquidity`, `collect` and `burn` calls to the V3
+/// `NonfungiblePositionManager` and `modifyLiquidities` calls to the V4 position manager, never
+/// `multicall`, so the migration is a list of router commands. Executing them in one `execute` call
+/// settles the tokens collected to the V4 position manager into the mint within the same
+/// transaction, before anyone else can sweep them. The router must be approved for the V3 position
+/// beforehand, e.g. via `approve` or a `V3_POSITION_MANAGER_PERMIT` command.
+#[derive(Clone, Debug)]
+pub struct MigrateCallParameters {
+    /// The router commands, in execution order: the V3 decrease, collect and optional burn, the
+    /// V4 pool initialization if requested, and the V4 mint.
+    pub commands: Vec<RouterCommand>,
+    /// The `execute` call to the Universal Router running [`Self::commands`] with the deadline of
+    /// the options.
+    pub router_call: MethodParameters,
+}
+
 pub type AllowanceTransferPermitSingle = IAllowanceTransfer::PermitSingle;
 pub type AllowanceTransferPermitBatch = IAllowanceTransfer::PermitBatch;
 pub type NFTPermitValues = IERC721Permit::Permit;
@@ -147,7 +235,30 @@ pub struct NFTPermitOptions {
     pub signature: Signature,
 }
 
-/// Public methods to encode method parameters for different actions on the PositionManager contract
+/// Extension methods for the [`MethodParameters`] returned by the position manager helpers.
+pub trait MethodParametersExt {
+    /// Returns the calldata as a `0x`-prefixed hex string, e.g. for pasting into a block explorer.
+    fn calldata_hex(&self) -> String;
+}
+
+impl MethodParametersExt for MethodParameters {
+    #[inline]
+    fn calldata_hex(&self) -> String {
+        hex::encode_prefixed(&self.calldata)
+    }
+}
+
+/// Encodes the method parameters for only initializing a pool, without adding liquidity.
+///
+/// ## Notes
+///
+/// To initialize a pool and mint a position in the same transaction, pass
+/// [`AddLiquidityOptions::create_pool`] to [`add_call_parameters`] instead.
+///
+/// ## Arguments
+///
+/// * `pool_key`: The key of the pool to initialize.
+/// * `sqrt_price_x96`: The initial sqrt price of the pool.
 #[inline]
 #[must_use]
 pub fn create_call_parameters(pool_key: PoolKey, sqrt_price_x96: U160) -> MethodParameters {
@@ -157,6 +268,21 @@ pub fn create_call_parameters(pool_key: PoolKey, sqrt_price_x96: U160) -> Method
     }
 }
 
+/// Encodes the method parameters for only initializing the given pool, reading its pool key.
+///
+/// ## Arguments
+///
+/// * `pool`: The pool to initialize.
+/// * `sqrt_price_x96`: The initial sqrt price of the pool.
+#[inline]
+#[must_use]
+pub fn initialize_pool_call_parameters<TP: TickDataProvider>(
+    pool: &Pool<TP>,
+    sqrt_price_x96: U160,
+) -> MethodParameters {
+    create_call_parameters(pool.pool_key.clone(), sqrt_price_x96)
+}
+
 /// Encodes the method parameters for adding liquidity to a position.
 ///
 /// ## Notes
@@ -179,7 +305,6 @@ pub fn add_call_parameters<TP: TickDataProvider>(
     assert!(position.liquidity > 0, "ZERO_LIQUIDITY");
 
     let mut calldatas: Vec<Bytes> = Vec::with_capacity(3);
-    let mut planner = V4PositionPlanner::default();
 
     // Encode initialize pool.
     if let AddLiquiditySpecificOptions::Mint(opts) = options.specific_opts {
@@ -192,6 +317,38 @@ pub fn add_call_parameters<TP: TickDataProvider>(
         }
     }
 
+    let (planner, value) = plan_add_liquidity(position, &options)?;
+
+    // We use permit2 to approve tokens to the position manager. The permit must precede the
+    // modifyLiquidities call for both mints and increases so the position manager can pull the
+    // tokens when settling.
+    if let Some(batch_permit) = options.batch_permit {
+        calldatas.push(encode_permit_batch(
+            batch_permit.owner,
+            batch_permit.permit_batch,
+            batch_permit.signature,
+        ));
+    }
+
+    calldatas.push(encode_modify_liquidities(
+        planner.0.finalize(),
+        options.common_opts.deadline,
+    ));
+
+    Ok(MethodParameters {
+        calldata: encode_multicall(calldatas),
+        value,
+    })
+}
+
+/// Plans the actions of [`add_call_parameters`] that add the position's liquidity and settle its
+/// currencies, returning the planner and the native value to send with the call.
+fn plan_add_liquidity<TP: TickDataProvider>(
+    position: &mut Position<TP>,
+    options: &AddLiquidityOptions,
+) -> Result<(V4PositionPlanner, U256), Error> {
+    let mut planner = V4PositionPlanner::default();
+
     // position.pool.currency0 is native if and only if options.useNative is set
     assert!(
         if let Some(ether) = &options.use_native {
@@ -208,15 +365,6 @@ pub fn add_call_parameters<TP: TickDataProvider>(
         amount1: amount1_max,
     } = position.mint_amounts_with_slippage(&options.slippage_tolerance)?;
 
-    // We use permit2 to approve tokens to the position manager
-    if let Some(batch_permit) = options.batch_permit {
-        calldatas.push(encode_permit_batch(
-            batch_permit.owner,
-            batch_permit.permit_batch,
-            batch_permit.signature,
-        ));
-    }
-
     match options.specific_opts {
         AddLiquiditySpecificOptions::Mint(opts) => {
             planner.add_mint(
@@ -227,7 +375,7 @@ pub fn add_call_parameters<TP: TickDataProvider>(
                 u128::try_from(amount0_max).unwrap(),
                 u128::try_from(amount1_max).unwrap(),
                 opts.recipient,
-                options.common_opts.hook_data,
+                options.common_opts.hook_data.clone(),
             );
         }
         AddLiquiditySpecificOptions::Increase(opts) => {
@@ -236,7 +384,7 @@ pub fn add_call_parameters<TP: TickDataProvider>(
                 U256::from(position.liquidity),
                 u128::try_from(amount0_max).unwrap(),
                 u128::try_from(amount1_max).unwrap(),
-                options.common_opts.hook_data,
+                options.common_opts.hook_data.clone(),
             );
         }
     }
@@ -278,14 +426,146 @@ pub fn add_call_parameters<TP: TickDataProvider>(
         }
     }
 
-    calldatas.push(encode_modify_liquidities(
-        planner.0.finalize(),
-        options.common_opts.deadline,
+    Ok((planner, value))
+}
+
+/// Produces the Universal Router commands for migrating a V3 position to V4: a V3 decrease and
+/// collect of all of the position's liquidity to the V4 position manager, followed by a V4 mint of
+/// the collected tokens, see [`MigrateCallParameters`]
+///
+/// ## Notes
+///
+/// - The V4 position's liquidity is capped to what the minimum amounts of the V3 burn can mint.
+/// - If the pool is created, it is initialized at the `sqrt_price_x96` of the options, or else at
+///   the price of the V4 position's pool.
+/// - A V4 pool of the native currency is supported for a V3 pool of its wrapped token; the V4 mint
+///   unwraps what the mint needs and sweeps any leftover wrapped token to the mint recipient.
+///
+/// ## Arguments
+///
+/// * `v3_position`: The V3 position to migrate
+/// * `v4_position`: The V4 position to mint
+/// * `options`: Additional information necessary for generating the calldata
+#[inline]
+pub fn migrate_v3_to_v4_call_parameters<TP3, TP>(
+    v3_position: &V3Position<TP3>,
+    v4_position: Position<TP>,
+    options: MigrateOptions,
+) -> Result<MigrateCallParameters, Error>
+where
+    TP3: Clone + TickDataProvider,
+    TP: TickDataProvider,
+{
+    let MigrateOptions {
+        v3_token_id,
+        burn_v3_token,
+        v4_position_manager,
+        mut add_liquidity_options,
+    } = options;
+    match &mut add_liquidity_options.specific_opts {
+        AddLiquiditySpecificOptions::Mint(opts) => opts.migrate = true,
+        AddLiquiditySpecificOptions::Increase(_) => return Err(Error::MigrateRequiresMint),
+    }
+
+    // the V3 pool pairs the wrapped token where the V4 pool may pair the native currency, which
+    // may also flip the order of the currencies
+    let (v3_amount0_min, v3_amount1_min) =
+        v3_position.burn_amounts_with_slippage(&add_liquidity_options.slippage_tolerance)?;
+    let pool = &v4_position.pool;
+    let v3_token0 = &v3_position.pool.token0;
+    let v3_token1 = &v3_position.pool.token1;
+    let (amount0_available, amount1_available) = if pool.currency0.wrapped().equals(v3_token0)
+        && pool.currency1.wrapped().equals(v3_token1)
+    {
+        (v3_amount0_min, v3_amount1_min)
+    } else if pool.currency0.wrapped().equals(v3_token1)
+        && pool.currency1.wrapped().equals(v3_token0)
+    {
+        (v3_amount1_min, v3_amount0_min)
+    } else {
+        return Err(Error::InvalidCurrency);
+    };
+    if pool.currency0.is_native() && add_liquidity_options.use_native.is_none() {
+        add_liquidity_options.use_native = Some(Ether::on_chain(pool.chain_id()));
+    }
+
+    let mut commands: Vec<RouterCommand> = Vec::with_capacity(5);
+    commands.push(RouterCommand::V3PositionManagerCall(
+        IV3NonfungiblePositionManager::decreaseLiquidityCall {
+            params: IV3NonfungiblePositionManager::DecreaseLiquidityParams {
+                tokenId: v3_token_id,
+                liquidity: v3_position.liquidity,
+                amount0Min: v3_amount0_min,
+                amount1Min: v3_amount1_min,
+                deadline: add_liquidity_options.deadline,
+            },
+        }
+        .abi_encode()
+        .into(),
+    ));
+    commands.push(RouterCommand::V3PositionManagerCall(
+        IV3NonfungiblePositionManager::collectCall {
+            params: IV3NonfungiblePositionManager::CollectParams {
+                tokenId: v3_token_id,
+                recipient: v4_position_manager,
+                amount0Max: u128::MAX,
+                amount1Max: u128::MAX,
+            },
+        }
+        .abi_encode()
+        .into(),
+    ));
+    if burn_v3_token {
+        commands.push(RouterCommand::V3PositionManagerCall(
+            IV3NonfungiblePositionManager::burnCall {
+                tokenId: v3_token_id,
+            }
+            .abi_encode()
+            .into(),
+        ));
+    }
+
+    // cap the V4 liquidity to what the collected tokens can mint
+    let max_liquidity = max_liquidity_for_amounts(
+        v4_position.pool.sqrt_price_x96,
+        get_sqrt_ratio_at_tick(v4_position.tick_lower.to_i24())?,
+        get_sqrt_ratio_at_tick(v4_position.tick_upper.to_i24())?,
+        amount0_available,
+        amount1_available,
+        true,
+    )
+    .to_u128()
+    .unwrap_or(u128::MAX);
+    let liquidity = v4_position.liquidity.min(max_liquidity);
+    let mut v4_position = Position::new(
+        v4_position.pool,
+        liquidity,
+        v4_position.tick_lower,
+        v4_position.tick_upper,
+    );
+
+    if let AddLiquiditySpecificOptions::Mint(opts) = add_liquidity_options.specific_opts {
+        if opts.create_pool {
+            commands.push(RouterCommand::V4InitializePool {
+                pool_key: v4_position.pool.pool_key.clone(),
+                sqrt_price_x96: opts
+                    .sqrt_price_x96
+                    .unwrap_or(v4_position.pool.sqrt_price_x96),
+            });
+        }
+    }
+    // the tokens are collected to the V4 position manager, which pays for the mint itself
+    let (planner, value) = plan_add_liquidity(&mut v4_position, &add_liquidity_options)?;
+    commands.push(RouterCommand::V4PositionManagerCall(
+        encode_modify_liquidities(planner.0.finalize(), add_liquidity_options.deadline),
     ));
 
-    Ok(MethodParameters {
-        calldata: encode_multicall(calldatas),
-        value,
+    Ok(MigrateCallParameters {
+        router_call: MethodParameters {
+            calldata: encode_execute(&commands, add_liquidity_options.deadline),
+            value,
+        },
+        commands,
     })
 }
 
@@ -406,7 +686,7 @@ pub fn collect_call_parameters<TP: TickDataProvider>(
 
     // To collect fees in V4, we need to:
     // - encode a decrease liquidity by 0
-    // - and encode a TAKE_PAIR
+    // - and encode a TAKE_PAIR, or a bounded TAKE of each currency if the fees are expected
     planner.add_decrease(
         options.token_id,
         U256::ZERO,
@@ -415,11 +695,29 @@ pub fn collect_call_parameters<TP: TickDataProvider>(
         options.common_opts.hook_data,
     );
 
-    planner.add_take_pair(
-        &position.pool.currency0,
-        &position.pool.currency1,
-        options.recipient,
-    );
+    match &options.expected_fees {
+        Some((expected_fees0, expected_fees1)) => {
+            // The position manager doesn't support TAKE_ALL, so the minimum of each currency is
+            // taken first. Taking the rest with OPEN_DELTA then reverts if the credit went
+            // negative, i.e. if less than the minimum was collectible.
+            let slippage_tolerance = &options.common_opts.slippage_tolerance;
+            for (currency, expected_fees) in [
+                (&position.pool.currency0, expected_fees0),
+                (&position.pool.currency1, expected_fees1),
+            ] {
+                let min_amount = min_amount_with_slippage(expected_fees, slippage_tolerance);
+                planner.add_take(currency, options.recipient, Some(min_amount));
+                planner.add_take(currency, options.recipient, Some(OPEN_DELTA));
+            }
+        }
+        None => {
+            planner.add_take_pair(
+                &position.pool.currency0,
+                &position.pool.currency1,
+                options.recipient,
+            );
+        }
+    }
 
     MethodParameters {
         calldata: encode_modify_liquidities(planner.0.finalize(), options.common_opts.deadline),
@@ -427,6 +725,54 @@ pub fn collect_call_parameters<TP: TickDataProvider>(
     }
 }
 
+/// Returns the amount minus the slippage tolerance, rounded down
+fn min_amount_with_slippage(
+    amount: &CurrencyAmount<Currency>,
+    slippage_tolerance: &Percent,
+) -> U256 {
+    let min_amount = amount.quotient()
+        * (slippage_tolerance.denominator - slippage_tolerance.numerator)
+        / slippage_tolerance.denominator;
+    U256::from_big_int(min_amount)
+}
+
+/// Produces the calldata for collecting fees from a position, sending each currency to its own
+/// recipient
+///
+/// ## Notes
+///
+/// Like [`collect_call_parameters`], a decrease liquidity by 0 is encoded first, but it is followed
+/// by a `TAKE` of each currency instead of a single `TAKE_PAIR`.
+///
+/// ## Arguments
+///
+/// * `position`: The position to collect fees from
+/// * `token_id`: The id of the position
+/// * `recipient0`: The recipient of the fees in currency0
+/// * `recipient1`: The recipient of the fees in currency1
+/// * `common_opts`: The deadline and hook data of the call
+#[inline]
+#[allow(clippy::needless_pass_by_value)]
+pub fn collect_call_parameters_split<TP: TickDataProvider>(
+    position: &Position<TP>,
+    token_id: U256,
+    recipient0: Address,
+    recipient1: Address,
+    common_opts: CommonOptions,
+) -> MethodParameters {
+    let mut planner = V4PositionPlanner::default();
+
+    planner.add_decrease(token_id, U256::ZERO, 0, 0, common_opts.hook_data);
+    planner
+        .add_take(&position.pool.currency0, recipient0, Some(OPEN_DELTA))
+        .add_take(&position.pool.currency1, recipient1, Some(OPEN_DELTA));
+
+    MethodParameters {
+        calldata: encode_modify_liquidities(planner.0.finalize(), common_opts.deadline),
+        value: U256::ZERO,
+    }
+}
+
 #[inline]
 fn encode_initialize_pool(pool_key: PoolKey, sqrt_price_x96: U160) -> Bytes {
     IPositionManager::initializePoolCall {
@@ -447,6 +793,24 @@ pub fn encode_modify_liquidities(unlock_data: Bytes, deadline: U256) -> Bytes {
     .into()
 }
 
+/// Encodes a Universal Router `execute` call running the commands in order within a single
+/// transaction
+#[inline]
+#[must_use]
+pub fn encode_execute(commands: &[RouterCommand], deadline: U256) -> Bytes {
+    IUniversalRouter::executeCall {
+        commands: commands
+            .iter()
+            .map(RouterCommand::command)
+            .collect::<Vec<u8>>()
+            .into(),
+        inputs: commands.iter().map(RouterCommand::input).collect(),
+        deadline,
+    }
+    .abi_encode()
+    .into()
+}
+
 #[inline]
 pub fn encode_permit_batch(
     owner: Address,
@@ -669,6 +1033,54 @@ mod tests {
         }
     }
 
+    mod calldata_hex {
+        use super::*;
+
+        #[test]
+        fn round_trips_to_calldata() {
+            let pool_key = Pool::get_pool_key(
+                &CURRENCY0.clone(),
+                &CURRENCY1.clone(),
+                FEE.into(),
+                TICK_SPACING,
+                Address::ZERO,
+            )
+            .unwrap();
+            let method_parameters = create_call_parameters(pool_key, *SQRT_PRICE_1_1);
+
+            let calldata_hex = method_parameters.calldata_hex();
+            assert!(calldata_hex.starts_with("0xf7020405"));
+            assert_eq!(
+                Bytes::from(hex::decode(calldata_hex).unwrap()),
+                method_parameters.calldata
+            );
+        }
+
+        #[test]
+        fn empty_calldata() {
+            let method_parameters = MethodParameters {
+                calldata: Bytes::new(),
+                value: U256::ZERO,
+            };
+            assert_eq!(method_parameters.calldata_hex(), "0x");
+        }
+    }
+
+    mod initialize_pool_call_parameters {
+        use super::*;
+
+        #[test]
+        fn matches_create_call_parameters() {
+            for pool in [&*POOL_0_1, &*POOL_1_ETH] {
+                let MethodParameters { calldata, value } =
+                    initialize_pool_call_parameters(pool, *SQRT_PRICE_1_1);
+                let expected = create_call_parameters(pool.pool_key.clone(), *SQRT_PRICE_1_1);
+                assert_eq!(calldata, expected.calldata);
+                assert_eq!(value, expected.value);
+            }
+        }
+    }
+
     mod add_call_parameters {
         use super::*;
 
@@ -1092,6 +1504,70 @@ mod tests {
             );
             assert_eq!(value, U256::ZERO);
         }
+
+        #[test]
+        fn succeeds_for_increase_with_batch_permit() {
+            let mut position = Position::new(POOL_0_1.clone(), 666, -TICK_SPACING, TICK_SPACING);
+
+            let batch_permit = BatchPermitOptions {
+                owner: MOCK_OWNER,
+                permit_batch: AllowanceTransferPermitBatch {
+                    details: vec![],
+                    spender: MOCK_SPENDER,
+                    sigDeadline: DEADLINE,
+                },
+                signature: Bytes::default(),
+            };
+
+            let options = AddLiquidityOptions {
+                common_opts: common_options(),
+                use_native: None,
+                batch_permit: Some(batch_permit.clone()),
+                specific_opts: ModifyPositionSpecificOptions { token_id: TOKEN_ID }.into(),
+            };
+
+            let MethodParameters { calldata, value } =
+                add_call_parameters(&mut position, options).unwrap();
+
+            let calldata_arr: Vec<Bytes> = decode_multicall(&calldata).unwrap();
+            assert_eq!(calldata_arr.len(), 2);
+            // Expect permitBatch to be called before modifyLiquidities
+            assert_eq!(
+                calldata_arr[0],
+                encode_permit_batch(
+                    batch_permit.owner,
+                    batch_permit.permit_batch,
+                    batch_permit.signature,
+                )
+            );
+
+            let MintAmounts {
+                amount0: amount0_max,
+                amount1: amount1_max,
+            } = position
+                .mint_amounts_with_slippage(&SLIPPAGE_TOLERANCE.clone())
+                .unwrap();
+
+            let mut planner = V4PositionPlanner::default();
+            planner.add_increase(
+                TOKEN_ID,
+                uint!(666_U256),
+                u128::try_from(amount0_max).unwrap(),
+                u128::try_from(amount1_max).unwrap(),
+                Bytes::default(),
+            );
+            planner.add_settle_pair(&POOL_0_1.currency0, &POOL_0_1.currency1);
+            let unlock_data = planner.0.finalize();
+            assert!(matches!(
+                parse_calldata(&unlock_data).unwrap().actions[0],
+                Actions::INCREASE_LIQUIDITY(_)
+            ));
+            assert_eq!(
+                calldata_arr[1],
+                encode_modify_liquidities(unlock_data, DEADLINE)
+            );
+            assert_eq!(value, U256::ZERO);
+        }
     }
 
     mod remove_call_parameters {
@@ -1265,6 +1741,226 @@ mod tests {
         }
     }
 
+    mod migrate_v3_to_v4_call_parameters {
+        use super::*;
+        use uniswap_v3_sdk::entities::Pool as V3Pool;
+
+        const V4_POSITION_MANAGER: Address = address!("000000000000000000000000000000000000000d");
+        const V3_LIQUIDITY: u128 = 1_000_000;
+
+        fn v3_position(token_a: &Currency, token_b: &Currency) -> V3Position {
+            let pool = V3Pool::new(
+                token_a.wrapped().clone(),
+                token_b.wrapped().clone(),
+                FEE,
+                *SQRT_PRICE_1_1,
+                ONE_ETHER,
+            )
+            .unwrap();
+            V3Position::new(pool, V3_LIQUIDITY, -TICK_SPACING, TICK_SPACING)
+        }
+
+        fn migrate_options(burn_v3_token: bool) -> MigrateOptions {
+            MigrateOptions {
+                v3_token_id: TOKEN_ID,
+                burn_v3_token,
+                v4_position_manager: V4_POSITION_MANAGER,
+                add_liquidity_options: AddLiquidityOptions {
+                    common_opts: common_options(),
+                    specific_opts: mint_specific_options(),
+                    ..Default::default()
+                },
+            }
+        }
+
+        fn v4_actions(commands: &[RouterCommand]) -> Vec<Actions> {
+            let Some(RouterCommand::V4PositionManagerCall(calldata)) = commands.last() else {
+                panic!("expected V4_POSITION_MANAGER_CALL");
+            };
+            let call = IPositionManager::modifyLiquiditiesCall::abi_decode(calldata).unwrap();
+            parse_calldata(&call.unlockData).unwrap().actions
+        }
+
+        #[test]
+        fn decreases_and_collects_v3_position_to_v4_position_manager() {
+            let v3_position = v3_position(&CURRENCY0, &CURRENCY1);
+            let v4_position =
+                Position::new(POOL_0_1.clone(), V3_LIQUIDITY, -TICK_SPACING, TICK_SPACING);
+
+            let MigrateCallParameters {
+                commands,
+                router_call,
+            } = migrate_v3_to_v4_call_parameters(&v3_position, v4_position, migrate_options(true))
+                .unwrap();
+
+            assert_eq!(commands.len(), 4);
+            let RouterCommand::V3PositionManagerCall(calldata) = &commands[0] else {
+                panic!("expected V3_POSITION_MANAGER_CALL");
+            };
+            let decrease =
+                IV3NonfungiblePositionManager::decreaseLiquidityCall::abi_decode(calldata)
+                    .unwrap()
+                    .params;
+            assert_eq!(decrease.tokenId, TOKEN_ID);
+            assert_eq!(decrease.liquidity, V3_LIQUIDITY);
+            assert_eq!(decrease.deadline, DEADLINE);
+            let RouterCommand::V3PositionManagerCall(calldata) = &commands[1] else {
+                panic!("expected V3_POSITION_MANAGER_CALL");
+            };
+            let collect = IV3NonfungiblePositionManager::collectCall::abi_decode(calldata)
+                .unwrap()
+                .params;
+            assert_eq!(collect.recipient, V4_POSITION_MANAGER);
+            assert_eq!(
+                commands[2],
+                RouterCommand::V3PositionManagerCall(Bytes::from(
+                    IV3NonfungiblePositionManager::burnCall { tokenId: TOKEN_ID }.abi_encode()
+                ))
+            );
+
+            let actions = v4_actions(&commands);
+            assert!(matches!(actions[0], Actions::MINT_POSITION(_)));
+            assert!(matches!(
+                &actions[1],
+                Actions::SETTLE(SettleParams {
+                    payerIsUser: false,
+                    ..
+                })
+            ));
+            assert!(matches!(actions.last().unwrap(), Actions::SWEEP(_)));
+            assert_eq!(router_call.value, U256::ZERO);
+        }
+
+        #[test]
+        fn encodes_commands_into_router_execute_call() {
+            let v3_position = v3_position(&CURRENCY0, &CURRENCY1);
+            let v4_position =
+                Position::new(POOL_0_1.clone(), V3_LIQUIDITY, -TICK_SPACING, TICK_SPACING);
+
+            let MigrateCallParameters {
+                commands,
+                router_call,
+            } = migrate_v3_to_v4_call_parameters(&v3_position, v4_position, migrate_options(true))
+                .unwrap();
+
+            let execute = IUniversalRouter::executeCall::abi_decode(&router_call.calldata).unwrap();
+            assert_eq!(execute.commands.to_vec(), vec![0x12, 0x12, 0x12, 0x14]);
+            assert_eq!(
+                execute.inputs,
+                commands
+                    .iter()
+                    .map(RouterCommand::input)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(execute.deadline, DEADLINE);
+        }
+
+        #[test]
+        fn initializes_pool_before_mint() {
+            let v3_position = v3_position(&CURRENCY0, &CURRENCY1);
+            let v4_position =
+                Position::new(POOL_0_1.clone(), V3_LIQUIDITY, -TICK_SPACING, TICK_SPACING);
+            let mut options = migrate_options(false);
+            options.add_liquidity_options.specific_opts = MintSpecificOptions {
+                recipient: RECIPIENT,
+                create_pool: true,
+                ..Default::default()
+            }
+            .into();
+
+            let MigrateCallParameters { commands, .. } =
+                migrate_v3_to_v4_call_parameters(&v3_position, v4_position, options).unwrap();
+
+            assert_eq!(commands.len(), 4);
+            assert_eq!(
+                commands[2],
+                RouterCommand::V4InitializePool {
+                    pool_key: POOL_0_1.pool_key.clone(),
+                    sqrt_price_x96: POOL_0_1.sqrt_price_x96,
+                }
+            );
+            assert_eq!(
+                commands[2].input(),
+                Bytes::from(
+                    (POOL_0_1.pool_key.clone(), POOL_0_1.sqrt_price_x96).abi_encode_params()
+                )
+            );
+            assert!(matches!(
+                commands[3],
+                RouterCommand::V4PositionManagerCall(_)
+            ));
+        }
+
+        #[test]
+        fn caps_v4_liquidity_to_collected_amounts() {
+            let v3_position = v3_position(&CURRENCY0, &CURRENCY1);
+            let v4_position = Position::new(
+                POOL_0_1.clone(),
+                V3_LIQUIDITY * 2,
+                -TICK_SPACING,
+                TICK_SPACING,
+            );
+
+            let MigrateCallParameters { commands, .. } =
+                migrate_v3_to_v4_call_parameters(&v3_position, v4_position, migrate_options(false))
+                    .unwrap();
+
+            assert_eq!(commands.len(), 3);
+            let actions = v4_actions(&commands);
+            let Actions::MINT_POSITION(mint) = &actions[0] else {
+                panic!("expected MINT_POSITION");
+            };
+            assert!(mint.liquidity > U256::ZERO);
+            assert!(mint.liquidity <= U256::from(V3_LIQUIDITY));
+        }
+
+        #[test]
+        fn unwraps_for_native_v4_pool() {
+            let v3_position = v3_position(&ETHER.clone().into(), &CURRENCY1);
+            let v4_position = Position::new(
+                POOL_1_ETH.clone(),
+                V3_LIQUIDITY,
+                -TICK_SPACING,
+                TICK_SPACING,
+            );
+
+            let MigrateCallParameters {
+                commands,
+                router_call,
+            } = migrate_v3_to_v4_call_parameters(&v3_position, v4_position, migrate_options(false))
+                .unwrap();
+
+            let actions = v4_actions(&commands);
+            assert!(matches!(actions[0], Actions::MINT_POSITION(_)));
+            assert_eq!(actions[1], Actions::UNWRAP(OPEN_DELTA));
+            assert_eq!(router_call.value, U256::ZERO);
+        }
+
+        #[test]
+        fn throws_for_mismatched_currencies() {
+            let v3_position = v3_position(&CURRENCY0, &ETHER.clone().into());
+            let v4_position =
+                Position::new(POOL_0_1.clone(), V3_LIQUIDITY, -TICK_SPACING, TICK_SPACING);
+
+            let result =
+                migrate_v3_to_v4_call_parameters(&v3_position, v4_position, migrate_options(false));
+            assert!(matches!(result, Err(Error::InvalidCurrency)));
+        }
+
+        #[test]
+        fn throws_for_increase() {
+            let v3_position = v3_position(&CURRENCY0, &CURRENCY1);
+            let v4_position =
+                Position::new(POOL_0_1.clone(), V3_LIQUIDITY, -TICK_SPACING, TICK_SPACING);
+            let mut options = migrate_options(false);
+            options.add_liquidity_options.specific_opts =
+                ModifyPositionSpecificOptions { token_id: TOKEN_ID }.into();
+
+            let result = migrate_v3_to_v4_call_parameters(&v3_position, v4_position, options);
+            assert!(matches!(result, Err(Error::MigrateRequiresMint)));
+        }
+    }
+
     mod collect_call_parameters {
         use super::*;
 
@@ -1277,6 +1973,7 @@ mod tests {
                     common_opts: common_options(),
                     token_id: TOKEN_ID,
                     recipient: RECIPIENT,
+                    expected_fees: None,
                 },
             );
 
@@ -1291,6 +1988,81 @@ mod tests {
             );
             assert_eq!(value, U256::ZERO);
         }
+
+        #[test]
+        fn bounds_takes_by_expected_fees() {
+            let position = Position::new(POOL_0_1.clone(), 100, -TICK_SPACING, TICK_SPACING);
+            let MethodParameters { calldata, value } = collect_call_parameters(
+                &position,
+                CollectOptions {
+                    common_opts: common_options(),
+                    token_id: TOKEN_ID,
+                    recipient: RECIPIENT,
+                    expected_fees: Some((
+                        CurrencyAmount::from_raw_amount(CURRENCY0.clone(), 1000).unwrap(),
+                        CurrencyAmount::from_raw_amount(CURRENCY1.clone(), 2000).unwrap(),
+                    )),
+                },
+            );
+
+            let mut planner = V4PositionPlanner::default();
+
+            planner.add_decrease(TOKEN_ID, U256::ZERO, 0, 0, Bytes::default());
+            planner.add_take(&*CURRENCY0, RECIPIENT, Some(U256::from(990)));
+            planner.add_take(&*CURRENCY0, RECIPIENT, Some(OPEN_DELTA));
+            planner.add_take(&*CURRENCY1, RECIPIENT, Some(U256::from(1980)));
+            planner.add_take(&*CURRENCY1, RECIPIENT, Some(OPEN_DELTA));
+
+            assert_eq!(
+                calldata,
+                encode_modify_liquidities(planner.0.finalize(), DEADLINE)
+            );
+            assert_eq!(value, U256::ZERO);
+        }
+    }
+
+    mod collect_call_parameters_split {
+        use super::*;
+
+        const RECIPIENT1: Address = address!("000000000000000000000000000000000000000d");
+
+        #[test]
+        fn takes_each_currency_to_its_recipient() {
+            let position = Position::new(POOL_0_1.clone(), 100, -TICK_SPACING, TICK_SPACING);
+            let MethodParameters { calldata, value } = collect_call_parameters_split(
+                &position,
+                TOKEN_ID,
+                RECIPIENT,
+                RECIPIENT1,
+                common_options(),
+            );
+            assert_eq!(value, U256::ZERO);
+
+            let call = IPositionManager::modifyLiquiditiesCall::abi_decode(&calldata).unwrap();
+            assert_eq!(call.deadline, DEADLINE);
+            assert_eq!(
+                decode_actions(&call.unlockData).unwrap(),
+                vec![
+                    Actions::DECREASE_LIQUIDITY(DecreaseLiquidityParams {
+                        tokenId: TOKEN_ID,
+                        liquidity: U256::ZERO,
+                        amount0Min: 0,
+                        amount1Min: 0,
+                        hookData: Bytes::default(),
+                    }),
+                    Actions::TAKE(TakeParams {
+                        currency: CURRENCY0.address(),
+                        recipient: RECIPIENT,
+                        amount: OPEN_DELTA,
+                    }),
+                    Actions::TAKE(TakeParams {
+                        currency: CURRENCY1.address(),
+                        recipient: RECIPIENT1,
+                        amount: OPEN_DELTA,
+                    }),
+                ]
+            );
+        }
     }
 
     mod get_permit_data {
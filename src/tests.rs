@@ -137,7 +137,7 @@ pub(crate) use extensions::*;
 #[cfg(feature = "extensions")]
 mod extensions {
     use super::*;
-    use crate::abi::IStateView;
+    use crate::{abi::IStateView, extensions::PoolManagerLens};
     use alloy::{
         eips::{BlockId, BlockNumberOrTag},
         providers::{DynProvider, ProviderBuilder},
@@ -171,6 +171,17 @@ mod extensions {
         .unwrap()
     });
 
+    pub(crate) static POOL_MANAGER: Lazy<PoolManagerLens> = Lazy::new(|| {
+        PoolManagerLens::new(
+            CHAIN_TO_ADDRESSES_MAP
+                .get(&1)
+                .unwrap()
+                .v4_pool_manager
+                .unwrap(),
+            PROVIDER.clone(),
+        )
+    });
+
     pub(crate) static STATE_VIEW: Lazy<IStateView::IStateViewInstance<DynProvider>> =
         Lazy::new(|| {
             IStateView::new(
@@ -182,4 +193,7 @@ mod extensions {
                 PROVIDER.clone(),
             )
         });
+
+    pub(crate) static V4_QUOTER: Lazy<alloy_primitives::Address> =
+        Lazy::new(|| CHAIN_TO_ADDRESSES_MAP.get(&1).unwrap().v4_quoter.unwrap());
 }
@@ -1,5 +1,8 @@
-use alloy_primitives::Address;
-use uniswap_sdk_core::prelude::BaseCurrency;
+use crate::prelude::Pool;
+use alloc::vec::Vec;
+use alloy_primitives::{map::HashMap, Address};
+use uniswap_sdk_core::prelude::{BaseCurrency, WETH9};
+use uniswap_v3_sdk::prelude::TickDataProvider;
 
 #[inline]
 pub fn to_address(currency: &impl BaseCurrency) -> Address {
@@ -8,3 +11,94 @@ pub fn to_address(currency: &impl BaseCurrency) -> Address {
         false => currency.address(),
     }
 }
+
+/// Indexes pools by the addresses of the currencies they involve, with [`Address::ZERO`] for the
+/// native currency, so that pathfinders can look up the pools adjacent to a currency instead of
+/// scanning all of them
+///
+/// Like [`Pool::v4_involves_token`], the native currency and its wrapped token share pools, so a
+/// pool of either is indexed under both addresses.
+///
+/// ## Arguments
+///
+/// * `pools`: The pools to index
+///
+/// ## Returns
+///
+/// The indices of the pools involving each currency, in ascending order
+#[inline]
+#[must_use]
+pub fn index_pools_by_currency<TP: TickDataProvider>(
+    pools: &[Pool<TP>],
+) -> HashMap<Address, Vec<usize>> {
+    let mut index: HashMap<Address, Vec<usize>> = HashMap::default();
+    for (i, pool) in pools.iter().enumerate() {
+        for currency in [&pool.currency0, &pool.currency1] {
+            let is_wrapped_native = currency.is_native()
+                || WETH9::on_chain(currency.chain_id()).is_some_and(|weth| weth.equals(currency));
+            let mut addresses = Vec::with_capacity(2);
+            addresses.push(to_address(currency));
+            if is_wrapped_native {
+                addresses.push(if currency.is_native() {
+                    currency.wrapped().address()
+                } else {
+                    Address::ZERO
+                });
+            }
+            for address in addresses {
+                let indices = index.entry(address).or_default();
+                if indices.last() != Some(&i) {
+                    indices.push(i);
+                }
+            }
+        }
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use uniswap_sdk_core::prelude::Currency;
+    use uniswap_v3_sdk::prelude::{FeeAmount, NoTickDataProvider};
+
+    mod index_pools_by_currency {
+        use super::*;
+
+        #[test]
+        fn indexes_eth_and_weth_together() {
+            let eth_weth = Pool::new(
+                Currency::NativeCurrency(ETHER.clone()),
+                Currency::Token(WETH.clone()),
+                FeeAmount::LOWEST.into(),
+                10,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap();
+            let weth_usdc = Pool::new(
+                Currency::Token(WETH.clone()),
+                Currency::Token(USDC.clone()),
+                FeeAmount::MEDIUM.into(),
+                60,
+                Address::ZERO,
+                *SQRT_PRICE_1_1,
+                0,
+            )
+            .unwrap();
+            let index = index_pools_by_currency(&[USDC_DAI.clone(), eth_weth, weth_usdc]);
+            assert_eq!(index[&Address::ZERO], [1, 2]);
+            assert_eq!(index[&WETH.address], [1, 2]);
+            assert_eq!(index[&USDC.address], [0, 2]);
+            assert_eq!(index[&DAI.address], [0]);
+            assert_eq!(index.len(), 4);
+        }
+
+        #[test]
+        fn is_empty_without_pools() {
+            assert!(index_pools_by_currency::<NoTickDataProvider>(&[]).is_empty());
+        }
+    }
+}
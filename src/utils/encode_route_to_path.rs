@@ -14,18 +14,19 @@ where
     TP: TickDataProvider,
 {
     let mut path_keys: Vec<PathKey> = Vec::with_capacity(route.pools.len());
+    let hook_data = |i: usize| route.hook_data.get(i).cloned().unwrap_or_default();
     if exact_output {
         let mut output_currency = &route.path_output;
-        for pool in route.pools.iter().rev() {
-            let (next_currency, key) = get_next_path_key(pool, output_currency);
+        for (i, pool) in route.pools.iter().enumerate().rev() {
+            let (next_currency, key) = get_next_path_key(pool, output_currency, hook_data(i));
             path_keys.push(key);
             output_currency = next_currency;
         }
         path_keys.reverse();
     } else {
         let mut input_currency = &route.path_input;
-        for pool in &route.pools {
-            let (next_currency, key) = get_next_path_key(pool, input_currency);
+        for (i, pool) in route.pools.iter().enumerate() {
+            let (next_currency, key) = get_next_path_key(pool, input_currency, hook_data(i));
             path_keys.push(key);
             input_currency = next_currency;
         }
@@ -37,6 +38,7 @@ where
 fn get_next_path_key<'a, TInput, TP>(
     pool: &'a Pool<TP>,
     input_currency: &'a TInput,
+    hook_data: Bytes,
 ) -> (&'a Currency, PathKey)
 where
     TInput: BaseCurrency,
@@ -58,7 +60,7 @@ where
             fee: U256::from(pool.fee),
             tickSpacing: pool.tick_spacing.to_i24(),
             hooks: pool.hooks,
-            hookData: Bytes::default(),
+            hookData: hook_data,
         },
     )
 }
@@ -1,4 +1,8 @@
-use alloy_primitives::Address;
+use crate::prelude::Error;
+use alloy_primitives::{Address, B256, U160, U256};
+
+/// The mask of the low bits of a hook address that encode its permissions
+const HOOK_FLAGS_MASK: u64 = (1 << 14) - 1;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
@@ -37,6 +41,59 @@ pub struct HookPermissions {
     pub before_initialize: bool,
 }
 
+impl HookPermissions {
+    /// Returns the flags the low 14 bits of a hook address must equal for the hook to have
+    /// exactly these permissions, the inverse of [`permissions`]
+    #[inline]
+    #[must_use]
+    pub const fn required_flags(&self) -> U160 {
+        let mut flags = 0_u64;
+        if self.before_initialize {
+            flags |= 1 << HookOptions::BeforeInitialize as u64;
+        }
+        if self.after_initialize {
+            flags |= 1 << HookOptions::AfterInitialize as u64;
+        }
+        if self.before_add_liquidity {
+            flags |= 1 << HookOptions::BeforeAddLiquidity as u64;
+        }
+        if self.after_add_liquidity {
+            flags |= 1 << HookOptions::AfterAddLiquidity as u64;
+        }
+        if self.before_remove_liquidity {
+            flags |= 1 << HookOptions::BeforeRemoveLiquidity as u64;
+        }
+        if self.after_remove_liquidity {
+            flags |= 1 << HookOptions::AfterRemoveLiquidity as u64;
+        }
+        if self.before_swap {
+            flags |= 1 << HookOptions::BeforeSwap as u64;
+        }
+        if self.after_swap {
+            flags |= 1 << HookOptions::AfterSwap as u64;
+        }
+        if self.before_donate {
+            flags |= 1 << HookOptions::BeforeDonate as u64;
+        }
+        if self.after_donate {
+            flags |= 1 << HookOptions::AfterDonate as u64;
+        }
+        if self.before_swap_returns_delta {
+            flags |= 1 << HookOptions::BeforeSwapReturnsDelta as u64;
+        }
+        if self.after_swap_returns_delta {
+            flags |= 1 << HookOptions::AfterSwapReturnsDelta as u64;
+        }
+        if self.after_add_liquidity_returns_delta {
+            flags |= 1 << HookOptions::AfterAddLiquidityReturnsDelta as u64;
+        }
+        if self.after_remove_liquidity_returns_delta {
+            flags |= 1 << HookOptions::AfterRemoveLiquidityReturnsDelta as u64;
+        }
+        U160::from_limbs([flags, 0, 0])
+    }
+}
+
 #[inline]
 #[must_use]
 pub const fn permissions(address: Address) -> HookPermissions {
@@ -103,11 +160,56 @@ pub const fn has_donate_permissions(address: Address) -> bool {
         || has_permission(address, HookOptions::AfterDonate)
 }
 
+/// Whether every return delta flag of a hook address is set together with the flag of the hook
+/// returning it, e.g. `BeforeSwapReturnsDelta` with `BeforeSwap`, as the pool manager requires
+#[inline]
+#[must_use]
+pub const fn has_valid_delta_flags(address: Address) -> bool {
+    (!has_permission(address, HookOptions::BeforeSwapReturnsDelta)
+        || has_permission(address, HookOptions::BeforeSwap))
+        && (!has_permission(address, HookOptions::AfterSwapReturnsDelta)
+            || has_permission(address, HookOptions::AfterSwap))
+        && (!has_permission(address, HookOptions::AfterAddLiquidityReturnsDelta)
+            || has_permission(address, HookOptions::AfterAddLiquidity))
+        && (!has_permission(address, HookOptions::AfterRemoveLiquidityReturnsDelta)
+            || has_permission(address, HookOptions::AfterRemoveLiquidity))
+}
+
+/// Brute-forces CREATE2 salts until the hook deployed with them has exactly the requested
+/// permissions, i.e. the low 14 bits of its address equal [`HookPermissions::required_flags`]
+///
+/// ## Arguments
+///
+/// * `deployer`: The address of the CREATE2 deployer
+/// * `init_code_hash`: The keccak256 hash of the hook's init code, including constructor arguments
+/// * `permissions`: The permissions the hook must have
+/// * `max_iterations`: The maximum number of salts to try
+///
+/// returns: The salt and the resulting hook address
+#[inline]
+pub fn mine_hook_address(
+    deployer: Address,
+    init_code_hash: B256,
+    permissions: HookPermissions,
+    max_iterations: u64,
+) -> Result<(B256, Address), Error> {
+    let flags = permissions.required_flags();
+    let mask = U160::from(HOOK_FLAGS_MASK);
+    for i in 0..max_iterations {
+        let salt = B256::from(U256::from(i));
+        let address = deployer.create2(salt, init_code_hash);
+        if U160::from_be_bytes(address.0 .0) & mask == flags {
+            return Ok((salt, address));
+        }
+    }
+    Err(Error::HookAddressNotFound)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use alloc::{vec, vec::Vec};
-    use alloy_primitives::{address, U160};
+    use alloy_primitives::{address, b256};
     use once_cell::sync::Lazy;
 
     fn construct_hook_address(hook_options: Vec<HookOptions>) -> Address {
@@ -537,4 +639,91 @@ mod tests {
             assert!(!has_donate_permissions(*HOOK_AFTER_SWAP));
         }
     }
+    mod has_valid_delta_flags {
+        use super::*;
+
+        #[test]
+        fn delta_flags_with_their_hooks() {
+            assert!(has_valid_delta_flags(ALL_HOOKS_ADDRESS));
+            assert!(has_valid_delta_flags(EMPTY_HOOK_ADDRESS));
+            assert!(has_valid_delta_flags(construct_hook_address(vec![
+                HookOptions::BeforeSwap,
+                HookOptions::BeforeSwapReturnsDelta,
+            ])));
+        }
+
+        #[test]
+        fn delta_flags_without_their_hooks() {
+            assert!(!has_valid_delta_flags(*HOOK_BEFORE_SWAP_RETURNS_DELTA));
+            assert!(!has_valid_delta_flags(*HOOK_AFTER_SWAP_RETURNS_DELTA));
+            assert!(!has_valid_delta_flags(
+                *HOOK_AFTER_ADD_LIQUIDITY_RETURNS_DELTA
+            ));
+            assert!(!has_valid_delta_flags(
+                *HOOK_AFTER_REMOVE_LIQUIDITY_RETURNS_DELTA
+            ));
+        }
+    }
+
+    mod required_flags {
+        use super::*;
+
+        #[test]
+        fn empty_permissions() {
+            assert_eq!(HookPermissions::default().required_flags(), U160::ZERO);
+        }
+
+        #[test]
+        fn round_trips_through_permissions() {
+            for address in [
+                ALL_HOOKS_ADDRESS,
+                *HOOK_BEFORE_INITIALIZE,
+                *HOOK_AFTER_SWAP,
+                *HOOK_BEFORE_SWAP_RETURNS_DELTA,
+                construct_hook_address(vec![
+                    HookOptions::BeforeSwap,
+                    HookOptions::AfterSwap,
+                    HookOptions::AfterAddLiquidity,
+                ]),
+            ] {
+                assert_eq!(
+                    Address::from(permissions(address).required_flags()),
+                    address
+                );
+            }
+        }
+    }
+
+    mod mine_hook_address {
+        use super::*;
+
+        const DEPLOYER: Address = address!("4e59b44847b379578588920ca78fbf26c0b4956c");
+        const INIT_CODE_HASH: B256 =
+            b256!("0101010101010101010101010101010101010101010101010101010101010101");
+
+        #[test]
+        fn mines_address_with_requested_permissions() {
+            let requested = HookPermissions {
+                before_swap: true,
+                after_swap: true,
+                ..Default::default()
+            };
+            let (salt, hook) =
+                mine_hook_address(DEPLOYER, INIT_CODE_HASH, requested, 1_000_000).unwrap();
+            assert_eq!(DEPLOYER.create2(salt, INIT_CODE_HASH), hook);
+            assert_eq!(permissions(hook), requested);
+        }
+
+        #[test]
+        fn throws_if_iteration_cap_is_reached() {
+            let requested = HookPermissions {
+                before_initialize: true,
+                ..Default::default()
+            };
+            assert!(matches!(
+                mine_hook_address(DEPLOYER, INIT_CODE_HASH, requested, 0),
+                Err(Error::HookAddressNotFound)
+            ));
+        }
+    }
 }
@@ -64,3 +64,130 @@ pub fn price_to_closest_tick(price: &Price<Currency, Currency>) -> Result<I24, E
         tick
     })
 }
+
+/// Returns the usable tick range enclosing a band around a price, e.g. for placing a concentrated
+/// position
+///
+/// The prices `price * (1 - band)` and `price * (1 + band)` are converted to their closest ticks,
+/// which are then snapped outward to multiples of the tick spacing. Prices beyond the tick range
+/// are clamped to the usable extremes.
+///
+/// ## Arguments
+///
+/// * `base_currency`: the base currency of the price
+/// * `quote_currency`: the quote currency of the price
+/// * `price`: the raw amount of quote currency per raw amount of base currency
+/// * `band`: how far below and above `price` the range extends
+/// * `tick_spacing`: the tick spacing of the pool
+///
+/// ## Returns
+///
+/// The lower and upper ticks of the range
+#[inline]
+pub fn tick_range_for_price_band(
+    base_currency: Currency,
+    quote_currency: Currency,
+    price: &Fraction,
+    band: &Percent,
+    tick_spacing: i32,
+) -> Result<(i32, i32), Error> {
+    let sorted = sorts_before(&base_currency, &quote_currency)?;
+    let min_price = tick_to_price(base_currency.clone(), quote_currency.clone(), MIN_TICK)?;
+    let max_price = tick_to_price(base_currency.clone(), quote_currency.clone(), MAX_TICK)?;
+    let one = Percent::new(1, 1);
+    let mut closest_ticks = [0; 2];
+    for (closest_tick, factor) in closest_ticks
+        .iter_mut()
+        .zip([one.clone() - band.clone(), one + band.clone()])
+    {
+        let bound = price.clone() * factor.as_fraction();
+        let bound = Price::new(
+            base_currency.clone(),
+            quote_currency.clone(),
+            bound.denominator,
+            bound.numerator,
+        );
+        // the price of a tick increases with the tick if the currencies are sorted
+        let (below_min, above_max) = if sorted {
+            (bound <= min_price, bound >= max_price)
+        } else {
+            (bound >= min_price, bound <= max_price)
+        };
+        *closest_tick = if below_min {
+            MIN_TICK_I32
+        } else if above_max {
+            MAX_TICK_I32
+        } else {
+            price_to_closest_tick(&bound)?.as_i32()
+        };
+    }
+    let tick_lower = closest_ticks[0].min(closest_ticks[1]);
+    let tick_upper = closest_ticks[0].max(closest_ticks[1]);
+    let min_usable_tick = nearest_usable_tick(MIN_TICK_I32, tick_spacing);
+    let max_usable_tick = nearest_usable_tick(MAX_TICK_I32, tick_spacing);
+    let tick_lower = (tick_lower.div_euclid(tick_spacing) * tick_spacing).max(min_usable_tick);
+    let tick_upper = (-(-tick_upper).div_euclid(tick_spacing) * tick_spacing).min(max_usable_tick);
+    Ok((tick_lower, tick_upper))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    mod tick_range_for_price_band {
+        use super::*;
+
+        #[test]
+        fn encloses_the_band_with_sorted_currencies() {
+            let range = tick_range_for_price_band(
+                DAI.clone().into(),
+                USDC.clone().into(),
+                &Fraction::new(1, 1),
+                &Percent::new(10, 100),
+                60,
+            )
+            .unwrap();
+            assert_eq!(range, (-1080, 960));
+        }
+
+        #[test]
+        fn encloses_the_band_with_unsorted_currencies() {
+            let range = tick_range_for_price_band(
+                USDC.clone().into(),
+                DAI.clone().into(),
+                &Fraction::new(1, 1),
+                &Percent::new(10, 100),
+                60,
+            )
+            .unwrap();
+            assert_eq!(range, (-960, 1080));
+        }
+
+        #[test]
+        fn clamps_to_the_usable_extremes() {
+            let range = tick_range_for_price_band(
+                DAI.clone().into(),
+                USDC.clone().into(),
+                &Fraction::new(1, 1),
+                &Percent::new(100, 100),
+                60,
+            )
+            .unwrap();
+            assert_eq!(range.0, nearest_usable_tick(MIN_TICK_I32, 60));
+
+            let max_price = tick_to_price(DAI.clone().into(), USDC.clone().into(), MAX_TICK)
+                .unwrap()
+                .as_fraction();
+            let range = tick_range_for_price_band(
+                DAI.clone().into(),
+                USDC.clone().into(),
+                &max_price,
+                &Percent::new(50, 100),
+                60,
+            )
+            .unwrap();
+            assert_eq!(range.1, nearest_usable_tick(MAX_TICK_I32, 60));
+        }
+    }
+}
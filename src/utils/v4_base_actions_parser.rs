@@ -1,3 +1,5 @@
+#[cfg(doc)]
+use crate::prelude::V4Planner;
 use crate::prelude::{Actions, ActionsParams, Error};
 use alloc::vec::Vec;
 use alloy_primitives::Bytes;
@@ -11,15 +13,33 @@ pub struct V4RouterCall {
 
 #[inline]
 pub fn parse_calldata(calldata: &Bytes) -> Result<V4RouterCall, Error> {
-    let ActionsParams { actions, params } =
-        ActionsParams::abi_decode_validate(calldata.iter().as_slice())?;
     Ok(V4RouterCall {
-        actions: zip(actions, params)
-            .map(|(command, data)| Actions::abi_decode(command, &data))
-            .collect::<Result<Vec<Actions>, Error>>()?,
+        actions: decode_actions(calldata)?,
     })
 }
 
+/// Decodes the actions packed by [`V4Planner::finalize`], the inverse of encoding them
+///
+/// ## Arguments
+///
+/// * `data`: The ABI encoded `ActionsParams`, e.g. the `unlockData` of a `modifyLiquidities` call
+///
+/// Returns [`Error::ActionsLengthMismatch`] if the number of actions and params disagree.
+#[inline]
+pub fn decode_actions(data: &Bytes) -> Result<Vec<Actions>, Error> {
+    let ActionsParams { actions, params } =
+        ActionsParams::abi_decode_validate(data.iter().as_slice())?;
+    if actions.len() != params.len() {
+        return Err(Error::ActionsLengthMismatch {
+            actions: actions.len(),
+            params: params.len(),
+        });
+    }
+    zip(actions, params)
+        .map(|(command, data)| Actions::abi_decode(command, &data))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +137,51 @@ mod tests {
             assert_eq!(result.actions, vec![test]);
         }
     }
+
+    mod decode_actions {
+        use super::*;
+        use alloy_sol_types::SolValue;
+
+        #[test]
+        fn decodes_position_planner_actions() {
+            let mut planner = V4PositionPlanner::default();
+            planner.add_decrease(U256::from(1), AMOUNT, 0, 0, Bytes::default());
+            planner.add_take_pair(&DAI.clone(), &USDC.clone(), ADDRESS_ONE);
+            let actions = decode_actions(&planner.into_planner().finalize()).unwrap();
+            assert_eq!(
+                actions,
+                vec![
+                    Actions::DECREASE_LIQUIDITY(DecreaseLiquidityParams {
+                        tokenId: U256::from(1),
+                        liquidity: AMOUNT,
+                        amount0Min: 0,
+                        amount1Min: 0,
+                        hookData: Bytes::default(),
+                    }),
+                    Actions::TAKE_PAIR(TakePairParams {
+                        currency0: DAI.address,
+                        currency1: USDC.address,
+                        recipient: ADDRESS_ONE,
+                    }),
+                ]
+            );
+        }
+
+        #[test]
+        fn throws_if_lengths_disagree() {
+            let data: Bytes = ActionsParams {
+                actions: vec![Actions::CLOSE_CURRENCY(ADDRESS_ONE).command(); 2].into(),
+                params: vec![Actions::CLOSE_CURRENCY(ADDRESS_ONE).abi_encode()],
+            }
+            .abi_encode()
+            .into();
+            assert!(matches!(
+                decode_actions(&data),
+                Err(Error::ActionsLengthMismatch {
+                    actions: 2,
+                    params: 1
+                })
+            ));
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use crate::prelude::{encode_route_to_path, Error, Trade, *};
-use alloy_primitives::{Bytes, U256};
+use alloc::string::String;
+use alloy_primitives::{hex, Bytes, U256};
 use alloy_sol_types::SolValue;
 use num_traits::ToPrimitive;
 use uniswap_sdk_core::prelude::*;
@@ -51,6 +52,31 @@ impl Actions {
         discriminant(self)
     }
 
+    /// Returns the name of the action's opcode, e.g. `SWAP_EXACT_IN`
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::INCREASE_LIQUIDITY(_) => "INCREASE_LIQUIDITY",
+            Self::DECREASE_LIQUIDITY(_) => "DECREASE_LIQUIDITY",
+            Self::MINT_POSITION(_) => "MINT_POSITION",
+            Self::BURN_POSITION(_) => "BURN_POSITION",
+            Self::SWAP_EXACT_IN_SINGLE(_) => "SWAP_EXACT_IN_SINGLE",
+            Self::SWAP_EXACT_IN(_) => "SWAP_EXACT_IN",
+            Self::SWAP_EXACT_OUT_SINGLE(_) => "SWAP_EXACT_OUT_SINGLE",
+            Self::SWAP_EXACT_OUT(_) => "SWAP_EXACT_OUT",
+            Self::SETTLE(_) => "SETTLE",
+            Self::SETTLE_ALL(_) => "SETTLE_ALL",
+            Self::SETTLE_PAIR(_) => "SETTLE_PAIR",
+            Self::TAKE(_) => "TAKE",
+            Self::TAKE_ALL(_) => "TAKE_ALL",
+            Self::TAKE_PORTION(_) => "TAKE_PORTION",
+            Self::TAKE_PAIR(_) => "TAKE_PAIR",
+            Self::CLOSE_CURRENCY(_) => "CLOSE_CURRENCY",
+            Self::SWEEP(_) => "SWEEP",
+            Self::UNWRAP(_) => "UNWRAP",
+        }
+    }
+
     #[inline]
     pub fn abi_encode(&self) -> Bytes {
         match self {
@@ -76,10 +102,12 @@ impl Actions {
         .into()
     }
 
+    /// Decodes the params of an action, rejecting data with trailing bytes that the params don't
+    /// consume
     #[inline]
     pub fn abi_decode(command: u8, data: &Bytes) -> Result<Self, Error> {
         let data = data.iter().as_slice();
-        Ok(match command {
+        let action = match command {
             0x00 => Self::INCREASE_LIQUIDITY(IncreaseLiquidityParams::abi_decode_validate(data)?),
             0x01 => Self::DECREASE_LIQUIDITY(DecreaseLiquidityParams::abi_decode_validate(data)?),
             0x02 => Self::MINT_POSITION(MintPositionParams::abi_decode_validate(data)?),
@@ -101,7 +129,20 @@ impl Actions {
             0x14 => Self::SWEEP(SweepParams::abi_decode_validate(data)?),
             0x16 => Self::UNWRAP(U256::abi_decode_validate(data)?),
             _ => return Err(Error::InvalidAction(command)),
-        })
+        };
+        // the params are always canonically encoded, so any extra bytes are trailing garbage
+        if action.abi_encode().len() != data.len() {
+            return Err(Error::TrailingActionData(command));
+        }
+        Ok(action)
+    }
+}
+
+/// Formats the action compactly as its opcode name and hex command, e.g. `SWAP_EXACT_IN(0x07)`
+impl core::fmt::Display for Actions {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}({:#04x})", self.name(), self.command())
     }
 }
 
@@ -125,6 +166,32 @@ impl V4Planner {
         trade: &Trade<TInput, TOutput, TP>,
         slippage_tolerance: Option<Percent>,
     ) -> Result<&mut Self, Error>
+    where
+        TInput: BaseCurrency,
+        TOutput: BaseCurrency,
+        TP: TickDataProvider,
+    {
+        self.add_trade_with_amount_override(trade, slippage_tolerance, None)
+    }
+
+    /// Adds a swap action for a single-route trade, like [`Self::add_trade`], but encoding
+    /// `amount_override` as the `amountIn` (exact input) or `amountOut` (exact output) of the swap
+    /// instead of the trade's amount, e.g. for trades simulated elsewhere
+    ///
+    /// The slippage-bounded limit is still computed from the trade.
+    ///
+    /// ## Arguments
+    ///
+    /// * `trade`: The trade to swap
+    /// * `slippage_tolerance`: The slippage tolerance, required for exact output trades
+    /// * `amount_override`: The amount to encode as the primary amount of the swap, if any
+    #[inline]
+    pub fn add_trade_with_amount_override<TInput, TOutput, TP>(
+        &mut self,
+        trade: &Trade<TInput, TOutput, TP>,
+        slippage_tolerance: Option<Percent>,
+        amount_override: Option<u128>,
+    ) -> Result<&mut Self, Error>
     where
         TInput: BaseCurrency,
         TOutput: BaseCurrency,
@@ -140,12 +207,15 @@ impl V4Planner {
             );
         }
         assert_eq!(
-            trade.swaps.len(),
+            trade.swaps().len(),
             1,
             "Only accepts Trades with 1 swap (must break swaps into individual trades)"
         );
 
         let route = trade.route();
+        // `Route::new` only accepts connected paths, so moving between native and wrapped native
+        // mid-path always goes through a pool that pairs them (see
+        // [`Route::requires_mid_path_wrap`]) and is encoded as a regular hop.
         let currency_in = currency_address(&route.path_input);
         let currency_out = currency_address(&route.path_output);
         let path = encode_route_to_path(route, exact_output);
@@ -155,24 +225,32 @@ impl V4Planner {
                 Actions::SWAP_EXACT_OUT(SwapExactOutParams {
                     currencyOut: currency_out,
                     path,
-                    amountOut: trade.output_amount()?.quotient().to_u128().unwrap(),
-                    amountInMaximum: trade
-                        .maximum_amount_in(slippage_tolerance.unwrap_or_default(), None)?
-                        .quotient()
-                        .to_u128()
-                        .unwrap(),
+                    amountOut: match amount_override {
+                        Some(amount) => amount,
+                        None => to_u128(trade.output_amount()?.quotient(), "amountOut")?,
+                    },
+                    amountInMaximum: to_u128(
+                        trade
+                            .maximum_amount_in(slippage_tolerance.unwrap_or_default(), None)?
+                            .quotient(),
+                        "amountInMaximum",
+                    )?,
                 })
             } else {
                 Actions::SWAP_EXACT_IN(SwapExactInParams {
                     currencyIn: currency_in,
                     path,
-                    amountIn: trade.input_amount()?.quotient().to_u128().unwrap(),
+                    amountIn: match amount_override {
+                        Some(amount) => amount,
+                        None => to_u128(trade.input_amount()?.quotient(), "amountIn")?,
+                    },
                     amountOutMinimum: if let Some(slippage_tolerance) = slippage_tolerance {
-                        trade
-                            .minimum_amount_out(slippage_tolerance, None)?
-                            .quotient()
-                            .to_u128()
-                            .unwrap()
+                        to_u128(
+                            trade
+                                .minimum_amount_out(slippage_tolerance, None)?
+                                .quotient(),
+                            "amountOutMinimum",
+                        )?
                     } else {
                         0
                     },
@@ -181,6 +259,49 @@ impl V4Planner {
         ))
     }
 
+    /// Encodes an exact output trade together with its settlement: the swap, a `SETTLE_ALL` of the
+    /// input owed, capped at the maximum allowed by the slippage tolerance, and a take of the
+    /// output to the recipient
+    ///
+    /// Only the actual debt of the swap is settled, so no input is left over in the pool manager.
+    /// Native currency sent in excess of the input stays in the Universal Router; to refund it,
+    /// the caller must add a Universal Router `SWEEP` command after the swap, since the V4 router
+    /// doesn't support the `SWEEP` action.
+    ///
+    /// ## Arguments
+    ///
+    /// * `trade`: The exact output trade to encode
+    /// * `slippage_tolerance`: How much the input amount may exceed the quote
+    /// * `recipient`: The recipient of the output
+    ///
+    /// Returns [`Error::InvalidTradeType`] if the trade is not an exact output trade.
+    #[inline]
+    pub fn encode_exact_out_swap<TInput, TOutput, TP>(
+        &mut self,
+        trade: &Trade<TInput, TOutput, TP>,
+        slippage_tolerance: Percent,
+        recipient: Address,
+    ) -> Result<&mut Self, Error>
+    where
+        TInput: BaseCurrency,
+        TOutput: BaseCurrency,
+        TP: TickDataProvider,
+    {
+        if trade.trade_type != TradeType::ExactOutput {
+            return Err(Error::InvalidTradeType);
+        }
+        let max_amount_in = to_u128(
+            trade
+                .maximum_amount_in(slippage_tolerance.clone(), None)?
+                .quotient(),
+            "amountInMaximum",
+        )?;
+        self.add_trade(trade, Some(slippage_tolerance))?;
+        Ok(self
+            .add_settle_all(trade.input_currency(), U256::from(max_amount_in))
+            .add_take(trade.output_currency(), recipient, Some(OPEN_DELTA)))
+    }
+
     #[inline]
     pub fn add_settle(
         &mut self,
@@ -209,11 +330,101 @@ impl V4Planner {
         }))
     }
 
+    /// Settles the full debt of one currency from the user and takes the full credit of another to
+    /// the caller, e.g. to close an exact input single hop swap
+    ///
+    /// ## Note
+    ///
+    /// The deployed v4 router and position manager have no dedicated settle-take-pair action, so
+    /// this encodes a `SETTLE` of `settle_currency` followed by a `TAKE` of `take_currency`, both
+    /// of the open delta.
+    #[inline]
+    pub fn add_settle_take_pair(
+        &mut self,
+        settle_currency: &impl BaseCurrency,
+        take_currency: &impl BaseCurrency,
+    ) -> &mut Self {
+        self.add_settle(settle_currency, true, Some(OPEN_DELTA))
+            .add_take(take_currency, MSG_SENDER, Some(OPEN_DELTA))
+    }
+
+    #[inline]
+    pub fn add_settle_all(&mut self, currency: &impl BaseCurrency, max_amount: U256) -> &mut Self {
+        self.add_action(&Actions::SETTLE_ALL(SettleAllParams {
+            currency: currency_address(currency),
+            maxAmount: max_amount,
+        }))
+    }
+
+    #[inline]
+    pub fn add_take_all(&mut self, currency: &impl BaseCurrency, min_amount: U256) -> &mut Self {
+        self.add_action(&Actions::TAKE_ALL(TakeAllParams {
+            currency: currency_address(currency),
+            minAmount: min_amount,
+        }))
+    }
+
+    /// Takes a portion of the open delta of a currency in basis points, e.g. to pay a fee
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `bips` exceeds 10_000
+    #[inline]
+    pub fn add_take_portion(
+        &mut self,
+        currency: &impl BaseCurrency,
+        recipient: Address,
+        bips: u16,
+    ) -> &mut Self {
+        assert!(bips <= 10_000, "BIPS");
+        self.add_action(&Actions::TAKE_PORTION(TakePortionParams {
+            currency: currency_address(currency),
+            recipient,
+            bips: U256::from(bips),
+        }))
+    }
+
+    /// Closes the open delta of a currency, settling a debt or taking a credit, whichever
+    /// is owed
+    #[inline]
+    pub fn add_close_currency(&mut self, currency: &impl BaseCurrency) -> &mut Self {
+        self.add_action(&Actions::CLOSE_CURRENCY(currency_address(currency)))
+    }
+
     #[inline]
     pub fn add_unwrap(&mut self, amount: U256) -> &mut Self {
         self.add_action(&Actions::UNWRAP(amount))
     }
 
+    /// Decodes the amounts encoded in each swap action of the plan, in order
+    ///
+    /// Non-swap actions are skipped.
+    ///
+    /// returns: `(amountIn, amountOutMinimum)` for exact input swaps and
+    /// `(amountOut, amountInMaximum)` for exact output swaps
+    #[inline]
+    pub fn extract_swap_amounts(&self) -> Result<Vec<(u128, u128)>, Error> {
+        let mut amounts = Vec::new();
+        for (&command, data) in self.actions.iter().zip(&self.params) {
+            match Actions::abi_decode(command, data)? {
+                Actions::SWAP_EXACT_IN_SINGLE(params) => {
+                    amounts.push((params.amountIn, params.amountOutMinimum));
+                }
+                Actions::SWAP_EXACT_IN(params) => {
+                    amounts.push((params.amountIn, params.amountOutMinimum));
+                }
+                Actions::SWAP_EXACT_OUT_SINGLE(params) => {
+                    amounts.push((params.amountOut, params.amountInMaximum));
+                }
+                Actions::SWAP_EXACT_OUT(params) => {
+                    amounts.push((params.amountOut, params.amountInMaximum));
+                }
+                _ => {}
+            }
+        }
+        Ok(amounts)
+    }
+
     #[inline]
     #[must_use]
     pub fn finalize(self) -> Bytes {
@@ -224,6 +435,20 @@ impl V4Planner {
         .abi_encode()
         .into()
     }
+
+    /// Finalizes the planner like [`Self::finalize`], returning the encoded actions and params as
+    /// a `0x`-prefixed hex string
+    #[inline]
+    #[must_use]
+    pub fn finalize_hex(self) -> String {
+        hex::encode_prefixed(self.finalize())
+    }
+}
+
+/// Converts an amount to the `uint128` used by the router's swap params, returning
+/// [`Error::AmountOverflow`] with the name of the param if it doesn't fit.
+fn to_u128(amount: BigInt, field: &'static str) -> Result<u128, Error> {
+    amount.to_u128().ok_or(Error::AmountOverflow(field))
 }
 
 fn currency_address(currency: &impl BaseCurrency) -> Address {
@@ -281,6 +506,33 @@ mod tests {
         .unwrap()
     });
 
+    static ETH_WETH: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+        Pool::new_with_tick_data_provider(
+            ETHER.clone().into(),
+            WETH.clone().into(),
+            FeeAmount::MEDIUM.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            ONE_ETHER,
+            TICK_LIST.clone(),
+        )
+        .unwrap()
+    });
+    static ETH_USDC: Lazy<Pool<Vec<Tick>>> = Lazy::new(|| {
+        Pool::new_with_tick_data_provider(
+            ETHER.clone().into(),
+            USDC.clone().into(),
+            FeeAmount::MEDIUM.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            ONE_ETHER,
+            TICK_LIST.clone(),
+        )
+        .unwrap()
+    });
+
     #[test]
     fn test_discriminant() {
         assert_eq!(
@@ -333,6 +585,35 @@ mod tests {
         assert_eq!(discriminant(&Actions::UNWRAP(U256::ZERO)), 0x16);
     }
 
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            alloc::format!("{}", Actions::SWAP_EXACT_IN(Default::default())),
+            "SWAP_EXACT_IN(0x07)"
+        );
+        assert_eq!(
+            alloc::format!("{}", Actions::INCREASE_LIQUIDITY(Default::default())),
+            "INCREASE_LIQUIDITY(0x00)"
+        );
+        assert_eq!(
+            alloc::format!("{}", Actions::UNWRAP(U256::ZERO)),
+            "UNWRAP(0x16)"
+        );
+        let actions = [
+            Actions::SETTLE_PAIR(Default::default()),
+            Actions::MINT_POSITION(Default::default()),
+            Actions::SWEEP(Default::default()),
+        ];
+        let names: Vec<_> = actions
+            .iter()
+            .map(|action| alloc::format!("{action}"))
+            .collect();
+        assert_eq!(
+            names,
+            ["SETTLE_PAIR(0x0d)", "MINT_POSITION(0x02)", "SWEEP(0x14)"]
+        );
+    }
+
     #[test]
     fn test_add_action_encode_v4_exact_in_single_swap() {
         let mut planner = V4Planner::default();
@@ -423,6 +704,236 @@ mod tests {
         }
     }
 
+    mod add_settle_take_pair {
+        use super::*;
+
+        #[test]
+        fn settles_one_currency_and_takes_the_other() {
+            let mut planner = V4Planner::default();
+            planner.add_settle_take_pair(&DAI.clone(), &USDC.clone());
+            assert_eq!(planner.actions, vec![0x0b, 0x0e]);
+            assert_eq!(
+                Actions::abi_decode(planner.actions[0], &planner.params[0]).unwrap(),
+                Actions::SETTLE(SettleParams {
+                    currency: DAI.address,
+                    amount: OPEN_DELTA,
+                    payerIsUser: true,
+                })
+            );
+            assert_eq!(
+                Actions::abi_decode(planner.actions[1], &planner.params[1]).unwrap(),
+                Actions::TAKE(TakeParams {
+                    currency: USDC.address,
+                    recipient: MSG_SENDER,
+                    amount: OPEN_DELTA,
+                })
+            );
+        }
+    }
+
+    mod add_settle_all {
+        use super::*;
+        use alloy_primitives::uint;
+
+        #[test]
+        fn completes_v4_settle_all() {
+            let mut planner = V4Planner::default();
+            planner.add_settle_all(&DAI.clone(), uint!(8_U256));
+            assert_eq!(planner.actions, vec![0x0c]);
+            assert_eq!(
+                planner.params[0],
+                hex!("0000000000000000000000006b175474e89094c44da98b954eedeac495271d0f0000000000000000000000000000000000000000000000000000000000000008").to_vec()
+            );
+        }
+
+        #[test]
+        fn completes_v4_settle_all_with_native_currency() {
+            let mut planner = V4Planner::default();
+            planner.add_settle_all(&ETHER.clone(), uint!(8_U256));
+            assert_eq!(planner.actions, vec![0x0c]);
+            assert_eq!(
+                planner.params[0],
+                hex!("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008").to_vec()
+            );
+        }
+    }
+
+    mod add_take_all {
+        use super::*;
+        use alloy_primitives::uint;
+
+        #[test]
+        fn completes_v4_take_all() {
+            let mut planner = V4Planner::default();
+            planner.add_take_all(&DAI.clone(), uint!(8_U256));
+            assert_eq!(planner.actions, vec![0x0f]);
+            assert_eq!(
+                planner.params[0],
+                hex!("0000000000000000000000006b175474e89094c44da98b954eedeac495271d0f0000000000000000000000000000000000000000000000000000000000000008").to_vec()
+            );
+        }
+
+        #[test]
+        fn completes_v4_take_all_with_native_currency() {
+            let mut planner = V4Planner::default();
+            planner.add_take_all(&ETHER.clone(), uint!(8_U256));
+            assert_eq!(planner.actions, vec![0x0f]);
+            assert_eq!(
+                planner.params[0],
+                hex!("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008").to_vec()
+            );
+        }
+    }
+
+    mod add_take_portion {
+        use super::*;
+        use alloy_primitives::address;
+
+        #[test]
+        fn completes_v4_take_portion() {
+            let mut planner = V4Planner::default();
+            planner.add_take_portion(
+                &DAI.clone(),
+                address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                25,
+            );
+            assert_eq!(planner.actions, vec![0x10]);
+            assert_eq!(
+                planner.params[0],
+                hex!("0000000000000000000000006b175474e89094c44da98b954eedeac495271d0f000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0000000000000000000000000000000000000000000000000000000000000019").to_vec()
+            );
+        }
+
+        #[test]
+        fn chains_with_other_actions() {
+            let mut planner = V4Planner::default();
+            planner
+                .add_take_portion(
+                    &ETHER.clone(),
+                    address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                    10_000,
+                )
+                .add_take(&ETHER.clone(), MSG_SENDER, None);
+            assert_eq!(planner.actions, vec![0x10, 0x0e]);
+            assert_eq!(
+                planner.params[0],
+                hex!("0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0000000000000000000000000000000000000000000000000000000000002710").to_vec()
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "BIPS")]
+        fn throws_if_bips_exceeds_10000() {
+            V4Planner::default().add_take_portion(
+                &DAI.clone(),
+                address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                10_001,
+            );
+        }
+    }
+
+    mod add_close_currency {
+        use super::*;
+        use alloy_sol_types::SolType;
+        use core::iter::zip;
+
+        #[test]
+        fn completes_v4_close_currency() {
+            let mut planner = V4Planner::default();
+            planner.add_close_currency(&DAI.clone());
+            assert_eq!(planner.actions, vec![0x12]);
+            assert_eq!(
+                planner.params[0],
+                hex!("0000000000000000000000006b175474e89094c44da98b954eedeac495271d0f").to_vec()
+            );
+        }
+
+        #[test]
+        fn round_trips_through_finalize() {
+            let mut planner = V4Planner::default();
+            planner
+                .add_settle(&DAI.clone(), true, None)
+                .add_take(&USDC.clone(), MSG_SENDER, None)
+                .add_close_currency(&DAI.clone())
+                .add_close_currency(&ETHER.clone());
+            let expected = vec![
+                Actions::SETTLE(SettleParams {
+                    currency: DAI.address(),
+                    amount: U256::ZERO,
+                    payerIsUser: true,
+                }),
+                Actions::TAKE(TakeParams {
+                    currency: USDC.address(),
+                    recipient: MSG_SENDER,
+                    amount: U256::ZERO,
+                }),
+                Actions::CLOSE_CURRENCY(DAI.address()),
+                Actions::CLOSE_CURRENCY(Address::ZERO),
+            ];
+
+            let ActionsParams { actions, params } =
+                ActionsParams::abi_decode_validate(&planner.finalize()).unwrap();
+            let decoded = zip(actions, params)
+                .map(|(command, data)| Actions::abi_decode(command, &data))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, expected);
+        }
+    }
+
+    mod finalize_hex {
+        use super::*;
+
+        #[test]
+        fn round_trips_to_finalize() {
+            let mut planner = V4Planner::default();
+            planner
+                .add_settle(&DAI.clone(), true, None)
+                .add_take(&USDC.clone(), MSG_SENDER, None);
+            let finalized = planner.clone().finalize();
+
+            let finalized_hex = planner.finalize_hex();
+            assert!(finalized_hex.starts_with("0x"));
+            assert_eq!(Bytes::from(hex::decode(finalized_hex).unwrap()), finalized);
+        }
+    }
+
+    mod abi_decode {
+        use super::*;
+
+        #[test]
+        fn decodes_valid_action() {
+            let action = Actions::CLOSE_CURRENCY(DAI.address());
+            assert_eq!(
+                Actions::abi_decode(0x12, &action.abi_encode()).unwrap(),
+                action
+            );
+        }
+
+        #[test]
+        fn throws_for_trailing_data() {
+            let action = Actions::TAKE_PAIR(TakePairParams {
+                currency0: DAI.address(),
+                currency1: USDC.address(),
+                recipient: MSG_SENDER,
+            });
+            let mut data = action.abi_encode().to_vec();
+            data.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+            assert!(matches!(
+                Actions::abi_decode(0x11, &data.into()),
+                Err(Error::TrailingActionData(0x11))
+            ));
+        }
+
+        #[test]
+        fn throws_for_unsupported_action() {
+            assert!(matches!(
+                Actions::abi_decode(0x15, &Bytes::default()),
+                Err(Error::InvalidAction(0x15))
+            ));
+        }
+    }
+
     mod add_unwrap {
         use super::*;
         use alloy_primitives::uint;
@@ -439,6 +950,124 @@ mod tests {
         }
     }
 
+    mod extract_swap_amounts {
+        use super::*;
+        use crate::{create_route, trade_from_route};
+
+        #[tokio::test]
+        async fn extracts_amounts_of_each_swap() {
+            let slippage_tolerance = Percent::new(5, 100);
+            let exact_in = trade_from_route!(
+                create_route!(DAI_USDC, USDC_WETH; DAI, WETH),
+                currency_amount!(DAI, ONE_ETHER),
+                TradeType::ExactInput
+            );
+            let exact_out = trade_from_route!(
+                create_route!(DAI_WETH, DAI, WETH),
+                currency_amount!(WETH, 1_000_000),
+                TradeType::ExactOutput
+            );
+            let mut planner = V4Planner::default();
+            planner
+                .add_trade(&exact_in, Some(slippage_tolerance.clone()))
+                .unwrap();
+            planner.add_settle(&DAI.clone(), true, None);
+            planner
+                .add_trade(&exact_out, Some(slippage_tolerance.clone()))
+                .unwrap();
+
+            let amount_out_minimum = exact_in
+                .minimum_amount_out(slippage_tolerance.clone(), None)
+                .unwrap()
+                .quotient()
+                .to_u128()
+                .unwrap();
+            let amount_in_maximum = exact_out
+                .maximum_amount_in(slippage_tolerance, None)
+                .unwrap()
+                .quotient()
+                .to_u128()
+                .unwrap();
+            assert_eq!(
+                planner.extract_swap_amounts().unwrap(),
+                vec![
+                    (ONE_ETHER, amount_out_minimum),
+                    (1_000_000, amount_in_maximum)
+                ]
+            );
+        }
+
+        #[test]
+        fn ignores_non_swap_actions() {
+            let mut planner = V4Planner::default();
+            planner.add_settle(&DAI.clone(), true, None);
+            planner.add_take_all(&USDC.clone(), U256::ZERO);
+            assert!(planner.extract_swap_amounts().unwrap().is_empty());
+        }
+    }
+
+    mod encode_exact_out_swap {
+        use super::*;
+        use crate::{create_route, trade_from_route};
+        use alloy_primitives::address;
+
+        const RECIPIENT: Address = address!("000000000000000000000000000000000000000c");
+
+        #[tokio::test]
+        async fn settles_swap_debt_and_takes_output() {
+            let route = create_route!(DAI_USDC, USDC_WETH; DAI, WETH);
+            let slippage_tolerance = Percent::new(5, 100);
+            let trade = trade_from_route!(
+                route,
+                currency_amount!(WETH, ONE_ETHER),
+                TradeType::ExactOutput
+            );
+            let mut planner = V4Planner::default();
+            planner
+                .encode_exact_out_swap(&trade, slippage_tolerance.clone(), RECIPIENT)
+                .unwrap();
+
+            assert_eq!(planner.actions, vec![0x09, 0x0c, 0x0e]);
+            let (_, max_amount_in) = planner.extract_swap_amounts().unwrap()[0];
+            let actions: Vec<Actions> = planner
+                .actions
+                .iter()
+                .zip(&planner.params)
+                .map(|(&command, data)| Actions::abi_decode(command, data).unwrap())
+                .collect();
+            assert_eq!(
+                actions[1..],
+                [
+                    Actions::SETTLE_ALL(SettleAllParams {
+                        currency: DAI.address,
+                        maxAmount: U256::from(max_amount_in),
+                    }),
+                    Actions::TAKE(TakeParams {
+                        currency: WETH.address,
+                        recipient: RECIPIENT,
+                        amount: OPEN_DELTA,
+                    }),
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn throws_for_exact_input_trades() {
+            let route = create_route!(DAI_USDC, USDC_WETH; DAI, WETH);
+            let trade = trade_from_route!(
+                route,
+                currency_amount!(DAI, ONE_ETHER),
+                TradeType::ExactInput
+            );
+            let mut planner = V4Planner::default();
+            assert!(matches!(
+                planner.encode_exact_out_swap(&trade, Percent::new(5, 100), RECIPIENT),
+                Err(Error::InvalidTradeType)
+            ));
+            assert!(planner.actions.is_empty());
+        }
+    }
+
     mod add_trade {
         use super::*;
         use crate::{create_route, trade_from_route};
@@ -531,6 +1160,47 @@ mod tests {
             );
         }
 
+        #[test]
+        fn completes_v4_exact_in_swap_through_mid_path_eth_weth_pool() {
+            let route = create_route!(DAI_WETH, ETH_WETH, ETH_USDC; DAI, USDC);
+            assert!(route.requires_mid_path_wrap());
+            let path = encode_route_to_path(&route, false);
+            let trade = Trade::create_unchecked_trade(
+                route,
+                currency_amount!(DAI, ONE_ETHER),
+                currency_amount!(USDC, ONE_ETHER),
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            let mut planner = V4Planner::default();
+            planner.add_trade(&trade, None).unwrap();
+
+            assert_eq!(planner.actions, vec![0x07]);
+            assert_eq!(
+                planner.params[0],
+                Actions::SWAP_EXACT_IN(SwapExactInParams {
+                    currencyIn: DAI.address,
+                    path,
+                    amountIn: ONE_ETHER,
+                    amountOutMinimum: 0,
+                })
+                .abi_encode()
+            );
+        }
+
+        #[test]
+        fn rejects_route_that_wraps_between_hops() {
+            // without the ETH/WETH pool, WETH would have to be unwrapped implicitly
+            assert!(matches!(
+                Route::new(
+                    vec![DAI_WETH.clone(), ETH_USDC.clone()],
+                    DAI.clone(),
+                    USDC.clone()
+                ),
+                Err(Error::DisconnectedPath)
+            ));
+        }
+
         #[tokio::test]
         #[should_panic(expected = "ExactOut requires slippageTolerance")]
         async fn throws_error_if_adding_exact_out_trade_without_slippage_tolerance() {
@@ -543,6 +1213,115 @@ mod tests {
             V4Planner::default().add_trade(&trade, None).unwrap();
         }
 
+        #[tokio::test]
+        async fn encodes_amount_override() {
+            let slippage_tolerance = Percent::new(5, 100);
+            let exact_in = trade_from_route!(
+                create_route!(DAI_USDC, USDC_WETH; DAI, WETH),
+                currency_amount!(DAI, ONE_ETHER),
+                TradeType::ExactInput
+            );
+            let exact_out = trade_from_route!(
+                create_route!(DAI_WETH, DAI, WETH),
+                currency_amount!(WETH, 1_000_000),
+                TradeType::ExactOutput
+            );
+            let mut planner = V4Planner::default();
+            planner
+                .add_trade_with_amount_override(
+                    &exact_in,
+                    Some(slippage_tolerance.clone()),
+                    Some(12345),
+                )
+                .unwrap()
+                .add_trade_with_amount_override(
+                    &exact_out,
+                    Some(slippage_tolerance.clone()),
+                    Some(678),
+                )
+                .unwrap();
+
+            let Actions::SWAP_EXACT_IN(exact_in_params) =
+                Actions::abi_decode(planner.actions[0], &planner.params[0]).unwrap()
+            else {
+                panic!("expected SWAP_EXACT_IN");
+            };
+            assert_eq!(exact_in_params.amountIn, 12345);
+            assert_eq!(
+                exact_in_params.amountOutMinimum,
+                exact_in
+                    .minimum_amount_out(slippage_tolerance.clone(), None)
+                    .unwrap()
+                    .quotient()
+                    .to_u128()
+                    .unwrap()
+            );
+
+            let Actions::SWAP_EXACT_OUT(exact_out_params) =
+                Actions::abi_decode(planner.actions[1], &planner.params[1]).unwrap()
+            else {
+                panic!("expected SWAP_EXACT_OUT");
+            };
+            assert_eq!(exact_out_params.amountOut, 678);
+            assert_eq!(
+                exact_out_params.amountInMaximum,
+                exact_out
+                    .maximum_amount_in(slippage_tolerance, None)
+                    .unwrap()
+                    .quotient()
+                    .to_u128()
+                    .unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn without_amount_override_matches_add_trade() {
+            let trade = trade_from_route!(
+                create_route!(DAI_USDC, USDC_WETH; DAI, WETH),
+                currency_amount!(DAI, ONE_ETHER),
+                TradeType::ExactInput
+            );
+            let mut planner = V4Planner::default();
+            planner.add_trade(&trade, None).unwrap();
+            let mut override_planner = V4Planner::default();
+            override_planner
+                .add_trade_with_amount_override(&trade, None, None)
+                .unwrap();
+            assert_eq!(planner, override_planner);
+        }
+
+        #[test]
+        fn throws_error_if_amount_in_overflows_u128() {
+            let route = create_route!(DAI_USDC, USDC_WETH; DAI, WETH);
+            let trade = Trade::create_unchecked_trade(
+                route,
+                currency_amount!(DAI, BigInt::from(u128::MAX) + BigInt::from(1)),
+                currency_amount!(WETH, ONE_ETHER),
+                TradeType::ExactInput,
+            )
+            .unwrap();
+            assert!(matches!(
+                V4Planner::default().add_trade(&trade, None),
+                Err(Error::AmountOverflow("amountIn"))
+            ));
+        }
+
+        #[test]
+        fn throws_error_if_amount_in_maximum_overflows_u128() {
+            let route = create_route!(DAI_USDC, USDC_WETH; DAI, WETH);
+            let trade = Trade::create_unchecked_trade(
+                route,
+                currency_amount!(DAI, u128::MAX),
+                currency_amount!(WETH, ONE_ETHER),
+                TradeType::ExactOutput,
+            )
+            .unwrap();
+            assert!(matches!(
+                V4Planner::default().add_trade(&trade, Some(Percent::new(5, 100))),
+                Err(Error::AmountOverflow("amountInMaximum"))
+            ));
+        }
+
         #[tokio::test]
         #[should_panic(
             expected = "Only accepts Trades with 1 swap (must break swaps into individual trades)"
@@ -1,13 +1,33 @@
-use crate::prelude::*;
+use crate::prelude::{Error, *};
+use alloc::vec::Vec;
 use alloy_primitives::{Address, Bytes, U256};
-use derive_more::{Deref, DerefMut};
-use uniswap_sdk_core::prelude::BaseCurrency;
-use uniswap_v3_sdk::prelude::{TickDataProvider, TickIndex};
+use derive_more::{Deref, DerefMut, From};
+use uniswap_sdk_core::prelude::{BaseCurrency, Percent};
+use uniswap_v3_sdk::prelude::{MintAmounts, TickDataProvider, TickIndex};
 
-#[derive(Clone, Debug, Default, PartialEq, Deref, DerefMut)]
+#[derive(Clone, Debug, Default, PartialEq, Deref, DerefMut, From)]
 pub struct V4PositionPlanner(pub V4Planner);
 
+impl From<V4PositionPlanner> for V4Planner {
+    #[inline]
+    fn from(planner: V4PositionPlanner) -> Self {
+        planner.0
+    }
+}
+
 impl V4PositionPlanner {
+    /// Returns the inner planner
+    #[inline]
+    pub const fn as_planner(&self) -> &V4Planner {
+        &self.0
+    }
+
+    /// Consumes the position planner and returns the inner planner with its actions
+    #[inline]
+    pub fn into_planner(self) -> V4Planner {
+        self.0
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn add_mint<TP: TickDataProvider>(
@@ -33,6 +53,68 @@ impl V4PositionPlanner {
         }));
     }
 
+    /// Appends a mint for each position, then a single settle for each distinct currency pair,
+    /// followed by a single sweep of any excess native currency back to the sender
+    ///
+    /// `SETTLE_PAIR` pays the full debt of both currencies accrued so far, so positions in pools
+    /// of the same pair are settled together after all the mints.
+    ///
+    /// ## Arguments
+    ///
+    /// * `positions`: The positions to mint
+    /// * `slippage_tolerance`: Tolerance of unfavorable slippage from the current price
+    /// * `recipient`: The owner of the minted positions
+    /// * `hook_data`: The hook data passed to each mint
+    #[inline]
+    pub fn add_mints<TP: TickDataProvider>(
+        &mut self,
+        positions: &mut [Position<TP>],
+        slippage_tolerance: &Percent,
+        recipient: Address,
+        hook_data: &Bytes,
+    ) -> Result<&mut Self, Error> {
+        let mut native_currency = None;
+        let mut settle_pairs: Vec<(Address, Address)> = Vec::new();
+        for position in positions.iter_mut() {
+            let MintAmounts {
+                amount0: amount0_max,
+                amount1: amount1_max,
+            } = position.mint_amounts_with_slippage(slippage_tolerance)?;
+            self.add_mint(
+                &position.pool,
+                position.tick_lower,
+                position.tick_upper,
+                U256::from(position.liquidity),
+                u128::try_from(amount0_max).map_err(|_| Error::AmountOverflow("amount0Max"))?,
+                u128::try_from(amount1_max).map_err(|_| Error::AmountOverflow("amount1Max"))?,
+                recipient,
+                hook_data.clone(),
+            );
+            let pair = (
+                to_address(&position.pool.currency0),
+                to_address(&position.pool.currency1),
+            );
+            if !settle_pairs.contains(&pair) {
+                settle_pairs.push(pair);
+            }
+            // native currency will always be currency0 in v4
+            if position.pool.currency0.is_native() {
+                native_currency = Some(position.pool.currency0.clone());
+            }
+        }
+        for (currency0, currency1) in settle_pairs {
+            self.add_action(&Actions::SETTLE_PAIR(SettlePairParams {
+                currency0,
+                currency1,
+            }));
+        }
+        // Any sweeping must happen after all the settling
+        if let Some(native_currency) = native_currency {
+            self.add_sweep(&native_currency, MSG_SENDER);
+        }
+        Ok(self)
+    }
+
     #[inline]
     pub fn add_increase(
         &mut self,
@@ -118,4 +200,169 @@ impl V4PositionPlanner {
             recipient,
         }));
     }
+
+    /// Returns the size in bytes of the `modifyLiquidities` calldata that submits the planned
+    /// actions
+    #[inline]
+    #[must_use]
+    pub fn estimated_calldata_bytes(&self) -> usize {
+        encode_modify_liquidities(self.0.clone().finalize(), U256::ZERO).len()
+    }
+
+    /// Checks the planned actions against a calldata size limit before they are submitted
+    ///
+    /// ## Arguments
+    ///
+    /// * `byte_limit`: The maximum size in bytes of the `modifyLiquidities` calldata
+    ///
+    /// ## Returns
+    ///
+    /// [`Error::CalldataTooLarge`] if [`Self::estimated_calldata_bytes`] exceeds `byte_limit`
+    #[inline]
+    pub fn warn_if_exceeds(&self, byte_limit: usize) -> Result<(), Error> {
+        let size = self.estimated_calldata_bytes();
+        if size > byte_limit {
+            return Err(Error::CalldataTooLarge {
+                size,
+                limit: byte_limit,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use alloy_primitives::address;
+    use uniswap_v3_sdk::prelude::FeeAmount;
+
+    #[test]
+    fn add_mints_settles_each_pair_once_after_the_mints() {
+        let dai_usdc = Pool::new(
+            DAI.clone().into(),
+            USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            ONE_ETHER,
+        )
+        .unwrap();
+        let eth_usdc = Pool::new(
+            ETHER.clone().into(),
+            USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            ONE_ETHER,
+        )
+        .unwrap();
+        let mut positions = [
+            Position::new(dai_usdc.clone(), ONE_ETHER, -100, 100),
+            Position::new(eth_usdc, ONE_ETHER, -200, 200),
+            Position::new(dai_usdc, ONE_ETHER, -300, 300),
+        ];
+        let recipient = address!("000000000000000000000000000000000000000c");
+
+        let mut planner = V4PositionPlanner::default();
+        planner
+            .add_mints(
+                &mut positions,
+                &Percent::new(1, 100),
+                recipient,
+                &Bytes::default(),
+            )
+            .unwrap();
+
+        assert_eq!(planner.actions, vec![0x02, 0x02, 0x02, 0x0d, 0x0d, 0x14]);
+        assert_eq!(planner.params.len(), 6);
+        for (i, position) in positions.iter().enumerate() {
+            let Actions::MINT_POSITION(params) =
+                Actions::abi_decode(0x02, &planner.params[i]).unwrap()
+            else {
+                unreachable!()
+            };
+            assert_eq!(params.poolKey, position.pool.pool_key);
+            assert_eq!(params.owner, recipient);
+        }
+        for (i, position) in positions[..2].iter().enumerate() {
+            assert_eq!(
+                Actions::abi_decode(0x0d, &planner.params[3 + i]).unwrap(),
+                Actions::SETTLE_PAIR(SettlePairParams {
+                    currency0: to_address(&position.pool.currency0),
+                    currency1: to_address(&position.pool.currency1),
+                })
+            );
+        }
+        assert_eq!(
+            Actions::abi_decode(0x14, &planner.params[5]).unwrap(),
+            Actions::SWEEP(SweepParams {
+                currency: Address::ZERO,
+                recipient: MSG_SENDER,
+            })
+        );
+    }
+
+    #[test]
+    fn estimates_calldata_bytes_of_many_mints() {
+        let pool = Pool::new(
+            DAI.clone().into(),
+            USDC.clone().into(),
+            FeeAmount::LOW.into(),
+            10,
+            Address::ZERO,
+            *SQRT_PRICE_1_1,
+            ONE_ETHER,
+        )
+        .unwrap();
+        let recipient = address!("000000000000000000000000000000000000000c");
+
+        let mut planner = V4PositionPlanner::default();
+        let empty_size = planner.estimated_calldata_bytes();
+        for i in 1..=100 {
+            planner.add_mint(
+                &pool,
+                -10 * i,
+                10 * i,
+                U256::from(ONE_ETHER),
+                u128::MAX,
+                u128::MAX,
+                recipient,
+                Bytes::default(),
+            );
+            planner.add_settle_pair(&pool.currency0, &pool.currency1);
+        }
+
+        let size = planner.estimated_calldata_bytes();
+        assert!(size > empty_size);
+        assert_eq!(
+            size,
+            encode_modify_liquidities(planner.clone().into_planner().finalize(), U256::ZERO).len()
+        );
+        assert!(planner.warn_if_exceeds(size).is_ok());
+        assert!(matches!(
+            planner.warn_if_exceeds(size - 1),
+            Err(Error::CalldataTooLarge { size: s, limit }) if s == size && limit == size - 1
+        ));
+    }
+
+    #[test]
+    fn converts_to_and_from_planner_preserving_actions() {
+        let mut planner = V4Planner::default();
+        planner.add_settle(&ETHER.clone(), true, None);
+        planner.add_take(&USDC.clone(), MSG_SENDER, None);
+        let expected = planner.clone();
+
+        let mut position_planner = V4PositionPlanner::from(planner);
+        assert_eq!(position_planner.as_planner(), &expected);
+        position_planner.add_settle_pair(&ETHER.clone(), &USDC.clone());
+
+        let planner: V4Planner = position_planner.clone().into();
+        assert_eq!(planner.actions, vec![0x0b, 0x0e, 0x0d]);
+        assert_eq!(planner.params[..2], expected.params[..]);
+        assert_eq!(position_planner.into_planner(), planner);
+    }
 }